@@ -1,47 +1,105 @@
 use crate::cli::{Args, GraphQCommand};
-use crate::cmds::{QueryBehaviour, handle_console_cmd, handle_query_cmd};
+use crate::cmds::{LoadTestConfig, QueryBehaviour, handle_console_cmd, handle_query_cmd};
+use crate::config::{get_config_file_path, get_profile};
+use crate::domain::{
+    DisplayOptions, OutputFormat, ParamSets, QueryParams, ResultsFormat, WriteOptions,
+};
 use crate::error::AppError;
 use crate::view::ConsoleConfig;
-use clap::Parser;
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches};
 use etcetera::BaseStrategy;
+use std::io::IsTerminal;
 
 pub async fn run() -> Result<(), AppError> {
     let xdg = etcetera::choose_base_strategy()?;
     crate::logging::setup(&xdg)?;
-    let args = Args::parse();
+    let matches = Args::command().get_matches();
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
 
     if args.debug {
         print!("DEBUG INFO\n{args}");
         return Ok(());
     }
 
+    let profile = match &args.profile {
+        Some(name) => Some(
+            get_profile(get_config_file_path(&xdg), name).map_err(AppError::CouldntLoadProfile)?,
+        ),
+        None => None,
+    };
+
+    // values from the profile only apply when the corresponding flag wasn't passed explicitly
+    let is_default = |id: &str| {
+        matches
+            .subcommand()
+            .and_then(|(_, m)| m.value_source(id))
+            .is_none_or(|s| s == ValueSource::DefaultValue)
+    };
+    let profile_results_dir = profile
+        .as_ref()
+        .and_then(|p| p.results_dir.clone())
+        .filter(|_| is_default("results_directory"));
+    let profile_results_format = profile
+        .as_ref()
+        .and_then(|p| p.results_format.clone())
+        .filter(|_| is_default("results_format"));
+
     match args.command {
         GraphQCommand::Console {
             page_results,
             write_results,
             results_directory,
             results_format,
+            results_compression,
+            flatten,
         } => {
             let console_config = ConsoleConfig {
                 page_results,
                 write_results,
-                results_directory,
-                results_format,
+                results_directory: profile_results_dir.unwrap_or(results_directory),
+                results_format: profile_results_format.unwrap_or(results_format),
+                write_options: WriteOptions {
+                    compression: results_compression,
+                    flatten,
+                },
+                output_format: OutputFormat::Table,
+                display: DisplayOptions::default(),
                 history_file_path: xdg.data_dir().join("grafq").join("history.txt"),
+                config_file_path: get_config_file_path(&xdg),
             };
 
-            handle_console_cmd(console_config).await?;
+            handle_console_cmd(console_config, profile.as_ref()).await?;
         }
         GraphQCommand::Query {
-            query,
+            queries,
+            params,
+            params_file,
             page_results,
             benchmark,
             bench_num_runs,
             bench_num_warmup_runs,
+            bench_params,
+            bench_params_order,
+            concurrency,
+            duration,
+            rate,
+            bench_output,
+            bench_baseline,
+            bench_threshold,
+            explain,
             print_query,
             write_results,
             results_directory,
             results_format,
+            results_compression,
+            flatten,
+            output,
+            format,
+            expanded,
+            max_column_width,
+            overflow,
+            max_rows,
         } => {
             if benchmark && write_results {
                 return Err(AppError::InvalidCLIUsage(
@@ -49,21 +107,123 @@ pub async fn run() -> Result<(), AppError> {
                 ));
             }
 
-            let behaviour = if benchmark {
+            if queries.len() > 1 {
+                if !benchmark {
+                    return Err(AppError::InvalidCLIUsage(
+                        "several queries can only be provided when benchmarking",
+                    ));
+                }
+                if concurrency.is_some() || bench_output.is_some() || bench_baseline.is_some() {
+                    return Err(AppError::InvalidCLIUsage(
+                        "load tests, benchmark reports and baselines only apply to a single query",
+                    ));
+                }
+                if queries.iter().filter(|q| q.as_str() == "-").count() > 1 {
+                    return Err(AppError::InvalidCLIUsage(
+                        "only one query can be read from stdin",
+                    ));
+                }
+            }
+
+            if bench_params.is_some() && (concurrency.is_some() || queries.len() > 1) {
+                return Err(AppError::InvalidCLIUsage(
+                    "parameter sets aren't supported for load tests or query comparisons",
+                ));
+            }
+
+            let mut query_params = match params_file {
+                Some(path) => QueryParams::from_json_file(path)?,
+                None => QueryParams::default(),
+            };
+            query_params.extend(params);
+
+            let results_directory = profile_results_dir.unwrap_or(results_directory);
+            let results_format = profile_results_format.unwrap_or(results_format);
+            let write_options = WriteOptions {
+                compression: results_compression,
+                flatten,
+            };
+            let paged_format_is_binary = match format {
+                Some(format) => format.results_format().is_some_and(|f| f.is_binary()),
+                None => results_format.is_binary(),
+            };
+            if page_results && paged_format_is_binary {
+                return Err(AppError::InvalidCLIUsage(
+                    "results in a binary format (parquet, arrow) cannot be displayed via a pager",
+                ));
+            }
+
+            let behaviour = if let Some(mode) = explain {
+                QueryBehaviour::Explain {
+                    mode,
+                    write_results,
+                    results_directory,
+                    results_format,
+                    write_options,
+                }
+            } else if benchmark && queries.len() > 1 {
+                QueryBehaviour::Comparison {
+                    num_runs: bench_num_runs,
+                    warmup_runs: bench_num_warmup_runs,
+                }
+            } else if benchmark {
                 QueryBehaviour::Benchmark {
                     num_runs: bench_num_runs,
                     warmup_runs: bench_num_warmup_runs,
+                    param_sets: bench_params
+                        .map(|path| ParamSets::from_file(path, bench_params_order))
+                        .transpose()?,
+                    load_test: concurrency.map(|concurrency| LoadTestConfig {
+                        concurrency,
+                        duration: duration.map(|d| d.value()),
+                        rate,
+                    }),
+                    output: bench_output,
+                    baseline: bench_baseline,
+                    regression_threshold: bench_threshold,
                 }
             } else {
+                // tables are meant for people; anything else reading results gets a machine format
+                let output_format = match format {
+                    Some(format) => format,
+                    // pagers get the results format too, unless asked for another one
+                    None if page_results => OutputFormat::from(&results_format),
+                    // files get the format their extension stands for, if it stands for one
+                    None if output.is_some() || !std::io::stdout().is_terminal() => {
+                        let format = output
+                            .as_deref()
+                            .and_then(ResultsFormat::from_extension)
+                            .unwrap_or_else(|| results_format.clone());
+                        OutputFormat::from(&format)
+                    }
+                    None => OutputFormat::Table,
+                };
+
                 QueryBehaviour::Normal {
                     page_results,
                     write_results,
                     results_directory,
                     results_format,
+                    write_options,
+                    output,
+                    output_format,
+                    display: DisplayOptions {
+                        expanded: expanded.unwrap_or_default(),
+                        max_column_width: max_column_width.map(usize::from),
+                        overflow: overflow.unwrap_or_default(),
+                        max_rows: max_rows.map(|r| r as usize),
+                    },
                 }
             };
 
-            handle_query_cmd(query, behaviour, print_query).await?;
+            handle_query_cmd(
+                queries,
+                query_params,
+                behaviour,
+                print_query,
+                profile.as_ref(),
+            )
+            .await?;
         }
     }
 
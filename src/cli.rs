@@ -1,5 +1,8 @@
 use crate::config::DEFAULT_RESULTS_DIR;
-use crate::domain::{BenchmarkNumRuns, ResultsFormat};
+use crate::domain::{
+    ArrayFlattening, BenchmarkNumRuns, CellOverflow, ExpandedMode, LoadTestDuration, OutputFormat,
+    ParamSetOrder, PlanMode, QueryParam, ResultsCompression, ResultsFormat,
+};
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 
@@ -11,8 +14,13 @@ pub struct Args {
     /// Output debug information without doing anything
     #[arg(long = "debug", global = true)]
     pub debug: bool,
+    /// Profile (from grafq's config file) to use; environment variables override its values
+    #[arg(long = "profile", value_name = "NAME", global = true)]
+    pub profile: Option<String>,
 }
 
+// parsed once, at startup, so the size difference between the variants doesn't matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, Debug)]
 pub enum GraphQCommand {
     /// Open grafq's console
@@ -40,6 +48,18 @@ pub enum GraphQCommand {
             default_value = "json"
         )]
         results_format: ResultsFormat,
+        /// Compression for parquet/arrow results [default: zstd for parquet, none for arrow]
+        #[arg(long = "results-compression", value_name = "CODEC")]
+        results_compression: Option<ResultsCompression>,
+        /// Flatten nested values into dotted columns (except in JSON/NDJSON); arrays stay as JSON, or are spread over columns/rows
+        #[arg(
+            long = "flatten",
+            value_name = "ARRAYS",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "json"
+        )]
+        flatten: Option<ArrayFlattening>,
     },
     /// Execute a one-off query
     #[command()]
@@ -47,9 +67,15 @@ pub enum GraphQCommand {
         /// Display results via a pager ("less", by default, can be overridden by $GRAFQ_PAGER)
         #[arg(short = 'p', long = "page-results")]
         page_results: bool,
-        /// Cypher query to execute
-        #[arg()]
-        query: String,
+        /// Cypher query to execute ("@FILE" reads it from a file, "-" from stdin); benchmarks can compare several queries
+        #[arg(value_name = "QUERY", required = true, num_args = 1..)]
+        queries: Vec<String>,
+        /// Query parameter, bound to $KEY (VALUE is parsed as JSON, falling back to a string); can be repeated
+        #[arg(long = "param", value_name = "KEY=VALUE")]
+        params: Vec<QueryParam>,
+        /// JSON file containing an object of query parameters (overridden by --param)
+        #[arg(long = "params-file", value_name = "FILE")]
+        params_file: Option<PathBuf>,
         /// Whether to benchmark the query
         #[arg(short = 'b', long = "bench")]
         benchmark: bool,
@@ -69,6 +95,62 @@ pub enum GraphQCommand {
             value_name = "NUMBER"
         )]
         bench_num_warmup_runs: u16,
+        /// CSV/NDJSON file with a parameter set per row/line; every benchmark run binds one of them (on top of --param)
+        #[arg(long = "bench-params", value_name = "FILE", requires = "benchmark")]
+        bench_params: Option<PathBuf>,
+        /// Order to bind the parameter sets in
+        #[arg(
+            long = "bench-params-order",
+            value_name = "ORDER",
+            default_value = "round-robin",
+            requires = "bench_params"
+        )]
+        bench_params_order: ParamSetOrder,
+        /// Run the benchmark from this many concurrent tasks (as a load test)
+        #[arg(
+            short = 'c',
+            long = "concurrency",
+            value_name = "NUMBER",
+            value_parser = clap::value_parser!(u16).range(1..),
+            requires = "benchmark"
+        )]
+        concurrency: Option<u16>,
+        /// Run the load test for this long (eg. 30s, 5m) instead of for a number of runs
+        #[arg(long = "duration", value_name = "DURATION", requires = "concurrency")]
+        duration: Option<LoadTestDuration>,
+        /// Cap the load test at this many queries per second (across all tasks)
+        #[arg(
+            long = "rate",
+            value_name = "NUMBER",
+            value_parser = clap::value_parser!(u32).range(1..),
+            requires = "concurrency"
+        )]
+        rate: Option<u32>,
+        /// File to write a benchmark report to (JSON or CSV, as per its extension)
+        #[arg(long = "bench-output", value_name = "FILE", requires = "benchmark")]
+        bench_output: Option<PathBuf>,
+        /// Benchmark report (from an earlier --bench-output) to compare the median against
+        #[arg(long = "bench-baseline", value_name = "FILE", requires = "benchmark")]
+        bench_baseline: Option<PathBuf>,
+        /// Percentage by which the median can exceed the baseline's before grafq exits with an error
+        #[arg(
+            long = "bench-threshold",
+            value_name = "PERCENT",
+            default_value_t = 10.0,
+            requires = "bench_baseline"
+        )]
+        bench_threshold: f64,
+        /// Show the query's execution plan instead of its results ("profile" and "details" execute the query to gather stats)
+        #[arg(
+            short = 'e',
+            long = "explain",
+            value_name = "MODE",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "explain",
+            conflicts_with_all = ["benchmark", "page_results"],
+        )]
+        explain: Option<PlanMode>,
         /// Print query
         #[arg(short = 'P', long = "print-query")]
         print_query: bool,
@@ -91,58 +173,181 @@ pub enum GraphQCommand {
             default_value = "json"
         )]
         results_format: ResultsFormat,
+        /// Compression for parquet/arrow results [default: zstd for parquet, none for arrow]
+        #[arg(long = "results-compression", value_name = "CODEC")]
+        results_compression: Option<ResultsCompression>,
+        /// Flatten nested values into dotted columns (except in JSON/NDJSON); arrays stay as JSON, or are spread over columns/rows
+        #[arg(
+            long = "flatten",
+            value_name = "ARRAYS",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "json"
+        )]
+        flatten: Option<ArrayFlattening>,
+        /// File to write results to ("-" for stdout), in --format (or as per its extension, falling back to --results-format)
+        #[arg(
+            short = 'o',
+            long = "output",
+            value_name = "FILE",
+            conflicts_with_all = ["benchmark", "explain", "page_results", "write_results"],
+        )]
+        output: Option<PathBuf>,
+        /// Format to print (or page) results in [default: table, or --results-format when stdout is piped or results are paged]
+        #[arg(
+            long = "format",
+            value_name = "FORMAT",
+            conflicts_with_all = ["benchmark", "explain", "write_results"],
+        )]
+        format: Option<OutputFormat>,
+        /// Display each record as a block of "column | value" lines ("auto" does so if the table is wider than the terminal)
+        #[arg(
+            short = 'x',
+            long = "expanded",
+            value_name = "MODE",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "on"
+        )]
+        expanded: Option<ExpandedMode>,
+        /// Maximum width of a table column (on top of the table being fit to the terminal)
+        #[arg(
+            long = "max-column-width",
+            value_name = "WIDTH",
+            value_parser = clap::value_parser!(u16).range(1..),
+        )]
+        max_column_width: Option<u16>,
+        /// What to do with cells that don't fit in their column [default: truncate]
+        #[arg(long = "overflow", value_name = "MODE")]
+        overflow: Option<CellOverflow>,
+        /// Maximum number of rows to display
+        #[arg(
+            long = "max-rows",
+            value_name = "NUMBER",
+            value_parser = clap::value_parser!(u32).range(1..),
+        )]
+        max_rows: Option<u32>,
     },
 }
 
 impl std::fmt::Display for Args {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let profile_info = self
+            .profile
+            .as_ref()
+            .map(|p| format!("\nprofile:                    {p}"))
+            .unwrap_or_default();
+
         let output = match &self.command {
             GraphQCommand::Console {
                 page_results,
                 write_results,
                 results_directory,
                 results_format,
+                results_compression,
+                flatten,
             } => format!(
                 "
-command:                    console
+command:                    console{}
 display results via pager:  {}
 write results:              {}
 results directory:          {}
-results format:             {}
+results format:             {}{}{}
 ",
+                profile_info,
                 page_results,
                 write_results,
                 results_directory.to_string_lossy(),
-                results_format
+                results_format,
+                compression_info(results_compression),
+                flatten_info(flatten),
             ),
             GraphQCommand::Query {
                 page_results,
-                query,
+                queries,
+                params,
+                params_file,
                 benchmark,
                 bench_num_runs,
                 bench_num_warmup_runs,
+                bench_params,
+                bench_params_order,
+                concurrency,
+                duration,
+                rate,
+                bench_output,
+                bench_baseline,
+                bench_threshold,
+                explain,
                 print_query,
                 write_results,
                 results_directory,
                 results_format,
+                results_compression,
+                flatten,
+                output,
+                format,
+                expanded,
+                max_column_width,
+                overflow,
+                max_rows,
             } => {
                 let benchmark_info = match benchmark {
                     true => Some(format!(
                         r#"
 benchmark num runs:         {}
-benchmark num warmup runs:  {}"#,
-                        bench_num_runs, bench_num_warmup_runs,
+benchmark num warmup runs:  {}{}{}{}{}"#,
+                        bench_num_runs,
+                        bench_num_warmup_runs,
+                        bench_params
+                            .as_ref()
+                            .map(|p| format!(
+                                "\nbenchmark params:           {} ({})",
+                                p.to_string_lossy(),
+                                bench_params_order
+                            ))
+                            .unwrap_or_default(),
+                        concurrency
+                            .map(|c| {
+                                let mut info = format!("\nconcurrency:                {c}");
+                                if let Some(d) = duration {
+                                    info.push_str(&format!("\nduration:                   {d}"));
+                                }
+                                if let Some(r) = rate {
+                                    info.push_str(&format!("\nrate:                       {r}/s"));
+                                }
+                                info
+                            })
+                            .unwrap_or_default(),
+                        bench_output
+                            .as_ref()
+                            .map(|p| format!(
+                                "\nbenchmark output:           {}",
+                                p.to_string_lossy()
+                            ))
+                            .unwrap_or_default(),
+                        bench_baseline
+                            .as_ref()
+                            .map(|p| format!(
+                                "\nbenchmark baseline:         {}\nregression threshold:       {}%",
+                                p.to_string_lossy(),
+                                bench_threshold
+                            ))
+                            .unwrap_or_default(),
                     )),
                     false => None,
                 };
 
-                let query_info = if query.as_str() == "-" {
-                    "
+                let explain_info = explain
+                    .map(|mode| format!("\nexplain:                    {mode}"))
+                    .unwrap_or_default();
+
+                let query_info = match queries.as_slice() {
+                    [query] if query.as_str() == "-" => "
 query:                      -
 "
-                    .to_string()
-                } else {
-                    format!(
+                    .to_string(),
+                    [query] => format!(
                         r#"
 query:
 ---
@@ -150,6 +355,33 @@ query:
 ---
 "#,
                         query
+                    ),
+                    _ => format!(
+                        r#"
+queries:
+---
+{}
+---
+"#,
+                        queries.join("\n---\n")
+                    ),
+                };
+
+                let params_file_info = params_file
+                    .as_ref()
+                    .map(|p| format!("\nparams file:                {}", p.to_string_lossy()))
+                    .unwrap_or_default();
+
+                let params_info = if params.is_empty() {
+                    String::new()
+                } else {
+                    format!(
+                        "\nparams:                     {}",
+                        params
+                            .iter()
+                            .map(|p| p.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
                     )
                 };
 
@@ -158,28 +390,58 @@ query:
                         "
 write results:              true
 results directory:          {}
-results format:             {}
+results format:             {}{}{}
 ",
                         results_directory.to_string_lossy(),
-                        results_format
+                        results_format,
+                        compression_info(results_compression),
+                        flatten_info(flatten),
                     )
                 } else {
-                    r#"
-write results:              false
-"#
-                    .to_string()
+                    format!(
+                        "
+write results:              false{}{}{}{}{}{}{}
+",
+                        output
+                            .as_ref()
+                            .map(|p| format!(
+                                "\noutput:                     {}",
+                                p.to_string_lossy()
+                            ))
+                            .unwrap_or_default(),
+                        format
+                            .map(|f| format!("\noutput format:              {f}"))
+                            .unwrap_or_default(),
+                        expanded
+                            .map(|m| format!("\nexpanded display:           {m}"))
+                            .unwrap_or_default(),
+                        max_column_width
+                            .map(|w| format!("\nmax column width:           {w}"))
+                            .unwrap_or_default(),
+                        overflow
+                            .map(|o| format!("\noverflow:                   {o}"))
+                            .unwrap_or_default(),
+                        max_rows
+                            .map(|r| format!("\nmax rows:                   {r}"))
+                            .unwrap_or_default(),
+                        flatten_info(flatten),
+                    )
                 };
 
                 format!(
                     r#"
-command:                    query
+command:                    query{}
 display results via pager:  {}
-benchmark:                  {}{}
-print query:                {}{}{}"#,
+benchmark:                  {}{}{}
+print query:                {}{}{}{}{}"#,
+                    profile_info,
                     page_results,
                     benchmark,
                     benchmark_info.unwrap_or_default(),
+                    explain_info,
                     print_query,
+                    params_file_info,
+                    params_info,
                     output_info,
                     query_info,
                 )
@@ -189,3 +451,15 @@ print query:                {}{}{}"#,
         f.write_str(&output)
     }
 }
+
+fn flatten_info(flatten: &Option<ArrayFlattening>) -> String {
+    flatten
+        .map(|f| format!("\nflatten (arrays):           {f}"))
+        .unwrap_or_default()
+}
+
+fn compression_info(compression: &Option<ResultsCompression>) -> String {
+    compression
+        .map(|c| format!("\nresults compression:        {c}"))
+        .unwrap_or_default()
+}
@@ -1,4 +1,5 @@
-use crate::repository::{DbClientError, get_db_client};
+use crate::config::Profile;
+use crate::repository::{DbClientError, QueryExecutor, get_db_client};
 use crate::utils::get_pager;
 use crate::view::{Console, ConsoleConfig};
 use anyhow::Context;
@@ -11,8 +12,11 @@ pub enum ConsoleCmdError {
     Uncategorised(#[from] anyhow::Error),
 }
 
-pub async fn handle_console_cmd(config: ConsoleConfig) -> Result<(), ConsoleCmdError> {
-    let db_client = get_db_client().await?;
+pub async fn handle_console_cmd(
+    config: ConsoleConfig,
+    profile: Option<&Profile>,
+) -> Result<(), ConsoleCmdError> {
+    let db_client = get_db_client(profile).await?;
     db_client.verify_connectivity().await?;
 
     if let Some(parent) = config.history_file_path.parent() {
@@ -30,7 +34,8 @@ pub async fn handle_console_cmd(config: ConsoleConfig) -> Result<(), ConsoleCmdE
         None
     };
 
-    let mut console = Console::new(db_client, config, pager);
+    let profile_name = profile.map(|p| p.name.clone());
+    let mut console = Console::new(db_client, config, pager, profile_name);
     console.run_loop().await?;
 
     Ok(())
@@ -1,66 +1,202 @@
-use crate::domain::{BenchmarkNumRuns, QueryResults, ResultsFormat};
+use crate::config::Profile;
+use crate::domain::{
+    BaselineComparison, BenchmarkNumRuns, BenchmarkReport, BenchmarkStats, BenchmarkSummary,
+    ComparedQuery, DisplayOptions, LoadTestResults, NonEmptyResults, OutputFormat, ParamSets,
+    PlanMode, QueryParams, QueryResults, ResultsFormat, WriteOptions, format_duration,
+};
 use crate::repository::{DbClient, DbClientError, QueryExecutor, get_db_client};
+use crate::service::{benchmark_query, print_benchmark_stats, run_warmups};
 use crate::utils::get_pager;
-use crate::view::get_results;
+use crate::view::{get_benchmark_comparison, get_plan, get_results_for_display};
 use anyhow::Context;
 use chrono::Utc;
 use colored::Colorize;
-use std::io::Read;
-use std::path::PathBuf;
-use std::time::Instant;
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+
+const MAX_QUERY_LABEL_LEN: usize = 40;
 
 pub enum QueryBehaviour {
     Benchmark {
         num_runs: BenchmarkNumRuns,
         warmup_runs: u16,
+        /// Parameter sets for the runs to bind, on top of the query's params.
+        param_sets: Option<ParamSets>,
+        load_test: Option<LoadTestConfig>,
+        output: Option<PathBuf>,
+        baseline: Option<PathBuf>,
+        /// Percentage by which the median can exceed the baseline's.
+        regression_threshold: f64,
+    },
+    Comparison {
+        num_runs: BenchmarkNumRuns,
+        warmup_runs: u16,
+    },
+    Explain {
+        mode: PlanMode,
+        write_results: bool,
+        results_directory: PathBuf,
+        results_format: ResultsFormat,
+        write_options: WriteOptions,
     },
     Normal {
         page_results: bool,
         write_results: bool,
         results_directory: PathBuf,
         results_format: ResultsFormat,
+        write_options: WriteOptions,
+        /// File to write results to instead of stdout ("-" stands for stdout).
+        output: Option<PathBuf>,
+        output_format: OutputFormat,
+        display: DisplayOptions,
     },
 }
 
+/// Settings for running a benchmark as a load test, ie. from several concurrent tasks.
+pub struct LoadTestConfig {
+    pub concurrency: u16,
+    /// How long to run for; the benchmark's number of runs is used if this is not set.
+    pub duration: Option<Duration>,
+    /// Maximum number of queries to start per second, across all tasks.
+    pub rate: Option<u32>,
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum QueryCmdError {
     #[error("couldn't build db client")]
     CouldntBuildDbClient(#[from] DbClientError),
+    #[error(
+        "median regressed by {change_percent:.2}% compared to the baseline (threshold: {threshold}%)"
+    )]
+    BenchmarkRegressed { change_percent: f64, threshold: f64 },
     #[error(transparent)]
     Uncategorised(#[from] anyhow::Error),
 }
 
 pub async fn handle_query_cmd(
-    query: String,
+    queries: Vec<String>,
+    params: QueryParams,
     behaviour: QueryBehaviour,
     print_query: bool,
+    profile: Option<&Profile>,
 ) -> Result<(), QueryCmdError> {
-    let db_client = get_db_client().await?;
+    let db_client = get_db_client(profile).await?;
 
-    let query = if query == "-" {
-        let mut buffer = String::new();
-        std::io::stdin()
-            .read_to_string(&mut buffer)
-            .context("couldn't read query from stdin")?;
-        buffer.trim().to_string()
-    } else {
-        query
-    };
+    let labelled_queries = queries
+        .iter()
+        .map(|arg| read_query(arg).map(|query| (query_label(arg, &query), query)))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let query = labelled_queries
+        .first()
+        .map(|(_, query)| query.clone())
+        .context("no query was provided")?;
 
     if print_query {
-        println!(
-            r#"---
+        for (_, query) in &labelled_queries {
+            println!(
+                r#"---
 {query}
 ---
 "#
-        );
+            );
+        }
     }
     match behaviour {
         QueryBehaviour::Benchmark {
             num_runs,
             warmup_runs,
+            mut param_sets,
+            load_test,
+            output,
+            baseline,
+            regression_threshold,
         } => {
-            benchmark_query(&db_client, &query, num_runs, warmup_runs).await?;
+            // the baseline is read upfront so that a bad path doesn't surface after a long run
+            let baseline = baseline
+                .map(crate::service::read_benchmark_report)
+                .transpose()?;
+
+            let summary = match load_test {
+                Some(config) => {
+                    load_test_query(db_client, &query, &params, num_runs, warmup_runs, config)
+                        .await?
+                }
+                None => {
+                    benchmark_query(
+                        &db_client,
+                        &query,
+                        &params,
+                        param_sets.as_mut(),
+                        num_runs,
+                        warmup_runs,
+                    )
+                    .await?
+                }
+            };
+            let Some(BenchmarkSummary { report, .. }) = summary else {
+                return Ok(());
+            };
+
+            if let Some(path) = output {
+                crate::service::write_benchmark_report(&report, &path)
+                    .context("couldn't write benchmark report")?;
+                println!("\nWrote benchmark report to {}", path.to_string_lossy());
+            }
+
+            if let Some(baseline) = baseline {
+                let comparison = BaselineComparison::new(&baseline, &report);
+                print_baseline_comparison(&query, &baseline, &comparison);
+
+                if comparison.is_regression(regression_threshold) {
+                    return Err(QueryCmdError::BenchmarkRegressed {
+                        change_percent: comparison.change_percent,
+                        threshold: regression_threshold,
+                    });
+                }
+            }
+        }
+
+        QueryBehaviour::Comparison {
+            num_runs,
+            warmup_runs,
+        } => {
+            compare_queries(
+                &db_client,
+                &labelled_queries,
+                &params,
+                num_runs,
+                warmup_runs,
+            )
+            .await?;
+        }
+
+        QueryBehaviour::Explain {
+            mode,
+            write_results,
+            results_directory,
+            results_format,
+            write_options,
+        } => {
+            let plan = db_client.explain_query(&query, &params, mode).await?;
+            println!("{}", get_plan(&plan));
+
+            if write_results && let QueryResults::NonEmpty(results) = plan.to_results() {
+                let plan_file_path = crate::service::write_results(
+                    &results,
+                    &results_directory,
+                    &results_format,
+                    write_options,
+                    Utc::now(),
+                )
+                .context("couldn't write query plan")?;
+                println!("\nWrote query plan to {}", plan_file_path.to_string_lossy());
+            }
         }
 
         QueryBehaviour::Normal {
@@ -68,6 +204,10 @@ pub async fn handle_query_cmd(
             write_results,
             results_directory,
             results_format,
+            write_options,
+            output,
+            output_format,
+            display,
         } => {
             let pager = if page_results {
                 Some(get_pager()?)
@@ -75,7 +215,18 @@ pub async fn handle_query_cmd(
                 None
             };
 
-            let results = db_client.execute_query(&query).await?;
+            let results = db_client.execute_query(&query, &params).await?;
+            if !page_results && !write_results {
+                output_results(
+                    &results,
+                    output_format,
+                    write_options,
+                    &display,
+                    output.as_deref(),
+                )?;
+                return Ok(());
+            }
+
             let results = match results {
                 QueryResults::Empty => {
                     println!("No results");
@@ -84,103 +235,442 @@ pub async fn handle_query_cmd(
                 QueryResults::NonEmpty(res) => res,
             };
 
-            if write_results {
+            let results_file_path = if write_results {
                 let results_file_path = crate::service::write_results(
                     &results,
                     &results_directory,
                     &results_format,
+                    write_options,
                     Utc::now(),
                 )
                 .context("couldn't write results")?;
                 println!("Wrote results to {}", results_file_path.to_string_lossy());
+                Some(results_file_path)
+            } else {
+                None
+            };
 
-                if let Some(pager) = pager {
-                    crate::service::page_results(&results_file_path, &pager)?;
-                }
-            } else if let Some(pager) = pager {
-                let temp_results_directory = tempfile::tempdir()
-                    .context("couldn't create temporary directory for paging results")?;
-                let results_file_path = crate::service::write_results(
+            if let Some(pager) = pager {
+                crate::service::page_results(
                     &results,
-                    &temp_results_directory,
+                    results_file_path.as_deref(),
+                    &pager,
+                    output_format,
                     &results_format,
-                    Utc::now(),
-                )
-                .context("couldn't write results to temporary location")?;
+                    write_options,
+                    &display,
+                )?;
+            }
+        }
+    }
 
-                crate::service::page_results(&results_file_path, &pager)?;
-            } else {
-                let results_str = get_results(&results);
-                println!("{}", results_str);
+    Ok(())
+}
+
+/// Prints results to stdout (or writes them to a file, if a path other than "-" is provided).
+///
+/// Empty results produce no output in machine formats, except for JSON, which gets an empty
+/// array, so that whatever is reading them doesn't have to special case a "No results" message.
+fn output_results(
+    results: &QueryResults,
+    format: OutputFormat,
+    options: WriteOptions,
+    display: &DisplayOptions,
+    output: Option<&Path>,
+) -> anyhow::Result<()> {
+    let mut terminal_width = None;
+    let mut writer: Box<dyn Write> = match output {
+        Some(path) if path != Path::new("-") => {
+            Box::new(File::create(path).with_context(|| {
+                format!("couldn't create output file: {}", path.to_string_lossy())
+            })?)
+        }
+        _ => {
+            terminal_width = crate::utils::terminal_width();
+            if format.results_format().is_some_and(|f| f.is_binary())
+                && std::io::stdout().is_terminal()
+            {
+                anyhow::bail!(
+                    "{format} results cannot be printed to a terminal; redirect stdout, or use --output <FILE>"
+                );
             }
+            Box::new(std::io::stdout().lock())
+        }
+    };
+
+    match (results, format.results_format()) {
+        (QueryResults::Empty, None) => writeln!(writer, "No results"),
+        (QueryResults::Empty, Some(ResultsFormat::Json)) => writeln!(writer, "[]"),
+        (QueryResults::Empty, Some(_)) => Ok(()),
+        (QueryResults::NonEmpty(results), None) => writeln!(
+            writer,
+            "{}",
+            get_results_for_display(results, display, terminal_width)
+        ),
+        (QueryResults::NonEmpty(results), Some(format)) => {
+            write_machine_readable_results(results, &format, options, &mut writer)?;
+            Ok(())
         }
     }
+    .context("couldn't write results")?;
 
+    writer.flush().context("couldn't write results")?;
     Ok(())
 }
 
-async fn benchmark_query(
+fn write_machine_readable_results<W: Write>(
+    results: &NonEmptyResults,
+    format: &ResultsFormat,
+    options: WriteOptions,
+    mut writer: W,
+) -> anyhow::Result<()> {
+    crate::service::write_results_to(results, format, options, &mut writer)?;
+    // pretty printed JSON doesn't end with a newline, unlike the line based formats
+    if matches!(format, ResultsFormat::Json) {
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+/// Reads a query from stdin for "-", from a file for "@FILE", and returns it as is otherwise.
+fn read_query(arg: &str) -> anyhow::Result<String> {
+    if arg == "-" {
+        let mut buffer = String::new();
+        std::io::stdin()
+            .read_to_string(&mut buffer)
+            .context("couldn't read query from stdin")?;
+        Ok(buffer.trim().to_string())
+    } else if let Some(path) = arg.strip_prefix('@') {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("couldn't read query from file: {path}"))?;
+        Ok(contents.trim().to_string())
+    } else {
+        Ok(arg.to_string())
+    }
+}
+
+/// A short, single line name for a query: the file it came from, or its (truncated) text.
+fn query_label(arg: &str, query: &str) -> String {
+    if let Some(path) = arg.strip_prefix('@') {
+        return path.to_string();
+    }
+
+    let query = query.split_whitespace().collect::<Vec<_>>().join(" ");
+    if query.chars().count() > MAX_QUERY_LABEL_LEN {
+        let truncated: String = query.chars().take(MAX_QUERY_LABEL_LEN - 3).collect();
+        format!("{truncated}...")
+    } else {
+        query
+    }
+}
+
+/// Benchmarks queries against each other, interleaving their runs (and rotating the order they
+/// run in every round), so that drift in the database's performance over the course of the
+/// benchmark (eg. caches warming up, or load from elsewhere) affects all of them alike.
+///
+/// Pressing ctrl+c stops the benchmark, and the runs completed until then are still compared.
+async fn compare_queries(
     db_client: &DbClient,
-    query: &str,
+    labelled_queries: &[(String, String)],
+    params: &QueryParams,
     num_runs: BenchmarkNumRuns,
     num_warmup_runs: u16,
 ) -> anyhow::Result<()> {
-    if num_warmup_runs > 0 {
+    let num_queries = labelled_queries.len();
+    let run_order = |round: usize| (0..num_queries).map(move |i| (i + round) % num_queries);
+
+    let mut timings = vec![vec![]; num_queries];
+    let mut row_counts = vec![BTreeSet::new(); num_queries];
+    let runs = async {
+        if num_warmup_runs > 0 {
+            println!(
+                "{}",
+                format!("Warming up ({num_warmup_runs} runs per query) ...")
+                    .yellow()
+                    .bold()
+            );
+        }
+        for round in 0..usize::from(num_warmup_runs) {
+            for i in run_order(round) {
+                let start = Instant::now();
+                db_client
+                    .execute_query(&labelled_queries[i].1, params)
+                    .await
+                    .with_context(|| {
+                        format!(
+                            "couldn't get results for warmup run #{} of query #{}",
+                            round + 1,
+                            i + 1
+                        )
+                    })?;
+                let elapsed = start.elapsed();
+                println!(
+                    "run {:03} #{}:   {}",
+                    round + 1,
+                    i + 1,
+                    format_duration(elapsed).cyan()
+                );
+            }
+        }
+        if num_warmup_runs > 0 {
+            println!();
+        }
+
         println!(
             "{}",
-            format!("Warming up ({num_warmup_runs} runs) ...")
-                .yellow()
-                .bold()
+            format!(
+                "Benchmarking ({} runs per query, interleaved) ...",
+                num_runs.value()
+            )
+            .yellow()
+            .bold()
+        );
+
+        for round in 0..usize::from(num_runs.value()) {
+            for i in run_order(round) {
+                let start = Instant::now();
+                let results = db_client
+                    .execute_query(&labelled_queries[i].1, params)
+                    .await
+                    .with_context(|| {
+                        format!(
+                            "couldn't execute query #{} for benchmark run #{}",
+                            i + 1,
+                            round + 1
+                        )
+                    })?;
+                let elapsed = start.elapsed();
+                println!(
+                    "run {:03} #{}:   {}",
+                    round + 1,
+                    i + 1,
+                    format_duration(elapsed).cyan()
+                );
+                timings[i].push(elapsed);
+                row_counts[i].insert(results.num_rows());
+            }
+        }
+
+        anyhow::Ok(())
+    };
+    let interrupted = tokio::select! {
+        res = runs => {
+            res?;
+            false
+        }
+        Ok(_) = tokio::signal::ctrl_c() => true,
+    };
+    if interrupted {
+        println!(
+            "{}",
+            "\nbenchmark interrupted; comparing the runs completed so far".yellow()
         );
     }
-    for i in 0..num_warmup_runs {
-        let start = Instant::now();
-        db_client
-            .execute_query(query)
-            .await
-            .with_context(|| format!("couldn't get results for warmup run #{}", i + 1))?;
-        let elapsed = start.elapsed().as_millis();
-        println!("run {:03}:      {}", i + 1, format!("{}ms", elapsed).cyan(),);
+
+    let mut compared = vec![];
+    for (((label, _), timings), row_counts) in labelled_queries.iter().zip(timings).zip(row_counts)
+    {
+        let Some(stats) = BenchmarkStats::new(timings) else {
+            if interrupted {
+                println!(
+                    "{}",
+                    "not every query was run before the interruption, so there's nothing to compare"
+                        .yellow()
+                );
+            }
+            return Ok(());
+        };
+        compared.push(ComparedQuery {
+            label: label.clone(),
+            stats,
+            row_counts,
+        });
     }
 
-    if num_warmup_runs > 0 {
-        println!();
+    println!("\n{}", "Comparison:".yellow().bold());
+    println!("{}", get_benchmark_comparison(&compared));
+
+    let distinct_row_counts: BTreeSet<usize> = compared
+        .iter()
+        .flat_map(|q| q.row_counts.iter().copied())
+        .collect();
+    if distinct_row_counts.len() > 1 {
+        println!(
+            "\n{}",
+            "note: the queries didn't all return the same number of rows, so they might not be equivalent"
+                .yellow()
+        );
     }
 
+    Ok(())
+}
+
+/// Runs a query from several concurrent tasks (sharing the db client), either for a duration or
+/// for a number of runs (in total), optionally capping the rate at which queries are started.
+///
+/// Unlike in a regular benchmark, failed queries don't stop the load test; they're counted by
+/// error message instead. Pressing ctrl+c stops the load test, and a summary of the queries
+/// run until then is still printed.
+async fn load_test_query(
+    db_client: DbClient,
+    query: &str,
+    params: &QueryParams,
+    num_runs: BenchmarkNumRuns,
+    num_warmup_runs: u16,
+    config: LoadTestConfig,
+) -> anyhow::Result<Option<BenchmarkSummary>> {
+    let warmup_times = run_warmups(&db_client, query, params, None, num_warmup_runs).await?;
+
+    let mut settings = vec![format!("concurrency: {}", config.concurrency)];
+    match config.duration {
+        Some(duration) => settings.push(format!("duration: {}s", duration.as_secs_f64())),
+        None => settings.push(format!("runs: {}", num_runs.value())),
+    }
+    if let Some(rate) = config.rate {
+        settings.push(format!("rate: {rate}/s"));
+    }
     println!(
         "{}",
-        format!("Benchmarking ({} runs) ...", num_runs.value())
+        format!("Load testing ({}) ...", settings.join(", "))
             .yellow()
             .bold()
     );
 
-    let mut times = vec![];
-    for i in 0..num_runs.value() {
-        let start = Instant::now();
-        db_client
-            .execute_query(query)
-            .await
-            .with_context(|| format!("couldn't execute query for benchmark run #{}", i + 1))?;
-        let elapsed = start.elapsed().as_millis();
-        println!("run {:03}:      {}", i + 1, format!("{}ms", elapsed).cyan(),);
-        times.push(elapsed);
-    }
-
-    if let (Some(min), Some(max)) = (times.iter().min(), times.iter().max()) {
-        let mean = times.iter().sum::<u128>() / times.len() as u128;
-        print!(
-            "
+    let db_client = Arc::new(db_client);
+    let results = Arc::new(Mutex::new(LoadTestResults::default()));
+    let next_run = Arc::new(AtomicU64::new(0));
+    let start = Instant::now();
+    let deadline = config.duration.map(|d| start + d);
+    let max_runs = deadline.is_none().then_some(u64::from(num_runs.value()));
+
+    let mut tasks = JoinSet::new();
+    for _ in 0..config.concurrency {
+        let db_client = Arc::clone(&db_client);
+        let results = Arc::clone(&results);
+        let next_run = Arc::clone(&next_run);
+        let query = query.to_string();
+        let params = params.clone();
+        let rate = config.rate;
+
+        tasks.spawn(async move {
+            loop {
+                let run = next_run.fetch_add(1, Ordering::Relaxed);
+                if max_runs.is_some_and(|max| run >= max) {
+                    break;
+                }
+                if let Some(rate) = rate {
+                    let scheduled_at = start + Duration::from_secs_f64(run as f64 / rate as f64);
+                    if deadline.is_some_and(|d| scheduled_at >= d) {
+                        break;
+                    }
+                    tokio::time::sleep_until(scheduled_at.into()).await;
+                }
+                if deadline.is_some_and(|d| Instant::now() >= d) {
+                    break;
+                }
+
+                let query_start = Instant::now();
+                let result = db_client.execute_query(&query, &params).await;
+                let elapsed = query_start.elapsed();
+
+                let mut results = results.lock().unwrap_or_else(|e| e.into_inner());
+                match result {
+                    Ok(_) => results.record_success(elapsed),
+                    Err(e) => results.record_failure(format!("{e:#}")),
+                }
+            }
+        });
+    }
+
+    let interrupted = tokio::select! {
+        _ = async { while tasks.join_next().await.is_some() {} } => false,
+        Ok(_) = tokio::signal::ctrl_c() => true,
+    };
+    if interrupted {
+        tasks.shutdown().await;
+        println!(
+            "{}",
+            "\nload test interrupted; summarising the queries run so far".yellow()
+        );
+    }
+    let elapsed = start.elapsed();
+
+    let results = results.lock().unwrap_or_else(|e| e.into_inner());
+    print_load_test_summary(&results, elapsed);
+
+    let Some(stats) = BenchmarkStats::new(results.timings().to_vec()) else {
+        return Ok(None);
+    };
+    print_benchmark_stats(&stats);
+
+    let report = BenchmarkReport::new(
+        query,
+        &db_client.db_uri(),
+        db_client.backend(),
+        &warmup_times,
+        &stats,
+        Utc::now(),
+    );
+
+    Ok(Some(BenchmarkSummary { stats, report }))
+}
+
+fn print_load_test_summary(results: &LoadTestResults, elapsed: Duration) {
+    print!(
+        "
 {}
-min:          {}
-max:          {}
-mean:         {}
+elapsed:      {}
+succeeded:    {}
+failed:       {}
+throughput:   {}
 ",
-            "Statistics:".yellow().bold(),
-            format!("{}ms", min).cyan(),
-            format!("{}ms", max).cyan(),
-            format!("{}ms", mean).cyan(),
-        );
+        "Summary:".yellow().bold(),
+        format!("{:.3}s", elapsed.as_secs_f64()).cyan(),
+        results.num_succeeded().to_string().cyan(),
+        results.num_failed().to_string().cyan(),
+        format!("{:.2} queries/s", results.throughput(elapsed)).cyan(),
+    );
+
+    let errors = results.errors();
+    if !errors.is_empty() {
+        println!("\n{}", "Errors:".yellow().bold());
+        for (message, count) in errors {
+            println!("{:>6}  {}", count.to_string().red(), message);
+        }
     }
+}
 
-    Ok(())
+fn print_baseline_comparison(
+    query: &str,
+    baseline: &BenchmarkReport,
+    comparison: &BaselineComparison,
+) {
+    let change = format!("{:+.2}%", comparison.change_percent);
+    let change = if comparison.change_percent > 0.0 {
+        change.red()
+    } else {
+        change.green()
+    };
+
+    print!(
+        "
+{}
+baseline:     {} ({})
+median:       {} -> {} ({})
+",
+        "Comparison with baseline:".yellow().bold(),
+        baseline.timestamp,
+        baseline.db_uri,
+        format_duration(comparison.baseline_median).cyan(),
+        format_duration(comparison.median).cyan(),
+        change,
+    );
+
+    if baseline.query.trim() != query.trim() {
+        println!(
+            "{}",
+            "note: the baseline was recorded for a different query".yellow()
+        );
+    }
 }
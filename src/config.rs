@@ -1 +1,228 @@
+use crate::domain::ResultsFormat;
+use crate::repository::DbClientError;
+use anyhow::Context;
+use etcetera::BaseStrategy;
+use etcetera::base_strategy::Xdg;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
 pub const DEFAULT_RESULTS_DIR: &str = ".grafq";
+const CONFIG_FILE_NAME: &str = "grafq.toml";
+
+/// A named set of connection details and defaults, read from grafq's config file.
+///
+/// ```toml
+/// [profiles.dev]
+/// uri = "bolt://127.0.0.1:7687"
+/// neo4j_user = "neo4j"
+/// neo4j_password = "password"
+/// neo4j_db = "neo4j"
+///
+/// [profiles.prod]
+/// uri = "https://abc.xyz.us-east-1.neptune.amazonaws.com:8182"
+/// aws_region = "us-east-1"
+/// aws_profile = "prod"
+/// results_dir = "results/prod"
+/// results_format = "csv"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    #[serde(skip)]
+    pub name: String,
+    pub uri: Option<String>,
+    pub neo4j_user: Option<String>,
+    pub neo4j_password: Option<String>,
+    pub neo4j_db: Option<String>,
+    pub aws_region: Option<String>,
+    pub aws_profile: Option<String>,
+    pub results_dir: Option<PathBuf>,
+    pub results_format: Option<ResultsFormat>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    profiles: HashMap<String, toml::Value>,
+}
+
+pub fn get_config_file_path(xdg: &Xdg) -> PathBuf {
+    xdg.config_dir().join("grafq").join(CONFIG_FILE_NAME)
+}
+
+pub fn get_profile<P>(config_file_path: P, name: &str) -> Result<Profile, DbClientError>
+where
+    P: AsRef<Path>,
+{
+    let path = config_file_path.as_ref();
+    let contents = match std::fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(DbClientError::ProfileNotFound(
+                name.to_string(),
+                path.to_path_buf(),
+            ));
+        }
+        Err(e) => {
+            Err(e).with_context(|| format!("couldn't read config file: {}", path.display()))?
+        }
+    };
+
+    parse_profile(&contents, name, path)
+}
+
+fn parse_profile(contents: &str, name: &str, path: &Path) -> Result<Profile, DbClientError> {
+    let config_file: ConfigFile = toml::from_str(contents)
+        .map_err(|e| DbClientError::ConfigFileIsMalformed(path.to_path_buf(), e))?;
+
+    let profile_value = config_file
+        .profiles
+        .get(name)
+        .ok_or_else(|| DbClientError::ProfileNotFound(name.to_string(), path.to_path_buf()))?;
+
+    let mut profile = profile_value
+        .clone()
+        .try_into::<Profile>()
+        .map_err(|e| DbClientError::ProfileIsMalformed(name.to_string(), e))?;
+    profile.name = name.to_string();
+
+    Ok(profile)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::{assert_debug_snapshot, assert_snapshot};
+
+    const CONFIG_FILE_PATH: &str = "path/to/grafq.toml";
+
+    //-------------//
+    //  SUCCESSES  //
+    //-------------//
+
+    #[test]
+    fn parse_profile_reads_all_fields() -> anyhow::Result<()> {
+        // GIVEN
+        let contents = r#"
+[profiles.dev]
+uri = "bolt://127.0.0.1:7687"
+neo4j_user = "neo4j"
+neo4j_password = "password"
+neo4j_db = "neo4j"
+aws_region = "us-east-1"
+aws_profile = "dev"
+results_dir = "results/dev"
+results_format = "csv"
+"#;
+
+        // WHEN
+        let result = parse_profile(contents, "dev", Path::new(CONFIG_FILE_PATH))?;
+
+        // THEN
+        assert_debug_snapshot!(result, @r#"
+        Profile {
+            name: "dev",
+            uri: Some(
+                "bolt://127.0.0.1:7687",
+            ),
+            neo4j_user: Some(
+                "neo4j",
+            ),
+            neo4j_password: Some(
+                "password",
+            ),
+            neo4j_db: Some(
+                "neo4j",
+            ),
+            aws_region: Some(
+                "us-east-1",
+            ),
+            aws_profile: Some(
+                "dev",
+            ),
+            results_dir: Some(
+                "results/dev",
+            ),
+            results_format: Some(
+                Csv,
+            ),
+        }
+        "#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_profile_ignores_malformed_profiles_other_than_the_one_requested() -> anyhow::Result<()>
+    {
+        // GIVEN
+        let contents = r#"
+[profiles.dev]
+uri = "bolt://127.0.0.1:7687"
+
+[profiles.prod]
+url = "https://abc.xyz.us-east-1.neptune.amazonaws.com:8182"
+"#;
+
+        // WHEN
+        let result = parse_profile(contents, "dev", Path::new(CONFIG_FILE_PATH))?;
+
+        // THEN
+        assert_eq!(result.uri.as_deref(), Some("bolt://127.0.0.1:7687"));
+
+        Ok(())
+    }
+
+    //------------//
+    //  FAILURES  //
+    //------------//
+
+    #[test]
+    fn parse_profile_fails_for_unknown_profile() {
+        // GIVEN
+        let contents = r#"
+[profiles.dev]
+uri = "bolt://127.0.0.1:7687"
+"#;
+
+        // WHEN
+        let result = parse_profile(contents, "prod", Path::new(CONFIG_FILE_PATH))
+            .expect_err("result should've been an error");
+
+        // THEN
+        assert_snapshot!(result, @r#"profile "prod" not found"#);
+    }
+
+    #[test]
+    fn parse_profile_fails_for_profile_with_unknown_fields() {
+        // GIVEN
+        let contents = r#"
+[profiles.prod]
+url = "https://abc.xyz.us-east-1.neptune.amazonaws.com:8182"
+"#;
+
+        // WHEN
+        let result = parse_profile(contents, "prod", Path::new(CONFIG_FILE_PATH))
+            .expect_err("result should've been an error");
+
+        // THEN
+        assert_snapshot!(result, @r#"profile "prod" is malformed"#);
+    }
+
+    #[test]
+    fn parse_profile_fails_for_invalid_toml() {
+        // GIVEN
+        let contents = r#"
+[profiles.dev
+uri = "bolt://127.0.0.1:7687"
+"#;
+
+        // WHEN
+        let result = parse_profile(contents, "dev", Path::new(CONFIG_FILE_PATH))
+            .expect_err("result should've been an error");
+
+        // THEN
+        assert_snapshot!(result, @"config file is malformed: path/to/grafq.toml");
+    }
+}
@@ -1,4 +1,6 @@
+use std::collections::BTreeSet;
 use std::str::FromStr;
+use std::time::Duration;
 
 #[derive(Debug, Clone)]
 pub struct BenchmarkNumRuns(u16);
@@ -9,6 +11,12 @@ impl BenchmarkNumRuns {
     }
 }
 
+impl Default for BenchmarkNumRuns {
+    fn default() -> Self {
+        BenchmarkNumRuns(5)
+    }
+}
+
 impl std::fmt::Display for BenchmarkNumRuns {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
@@ -28,3 +36,447 @@ impl FromStr for BenchmarkNumRuns {
         Ok(BenchmarkNumRuns(number))
     }
 }
+
+const SPARKLINE_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const MAX_HISTOGRAM_BUCKETS: usize = 10;
+const SIGNIFICANCE_LEVEL: f64 = 0.05;
+
+/// Statistics for the timings of a benchmark's runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkStats {
+    timings: Vec<Duration>,
+    pub min: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+    pub median: Duration,
+    pub p90: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+    pub std_dev: Duration,
+    /// Standard deviation relative to the mean.
+    pub coefficient_of_variation: f64,
+    /// Indices of the runs that fall outside Tukey's fences, ie. are more than 1.5 times the
+    /// interquartile range below the first quartile, or above the third one.
+    pub outliers: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistogramBucket {
+    pub start: Duration,
+    pub end: Duration,
+    pub count: usize,
+}
+
+impl BenchmarkStats {
+    /// Computes statistics for timings (in the order the runs happened in); returns `None` if
+    /// there are no timings.
+    ///
+    /// Percentiles are linearly interpolated between the closest ranks, and the standard
+    /// deviation is that of a sample (ie. uses Bessel's correction).
+    pub fn new(timings: Vec<Duration>) -> Option<Self> {
+        let nanos: Vec<f64> = timings.iter().map(|t| t.as_nanos() as f64).collect();
+        let mut sorted = nanos.clone();
+        sorted.sort_by(f64::total_cmp);
+
+        let (&min, &max) = (sorted.first()?, sorted.last()?);
+        let n = sorted.len() as f64;
+        let mean = sorted.iter().sum::<f64>() / n;
+        let variance = if sorted.len() > 1 {
+            sorted.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / (n - 1.0)
+        } else {
+            0.0
+        };
+        let std_dev = variance.sqrt();
+        let coefficient_of_variation = if mean > 0.0 { std_dev / mean } else { 0.0 };
+
+        let q1 = percentile(&sorted, 25.0);
+        let q3 = percentile(&sorted, 75.0);
+        let iqr = q3 - q1;
+        let (lower_fence, upper_fence) = (q1 - 1.5 * iqr, q3 + 1.5 * iqr);
+        let outliers = nanos
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| **t < lower_fence || **t > upper_fence)
+            .map(|(i, _)| i)
+            .collect();
+
+        Some(Self {
+            min: nanos_to_duration(min),
+            max: nanos_to_duration(max),
+            mean: nanos_to_duration(mean),
+            median: nanos_to_duration(percentile(&sorted, 50.0)),
+            p90: nanos_to_duration(percentile(&sorted, 90.0)),
+            p95: nanos_to_duration(percentile(&sorted, 95.0)),
+            p99: nanos_to_duration(percentile(&sorted, 99.0)),
+            std_dev: nanos_to_duration(std_dev),
+            coefficient_of_variation,
+            outliers,
+            timings,
+        })
+    }
+
+    /// Timings of the runs, in the order they happened in.
+    pub fn timings(&self) -> &[Duration] {
+        &self.timings
+    }
+
+    /// Groups the timings into equal width buckets spanning `min..=max`; the number of buckets
+    /// is the square root of the number of runs (capped at 10).
+    pub fn histogram(&self) -> Vec<HistogramBucket> {
+        let num_buckets =
+            ((self.timings.len() as f64).sqrt().ceil() as usize).clamp(1, MAX_HISTOGRAM_BUCKETS);
+        let min = self.min.as_nanos() as f64;
+        let width = (self.max.as_nanos() as f64 - min) / num_buckets as f64;
+
+        let mut counts = vec![0; num_buckets];
+        for timing in &self.timings {
+            let index = if width > 0.0 {
+                ((timing.as_nanos() as f64 - min) / width) as usize
+            } else {
+                0
+            };
+            counts[index.min(num_buckets - 1)] += 1;
+        }
+
+        counts
+            .into_iter()
+            .enumerate()
+            .map(|(i, count)| HistogramBucket {
+                start: nanos_to_duration(min + width * i as f64),
+                end: nanos_to_duration(min + width * (i + 1) as f64),
+                count,
+            })
+            .collect()
+    }
+
+    /// Returns a sparkline of the timings, with a character per run.
+    pub fn sparkline(&self) -> String {
+        let min = self.min.as_nanos() as f64;
+        let range = self.max.as_nanos() as f64 - min;
+        let top_level = (SPARKLINE_LEVELS.len() - 1) as f64;
+
+        self.timings
+            .iter()
+            .map(|timing| {
+                let level = if range > 0.0 {
+                    ((timing.as_nanos() as f64 - min) / range * top_level).round() as usize
+                } else {
+                    0
+                };
+                SPARKLINE_LEVELS[level.min(SPARKLINE_LEVELS.len() - 1)]
+            })
+            .collect()
+    }
+}
+
+/// A query benchmarked alongside others (with runs interleaved between them).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComparedQuery {
+    pub label: String,
+    pub stats: BenchmarkStats,
+    /// Distinct numbers of rows the query's runs returned; ideally just the one.
+    pub row_counts: BTreeSet<usize>,
+}
+
+/// How a query's timings compare to those of a reference query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Speedup {
+    /// The reference's median divided by the query's; above 1 means the query is faster.
+    pub ratio: f64,
+    /// Two-sided p-value of a Mann-Whitney U test on both sets of timings, ie. the probability
+    /// of seeing a difference at least this large if neither query was faster.
+    pub p_value: f64,
+}
+
+impl Speedup {
+    pub fn new(reference: &BenchmarkStats, stats: &BenchmarkStats) -> Self {
+        let ratio = if stats.median.is_zero() {
+            1.0
+        } else {
+            reference.median.as_secs_f64() / stats.median.as_secs_f64()
+        };
+
+        Self {
+            ratio,
+            p_value: mann_whitney_p_value(&reference.timings, &stats.timings),
+        }
+    }
+
+    /// Whether the difference is statistically significant (at the 5% level).
+    pub fn is_significant(&self) -> bool {
+        self.p_value < SIGNIFICANCE_LEVEL
+    }
+}
+
+/// Formats a duration in milliseconds, with microsecond precision, eg. `12.345ms`.
+pub fn format_duration(duration: Duration) -> String {
+    format!("{:.3}ms", duration.as_secs_f64() * 1000.0)
+}
+
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+/// Uses the normal approximation of the U statistic (with corrections for ties and continuity),
+/// which is reasonable from around 8 runs per query; timings don't need to be normally
+/// distributed, which latencies seldom are.
+fn mann_whitney_p_value(a: &[Duration], b: &[Duration]) -> f64 {
+    let (n1, n2) = (a.len() as f64, b.len() as f64);
+    if a.is_empty() || b.is_empty() {
+        return 1.0;
+    }
+
+    let mut combined: Vec<(Duration, bool)> = a
+        .iter()
+        .map(|t| (*t, true))
+        .chain(b.iter().map(|t| (*t, false)))
+        .collect();
+    combined.sort_by_key(|(t, _)| *t);
+
+    // tied timings share the average of the ranks they span
+    let mut rank_sum_a = 0.0;
+    let mut tie_term = 0.0;
+    let mut start = 0;
+    while start < combined.len() {
+        let end = combined[start..]
+            .iter()
+            .position(|(t, _)| *t != combined[start].0)
+            .map_or(combined.len(), |p| start + p);
+        let num_tied = (end - start) as f64;
+        let average_rank = (start + end + 1) as f64 / 2.0;
+        rank_sum_a += combined[start..end]
+            .iter()
+            .filter(|(_, in_a)| *in_a)
+            .count() as f64
+            * average_rank;
+        tie_term += num_tied.powi(3) - num_tied;
+        start = end;
+    }
+
+    let n = n1 + n2;
+    let u = rank_sum_a - n1 * (n1 + 1.0) / 2.0;
+    let mean = n1 * n2 / 2.0;
+    let variance = n1 * n2 / 12.0 * ((n + 1.0) - tie_term / (n * (n - 1.0)));
+    if variance <= 0.0 {
+        return 1.0;
+    }
+
+    let z = ((u - mean).abs() - 0.5).max(0.0) / variance.sqrt();
+    erfc(z / std::f64::consts::SQRT_2).min(1.0)
+}
+
+/// Complementary error function, as per Abramowitz and Stegun's approximation 7.1.26 (accurate
+/// to around 1e-7, which is plenty for a p-value).
+fn erfc(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.327_591_1 * x);
+    let polynomial = t
+        * (0.254_829_592
+            + t * (-0.284_496_736
+                + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+
+    polynomial * (-x * x).exp()
+}
+
+fn nanos_to_duration(nanos: f64) -> Duration {
+    Duration::from_nanos(nanos.round().max(0.0) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::{assert_debug_snapshot, assert_snapshot};
+
+    //-------------//
+    //  SUCCESSES  //
+    //-------------//
+
+    #[test]
+    fn benchmark_stats_are_computed_correctly() {
+        // GIVEN
+        let timings = millis(&[
+            12.5, 10.25, 11.0, 48.75, 10.5, 11.25, 12.0, 10.75, 11.5, 9.5,
+        ]);
+
+        // WHEN
+        let result = BenchmarkStats::new(timings).expect("stats should've been computed");
+
+        // THEN
+        assert_debug_snapshot!(result, @r"
+        BenchmarkStats {
+            timings: [
+                12.5ms,
+                10.25ms,
+                11ms,
+                48.75ms,
+                10.5ms,
+                11.25ms,
+                12ms,
+                10.75ms,
+                11.5ms,
+                9.5ms,
+            ],
+            min: 9.5ms,
+            max: 48.75ms,
+            mean: 14.8ms,
+            median: 11.125ms,
+            p90: 16.125ms,
+            p95: 32.4375ms,
+            p99: 45.4875ms,
+            std_dev: 11.959887ms,
+            coefficient_of_variation: 0.8081004499119888,
+            outliers: [
+                3,
+            ],
+        }
+        ");
+    }
+
+    #[test]
+    fn benchmark_stats_handle_a_single_run() {
+        // GIVEN
+        let timings = millis(&[12.345]);
+
+        // WHEN
+        let result = BenchmarkStats::new(timings).expect("stats should've been computed");
+
+        // THEN
+        assert_eq!(result.median, Duration::from_micros(12345));
+        assert_eq!(result.std_dev, Duration::ZERO);
+        assert!(result.outliers.is_empty());
+    }
+
+    #[test]
+    fn histogram_groups_timings_into_buckets() {
+        // GIVEN
+        let timings = millis(&[
+            12.5, 10.25, 11.0, 48.75, 10.5, 11.25, 12.0, 10.75, 11.5, 9.5,
+        ]);
+        let stats = BenchmarkStats::new(timings).expect("stats should've been computed");
+
+        // WHEN
+        let result: Vec<String> = stats
+            .histogram()
+            .iter()
+            .map(|b| {
+                format!(
+                    "{} - {}: {}",
+                    format_duration(b.start),
+                    format_duration(b.end),
+                    b.count
+                )
+            })
+            .collect();
+
+        // THEN
+        assert_debug_snapshot!(result, @r#"
+        [
+            "9.500ms - 19.312ms: 9",
+            "19.312ms - 29.125ms: 0",
+            "29.125ms - 38.938ms: 0",
+            "38.938ms - 48.750ms: 1",
+        ]
+        "#);
+    }
+
+    #[test]
+    fn sparkline_has_a_character_per_run() {
+        // GIVEN
+        let timings = millis(&[10.0, 12.0, 14.0, 16.0, 18.0, 20.0, 22.0, 24.0, 17.0]);
+        let stats = BenchmarkStats::new(timings).expect("stats should've been computed");
+
+        // WHEN
+        let result = stats.sparkline();
+
+        // THEN
+        assert_snapshot!(result, @"▁▂▃▄▅▆▇█▅");
+    }
+
+    #[test]
+    fn sparkline_is_flat_for_identical_timings() {
+        // GIVEN
+        let stats =
+            BenchmarkStats::new(millis(&[5.0, 5.0, 5.0])).expect("stats should've been computed");
+
+        // WHEN
+        let result = stats.sparkline();
+
+        // THEN
+        assert_snapshot!(result, @"▁▁▁");
+    }
+
+    #[test]
+    fn speedup_is_significant_for_a_consistently_faster_query() {
+        // GIVEN
+        let reference = BenchmarkStats::new(millis(&[
+            20.5, 21.0, 19.75, 22.25, 20.0, 21.5, 20.25, 23.0, 19.5, 20.75,
+        ]))
+        .expect("stats should've been computed");
+        let faster = BenchmarkStats::new(millis(&[
+            10.25, 11.0, 10.5, 12.75, 10.0, 10.75, 11.5, 9.75, 10.25, 11.25,
+        ]))
+        .expect("stats should've been computed");
+
+        // WHEN
+        let result = Speedup::new(&reference, &faster);
+
+        // THEN
+        assert_eq!(format!("{:.2}", result.ratio), "1.94");
+        assert_eq!(format!("{:.5}", result.p_value), "0.00018");
+        assert!(result.is_significant());
+    }
+
+    #[test]
+    fn speedup_is_not_significant_for_overlapping_timings() {
+        // GIVEN
+        let reference =
+            BenchmarkStats::new(millis(&[10.5, 12.0, 11.25, 10.0, 13.5, 11.0, 12.5, 10.75]))
+                .expect("stats should've been computed");
+        let other =
+            BenchmarkStats::new(millis(&[11.0, 10.25, 12.75, 11.5, 10.5, 13.0, 11.75, 10.0]))
+                .expect("stats should've been computed");
+
+        // WHEN
+        let result = Speedup::new(&reference, &other);
+
+        // THEN
+        assert!(!result.is_significant());
+    }
+
+    #[test]
+    fn speedup_is_not_significant_for_identical_timings() {
+        // GIVEN
+        let stats =
+            BenchmarkStats::new(millis(&[5.0, 5.0, 5.0])).expect("stats should've been computed");
+
+        // WHEN
+        let result = Speedup::new(&stats, &stats);
+
+        // THEN
+        assert_eq!(result.ratio, 1.0);
+        assert_eq!(result.p_value, 1.0);
+    }
+
+    //------------//
+    //  FAILURES  //
+    //------------//
+
+    #[test]
+    fn benchmark_stats_are_not_computed_without_timings() {
+        // GIVEN
+        // WHEN
+        let result = BenchmarkStats::new(vec![]);
+
+        // THEN
+        assert!(result.is_none());
+    }
+
+    fn millis(values: &[f64]) -> Vec<Duration> {
+        values
+            .iter()
+            .map(|v| Duration::from_micros((v * 1000.0).round() as u64))
+            .collect()
+    }
+}
@@ -0,0 +1,318 @@
+use super::{BenchmarkStats, ParamSets};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// The outcome of a benchmark, in a form that can be persisted (and compared against later).
+///
+/// Timings are in microseconds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkReport {
+    pub query: String,
+    pub db_uri: String,
+    pub backend: String,
+    /// RFC 3339 timestamp of when the benchmark finished.
+    pub timestamp: String,
+    pub warmup_timings_us: Vec<u64>,
+    pub timings_us: Vec<u64>,
+    pub stats: BenchmarkReportStats,
+    /// Latencies per parameter set, slowest first; only present if the runs bound parameter
+    /// sets.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub param_sets: Vec<ParamSetReport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkReportStats {
+    pub min_us: u64,
+    pub max_us: u64,
+    pub mean_us: u64,
+    pub median_us: u64,
+    pub p90_us: u64,
+    pub p95_us: u64,
+    pub p99_us: u64,
+    pub std_dev_us: u64,
+    pub coefficient_of_variation: f64,
+    /// Numbers (starting at 1) of the runs that were flagged as outliers.
+    pub outlier_runs: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParamSetReport {
+    /// Number (starting at 1) of the parameter set, ie. its position in the parameter sets file.
+    pub param_set: usize,
+    /// The parameter set, as JSON.
+    pub params: String,
+    pub runs: usize,
+    pub median_us: u64,
+    pub mean_us: u64,
+    pub max_us: u64,
+}
+
+impl BenchmarkReport {
+    pub fn new(
+        query: &str,
+        db_uri: &str,
+        backend: &str,
+        warmup_timings: &[Duration],
+        stats: &BenchmarkStats,
+        reference_time: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            query: query.to_string(),
+            db_uri: db_uri.to_string(),
+            backend: backend.to_string(),
+            timestamp: reference_time.to_rfc3339(),
+            warmup_timings_us: warmup_timings.iter().map(|t| micros(*t)).collect(),
+            timings_us: stats.timings().iter().map(|t| micros(*t)).collect(),
+            stats: BenchmarkReportStats {
+                min_us: micros(stats.min),
+                max_us: micros(stats.max),
+                mean_us: micros(stats.mean),
+                median_us: micros(stats.median),
+                p90_us: micros(stats.p90),
+                p95_us: micros(stats.p95),
+                p99_us: micros(stats.p99),
+                std_dev_us: micros(stats.std_dev),
+                coefficient_of_variation: stats.coefficient_of_variation,
+                outlier_runs: stats.outliers.iter().map(|i| i + 1).collect(),
+            },
+            param_sets: vec![],
+        }
+    }
+
+    pub fn median(&self) -> Duration {
+        Duration::from_micros(self.stats.median_us)
+    }
+}
+
+/// Groups timings by the parameter set their runs bound (`run_param_sets` holds the index of
+/// the set for every run), slowest median first, so that slow keys stand out.
+pub fn param_set_breakdown(
+    param_sets: &ParamSets,
+    run_param_sets: &[usize],
+    timings: &[Duration],
+) -> Vec<ParamSetReport> {
+    let mut timings_per_set = vec![vec![]; param_sets.len()];
+    for (index, timing) in run_param_sets.iter().zip(timings) {
+        if let Some(set_timings) = timings_per_set.get_mut(*index) {
+            set_timings.push(*timing);
+        }
+    }
+
+    let mut breakdown: Vec<ParamSetReport> = timings_per_set
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, set_timings)| {
+            let runs = set_timings.len();
+            let stats = BenchmarkStats::new(set_timings)?;
+            Some(ParamSetReport {
+                param_set: index + 1,
+                params: param_sets
+                    .get(index)
+                    .map(|p| p.to_json())
+                    .unwrap_or_default(),
+                runs,
+                median_us: micros(stats.median),
+                mean_us: micros(stats.mean),
+                max_us: micros(stats.max),
+            })
+        })
+        .collect();
+    breakdown.sort_by(|a, b| {
+        b.median_us
+            .cmp(&a.median_us)
+            .then(a.param_set.cmp(&b.param_set))
+    });
+
+    breakdown
+}
+
+/// A finished benchmark: the stats to display, along with the report to persist.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkSummary {
+    pub stats: BenchmarkStats,
+    pub report: BenchmarkReport,
+}
+
+/// How a benchmark's median compares to that of a baseline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BaselineComparison {
+    pub baseline_median: Duration,
+    pub median: Duration,
+    /// Change in the median relative to the baseline's, in percent (positive means slower).
+    pub change_percent: f64,
+}
+
+impl BaselineComparison {
+    pub fn new(baseline: &BenchmarkReport, current: &BenchmarkReport) -> Self {
+        let baseline_median = baseline.median();
+        let median = current.median();
+        let change_percent = if baseline_median.is_zero() {
+            0.0
+        } else {
+            (median.as_secs_f64() - baseline_median.as_secs_f64()) / baseline_median.as_secs_f64()
+                * 100.0
+        };
+
+        Self {
+            baseline_median,
+            median,
+            change_percent,
+        }
+    }
+
+    /// Whether the median got slower by more than `threshold_percent`.
+    pub fn is_regression(&self, threshold_percent: f64) -> bool {
+        self.change_percent > threshold_percent
+    }
+}
+
+fn micros(duration: Duration) -> u64 {
+    duration.as_micros() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{ParamSetOrder, QueryParams};
+    use chrono::TimeZone;
+    use insta::assert_yaml_snapshot;
+
+    #[test]
+    fn benchmark_report_is_built_from_stats() {
+        // GIVEN
+        let stats = BenchmarkStats::new(
+            [12.5, 10.25, 11.0, 48.75, 10.5]
+                .iter()
+                .map(|ms| Duration::from_micros((ms * 1000.0_f64).round() as u64))
+                .collect(),
+        )
+        .expect("stats should've been computed");
+        let reference_time = Utc
+            .with_ymd_and_hms(2025, 1, 16, 12, 0, 0)
+            .single()
+            .expect("timestamp should've been valid");
+
+        // WHEN
+        let result = BenchmarkReport::new(
+            "MATCH (n) RETURN n LIMIT 1",
+            "bolt://127.0.0.1:7687",
+            "neo4j",
+            &[Duration::from_micros(20_500)],
+            &stats,
+            reference_time,
+        );
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        query: MATCH (n) RETURN n LIMIT 1
+        db_uri: "bolt://127.0.0.1:7687"
+        backend: neo4j
+        timestamp: "2025-01-16T12:00:00+00:00"
+        warmup_timings_us:
+          - 20500
+        timings_us:
+          - 12500
+          - 10250
+          - 11000
+          - 48750
+          - 10500
+        stats:
+          min_us: 10250
+          max_us: 48750
+          mean_us: 18600
+          median_us: 11000
+          p90_us: 34250
+          p95_us: 41500
+          p99_us: 47300
+          std_dev_us: 16876
+          coefficient_of_variation: 0.9073625985178357
+          outlier_runs:
+            - 4
+        "#);
+    }
+
+    #[test]
+    fn baseline_comparison_detects_regressions() {
+        // GIVEN
+        let report = |median_us| BenchmarkReport {
+            query: "RETURN 1".to_string(),
+            db_uri: "bolt://127.0.0.1:7687".to_string(),
+            backend: "neo4j".to_string(),
+            timestamp: "2025-01-16T12:00:00+00:00".to_string(),
+            warmup_timings_us: vec![],
+            timings_us: vec![median_us],
+            stats: BenchmarkReportStats {
+                min_us: median_us,
+                max_us: median_us,
+                mean_us: median_us,
+                median_us,
+                p90_us: median_us,
+                p95_us: median_us,
+                p99_us: median_us,
+                std_dev_us: 0,
+                coefficient_of_variation: 0.0,
+                outlier_runs: vec![],
+            },
+            param_sets: vec![],
+        };
+        let baseline = report(10_000);
+
+        // WHEN
+        let slower = BaselineComparison::new(&baseline, &report(11_500));
+        let faster = BaselineComparison::new(&baseline, &report(8_000));
+
+        // THEN
+        assert_eq!(format!("{:.2}", slower.change_percent), "15.00");
+        assert!(slower.is_regression(10.0));
+        assert!(!slower.is_regression(20.0));
+        assert_eq!(format!("{:.2}", faster.change_percent), "-20.00");
+        assert!(!faster.is_regression(10.0));
+    }
+
+    #[test]
+    fn param_set_breakdown_lists_slowest_param_sets_first() -> anyhow::Result<()> {
+        // GIVEN
+        let param_sets = ParamSets::new(
+            vec![
+                QueryParams::from_json(r#"{"name": "Rust"}"#)?,
+                QueryParams::from_json(r#"{"name": "Python"}"#)?,
+                QueryParams::from_json(r#"{"name": "Go"}"#)?,
+            ],
+            ParamSetOrder::RoundRobin,
+        )?;
+        let run_param_sets = [0, 1, 2, 0, 1, 2, 0];
+        let timings: Vec<Duration> = [10_000, 45_000, 12_000, 11_000, 50_000, 12_500, 10_500]
+            .iter()
+            .map(|us| Duration::from_micros(*us))
+            .collect();
+
+        // WHEN
+        let result = param_set_breakdown(&param_sets, &run_param_sets, &timings);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - param_set: 2
+          params: "{\"name\":\"Python\"}"
+          runs: 2
+          median_us: 47500
+          mean_us: 47500
+          max_us: 50000
+        - param_set: 3
+          params: "{\"name\":\"Go\"}"
+          runs: 2
+          median_us: 12250
+          mean_us: 12250
+          max_us: 12500
+        - param_set: 1
+          params: "{\"name\":\"Rust\"}"
+          runs: 3
+          median_us: 10500
+          mean_us: 10500
+          max_us: 11000
+        "#);
+
+        Ok(())
+    }
+}
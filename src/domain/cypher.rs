@@ -0,0 +1,846 @@
+use super::PlanMode;
+
+/// Returns the names of the columns projected by the last `RETURN` clause of a query, in order.
+///
+/// Neither neo4rs' `Row` nor the AWS SDK's `Document` preserve the order of the keys the
+/// database returns, so this is what grafq relies on to display columns in the order they were
+/// asked for. An empty list is returned when the columns can't be determined (eg. for
+/// `RETURN *`, or when there's no `RETURN` clause).
+pub fn return_columns(query: &str) -> Vec<String> {
+    let masked = mask_query(query);
+    let mask = &masked.top_level;
+
+    let Some(return_start) = find_last_word(mask, "RETURN") else {
+        return vec![];
+    };
+    let projection_start = return_start + "RETURN".len();
+
+    let projection_end = ["ORDER", "SKIP", "LIMIT", "UNION"]
+        .iter()
+        .filter_map(|w| find_word(mask, w, projection_start))
+        .chain(
+            mask[projection_start..]
+                .find(';')
+                .map(|i| i + projection_start),
+        )
+        .min()
+        .unwrap_or(query.len());
+
+    let mut start = projection_start;
+    if let Some(distinct_start) = find_word(mask, "DISTINCT", start)
+        && mask[start..distinct_start].trim().is_empty()
+    {
+        start = distinct_start + "DISTINCT".len();
+    }
+
+    let mut columns = vec![];
+    let mut item_start = start;
+    for (i, c) in mask[start..projection_end].char_indices() {
+        if c == ',' {
+            columns.push(column_name(&masked, item_start, start + i));
+            item_start = start + i + 1;
+        }
+    }
+    columns.push(column_name(&masked, item_start, projection_end));
+
+    if columns.iter().any(|c| c.is_empty() || c == "*") {
+        return vec![];
+    }
+
+    columns
+}
+
+/// Returns whether input forms complete statements, ie. whether it ends with a top level `;`,
+/// and has no unclosed brackets, quotes, or block comments.
+pub fn is_statement_complete(input: &str) -> bool {
+    let masked = mask_query(input);
+
+    !masked.unterminated && masked.top_level.trim_end().ends_with(';')
+}
+
+/// Splits input into the statements in it, as separated by top level `;`s. The separators are
+/// not included in the statements, and statements that are empty (or only have comments) are
+/// left out.
+pub fn split_statements(input: &str) -> Vec<String> {
+    let masked = mask_query(input);
+
+    let mut statements = vec![];
+    let mut start = 0;
+    let ends = masked
+        .top_level
+        .match_indices(';')
+        .map(|(i, _)| i)
+        .chain(std::iter::once(input.len()));
+    for end in ends {
+        if !masked.code[start..end].trim().is_empty() {
+            statements.push(input[start..end].trim().to_string());
+        }
+        start = end + 1;
+    }
+
+    statements
+}
+
+/// Splits off a leading `EXPLAIN`/`PROFILE` (matched case insensitively) from a statement,
+/// returning the corresponding plan mode along with the rest of the statement.
+pub fn split_plan_prefix(statement: &str) -> Option<(PlanMode, &str)> {
+    let statement = statement.trim_start();
+    let (word, rest) = statement.split_once(char::is_whitespace)?;
+
+    let mode = if word.eq_ignore_ascii_case("EXPLAIN") {
+        PlanMode::Explain
+    } else if word.eq_ignore_ascii_case("PROFILE") {
+        PlanMode::Profile
+    } else {
+        return None;
+    };
+
+    match rest.trim() {
+        "" => None,
+        rest => Some((mode, rest)),
+    }
+}
+
+fn column_name(masked: &MaskedQuery, start: usize, end: usize) -> String {
+    let code = &masked.code;
+    let name = match find_last_word(&masked.top_level[start..end], "AS") {
+        Some(as_start) => code[start + as_start + "AS".len()..end].trim(),
+        None => code[start..end].trim(),
+    };
+
+    name.strip_prefix('`')
+        .and_then(|n| n.strip_suffix('`'))
+        .unwrap_or(name)
+        .to_string()
+}
+
+pub const KEYWORDS: &[&str] = &[
+    "ALL",
+    "AND",
+    "AS",
+    "ASC",
+    "ASCENDING",
+    "BY",
+    "CALL",
+    "CASE",
+    "CONSTRAINT",
+    "CONTAINS",
+    "CREATE",
+    "CSV",
+    "DELETE",
+    "DESC",
+    "DESCENDING",
+    "DETACH",
+    "DISTINCT",
+    "DROP",
+    "ELSE",
+    "END",
+    "ENDS",
+    "EXISTS",
+    "EXPLAIN",
+    "FALSE",
+    "FOR",
+    "FOREACH",
+    "FROM",
+    "HEADERS",
+    "IN",
+    "INDEX",
+    "IS",
+    "LIMIT",
+    "LOAD",
+    "MATCH",
+    "MERGE",
+    "NOT",
+    "NULL",
+    "ON",
+    "OPTIONAL",
+    "OR",
+    "ORDER",
+    "PROFILE",
+    "REMOVE",
+    "REQUIRE",
+    "RETURN",
+    "SET",
+    "SHOW",
+    "SKIP",
+    "STARTS",
+    "THEN",
+    "TRUE",
+    "UNION",
+    "UNIQUE",
+    "UNWIND",
+    "USE",
+    "WHEN",
+    "WHERE",
+    "WITH",
+    "XOR",
+    "YIELD",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Keyword,
+    Label,
+    RelationshipType,
+    PropertyKey,
+    String,
+    Number,
+    Parameter,
+    Comment,
+    Bracket,
+}
+
+/// A token in a query, spanning the bytes `start..end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Splits a (possibly incomplete) query into the tokens that are of interest when displaying it;
+/// anything else (variables, function names, operators, etc.) is not returned.
+pub fn tokenize(query: &str) -> Vec<Token> {
+    let chars: Vec<(usize, char)> = query.char_indices().collect();
+    let byte_at = |i: usize| chars.get(i).map(|(b, _)| *b).unwrap_or(query.len());
+    let char_at = |i: usize| chars.get(i).map(|(_, c)| *c);
+
+    let mut tokens = vec![];
+    let mut brackets: Vec<char> = vec![];
+    // the kind of name expected after a `:` (or a `|`/`&` in a label expression)
+    let mut expected_name: Option<TokenKind> = None;
+    let mut previous: Option<char> = None;
+    let mut i = 0;
+
+    while let Some(c) = char_at(i) {
+        let start = byte_at(i);
+        let next = char_at(i + 1);
+        let mut push = |kind: TokenKind, end: usize| {
+            tokens.push(Token {
+                kind,
+                start,
+                end: byte_at(end),
+            })
+        };
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let mut end = i + 1;
+        match c {
+            '/' if next == Some('/') => {
+                while char_at(end).is_some_and(|c| c != '\n') {
+                    end += 1;
+                }
+                push(TokenKind::Comment, end);
+                i = end;
+                continue;
+            }
+            '/' if next == Some('*') => {
+                end = i + 2;
+                while let Some(c) = char_at(end) {
+                    end += 1;
+                    if c == '*' && char_at(end) == Some('/') {
+                        end += 1;
+                        break;
+                    }
+                }
+                push(TokenKind::Comment, end);
+                i = end;
+                continue;
+            }
+            '\'' | '"' => {
+                while let Some(ch) = char_at(end) {
+                    end += 1;
+                    if ch == '\\' {
+                        end += 1;
+                    } else if ch == c {
+                        break;
+                    }
+                }
+                push(TokenKind::String, end.min(chars.len()));
+            }
+            '$' => {
+                end = scan_name(&chars, i + 1);
+                if end > i + 1 {
+                    push(TokenKind::Parameter, end);
+                }
+            }
+            '(' | '[' | '{' => {
+                brackets.push(c);
+                push(TokenKind::Bracket, end);
+            }
+            ')' | ']' | '}' => {
+                brackets.pop();
+                push(TokenKind::Bracket, end);
+            }
+            '0'..='9' => {
+                end = scan_number(&chars, i);
+                push(TokenKind::Number, end);
+            }
+            c if c.is_alphabetic() || c == '_' || c == '`' => {
+                end = scan_name(&chars, i);
+                let next_significant = chars[end.min(chars.len())..]
+                    .iter()
+                    .map(|(_, c)| *c)
+                    .find(|c| !c.is_whitespace());
+
+                let kind = if let Some(kind) = expected_name {
+                    Some(kind)
+                } else if previous == Some('.')
+                    || (brackets.last() == Some(&'{') && next_significant == Some(':'))
+                {
+                    Some(TokenKind::PropertyKey)
+                } else {
+                    let word = &query[start..byte_at(end)];
+                    KEYWORDS
+                        .contains(&word.to_ascii_uppercase().as_str())
+                        .then_some(TokenKind::Keyword)
+                };
+
+                if let Some(kind) = kind {
+                    push(kind, end);
+                }
+                expected_name = None;
+                previous = Some('a');
+                i = end;
+                continue;
+            }
+            _ => {}
+        }
+
+        expected_name = match c {
+            ':' if brackets.last() != Some(&'{') => Some(match brackets.last() {
+                Some('[') => TokenKind::RelationshipType,
+                _ => TokenKind::Label,
+            }),
+            '|' | '&' | '!' => tokens
+                .last()
+                .map(|t| t.kind)
+                .filter(|k| matches!(k, TokenKind::Label | TokenKind::RelationshipType)),
+            _ => None,
+        };
+        previous = Some(c);
+        i = end;
+    }
+
+    tokens
+}
+
+/// Returns the index after the name (a plain or a backticked identifier) starting at `start`.
+fn scan_name(chars: &[(usize, char)], start: usize) -> usize {
+    let mut end = start;
+    if chars.get(start).is_some_and(|(_, c)| *c == '`') {
+        end += 1;
+        while let Some((_, c)) = chars.get(end) {
+            end += 1;
+            if *c == '`' {
+                break;
+            }
+        }
+        return end;
+    }
+
+    while chars
+        .get(end)
+        .is_some_and(|(_, c)| c.is_alphanumeric() || *c == '_')
+    {
+        end += 1;
+    }
+
+    end
+}
+
+/// Returns the index after the number starting at `start`, eg. `42`, `3.14`, `1e-3`, `0xff`.
+fn scan_number(chars: &[(usize, char)], start: usize) -> usize {
+    let char_at = |i: usize| chars.get(i).map(|(_, c)| *c);
+    let mut end = start;
+
+    if char_at(start) == Some('0') && matches!(char_at(start + 1), Some('x') | Some('X')) {
+        end += 2;
+        while char_at(end).is_some_and(|c| c.is_ascii_hexdigit()) {
+            end += 1;
+        }
+        return end;
+    }
+
+    while char_at(end).is_some_and(|c| c.is_ascii_digit()) {
+        end += 1;
+    }
+    if char_at(end) == Some('.') && char_at(end + 1).is_some_and(|c| c.is_ascii_digit()) {
+        end += 1;
+        while char_at(end).is_some_and(|c| c.is_ascii_digit()) {
+            end += 1;
+        }
+    }
+    if matches!(char_at(end), Some('e') | Some('E')) {
+        let mut exp_end = end + 1;
+        if matches!(char_at(exp_end), Some('+') | Some('-')) {
+            exp_end += 1;
+        }
+        if char_at(exp_end).is_some_and(|c| c.is_ascii_digit()) {
+            end = exp_end;
+            while char_at(end).is_some_and(|c| c.is_ascii_digit()) {
+                end += 1;
+            }
+        }
+    }
+
+    end
+}
+
+/// Returns the position of the bracket matching the one at `index` (a byte offset), if there's
+/// a bracket there (outside of strings and comments) and it has a match.
+pub fn matching_bracket(tokens: &[Token], query: &str, index: usize) -> Option<usize> {
+    let brackets: Vec<(usize, char)> = tokens
+        .iter()
+        .filter(|t| t.kind == TokenKind::Bracket)
+        .filter_map(|t| query[t.start..].chars().next().map(|c| (t.start, c)))
+        .collect();
+    let position = brackets.iter().position(|(start, _)| *start == index)?;
+    let (_, bracket) = brackets[position];
+
+    let (open, close, forward) = match bracket {
+        '(' => ('(', ')', true),
+        '[' => ('[', ']', true),
+        '{' => ('{', '}', true),
+        ')' => ('(', ')', false),
+        ']' => ('[', ']', false),
+        '}' => ('{', '}', false),
+        _ => return None,
+    };
+
+    let mut depth = 0usize;
+    let candidates: Box<dyn Iterator<Item = &(usize, char)>> = if forward {
+        Box::new(brackets[position..].iter())
+    } else {
+        Box::new(brackets[..=position].iter().rev())
+    };
+    for (start, c) in candidates {
+        if *c == open || *c == close {
+            let opens = (*c == open) == forward;
+            if opens {
+                depth += 1;
+            } else {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(*start);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+struct MaskedQuery {
+    /// The query with comments blanked out.
+    code: String,
+    /// An uppercased copy of `code` in which the contents of strings and brackets are blanked
+    /// out as well, so that only top level keywords and separators remain searchable.
+    top_level: String,
+    /// Whether the query ends inside brackets, a quote, or a block comment.
+    unterminated: bool,
+}
+
+/// Masks a query while retaining byte offsets, so that positions found in the masked versions
+/// can be used to slice the original.
+fn mask_query(query: &str) -> MaskedQuery {
+    let mut code = String::with_capacity(query.len());
+    let mut top_level = String::with_capacity(query.len());
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    let mut chars = query.chars().peekable();
+    let mut in_line_comment = false;
+    let mut in_block_comment = false;
+
+    let blank = |s: &mut String, c: char| {
+        for _ in 0..c.len_utf8() {
+            s.push(' ');
+        }
+    };
+
+    while let Some(c) = chars.next() {
+        if in_line_comment {
+            if c == '\n' {
+                in_line_comment = false;
+                code.push(c);
+                top_level.push(c);
+            } else {
+                blank(&mut code, c);
+                blank(&mut top_level, c);
+            }
+            continue;
+        }
+
+        if in_block_comment {
+            if c == '*' && chars.peek() == Some(&'/') {
+                chars.next();
+                in_block_comment = false;
+                code.push_str("  ");
+                top_level.push_str("  ");
+            } else {
+                blank(&mut code, c);
+                blank(&mut top_level, c);
+            }
+            continue;
+        }
+
+        code.push(c);
+
+        if let Some(q) = quote {
+            if c == '\\' && q != '`' {
+                blank(&mut top_level, c);
+                if let Some(escaped) = chars.next() {
+                    code.push(escaped);
+                    blank(&mut top_level, escaped);
+                }
+                continue;
+            }
+            if c == q {
+                quote = None;
+                top_level.push(if depth == 0 { c } else { ' ' });
+            } else {
+                blank(&mut top_level, c);
+            }
+            continue;
+        }
+
+        match c {
+            '/' if matches!(chars.peek(), Some('/') | Some('*')) => {
+                code.pop();
+                in_line_comment = chars.next() == Some('/');
+                in_block_comment = !in_line_comment;
+                code.push_str("  ");
+                top_level.push_str("  ");
+            }
+            '\'' | '"' | '`' => {
+                quote = Some(c);
+                top_level.push(if depth == 0 { c } else { ' ' });
+            }
+            '(' | '[' | '{' => {
+                top_level.push(if depth == 0 { c } else { ' ' });
+                depth += 1;
+            }
+            ')' | ']' | '}' => {
+                depth = depth.saturating_sub(1);
+                top_level.push(if depth == 0 { c } else { ' ' });
+            }
+            _ if depth > 0 => blank(&mut top_level, c),
+            _ => top_level.push(c.to_ascii_uppercase()),
+        }
+    }
+
+    MaskedQuery {
+        code,
+        top_level,
+        unterminated: depth > 0 || quote.is_some() || in_block_comment,
+    }
+}
+
+fn is_word_at(mask: &str, index: usize, word: &str) -> bool {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+
+    let starts_word = mask[..index]
+        .chars()
+        .next_back()
+        .is_none_or(|c| !is_word_char(c));
+    let ends_word = mask[index + word.len()..]
+        .chars()
+        .next()
+        .is_none_or(|c| !is_word_char(c));
+
+    starts_word && ends_word
+}
+
+fn find_word(mask: &str, word: &str, from: usize) -> Option<usize> {
+    mask[from..]
+        .match_indices(word)
+        .map(|(i, _)| i + from)
+        .find(|&i| is_word_at(mask, i, word))
+}
+
+fn find_last_word(mask: &str, word: &str) -> Option<usize> {
+    mask.rmatch_indices(word)
+        .map(|(i, _)| i)
+        .find(|&i| is_word_at(mask, i, word))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_debug_snapshot;
+
+    //-------------//
+    //  SUCCESSES  //
+    //-------------//
+
+    #[test]
+    fn return_columns_returns_columns_in_order() {
+        // GIVEN
+        let query = "MATCH (l:Language) RETURN l.name, l.creator, l.year";
+
+        // WHEN
+        let result = return_columns(query);
+
+        // THEN
+        assert_debug_snapshot!(result, @r#"
+        [
+            "l.name",
+            "l.creator",
+            "l.year",
+        ]
+        "#);
+    }
+
+    #[test]
+    fn return_columns_uses_aliases() {
+        // GIVEN
+        let query = r#"
+MATCH (l:Language)
+RETURN DISTINCT l.name AS language, l.creator as `created by`, count(*) AS num
+ORDER BY l.year DESC
+LIMIT 5;
+"#;
+
+        // WHEN
+        let result = return_columns(query);
+
+        // THEN
+        assert_debug_snapshot!(result, @r#"
+        [
+            "language",
+            "created by",
+            "num",
+        ]
+        "#);
+    }
+
+    #[test]
+    fn return_columns_ignores_commas_and_keywords_in_nested_expressions() {
+        // GIVEN
+        let query = r#"
+MATCH (l:Language)
+WITH l, [x IN l.tags WHERE x <> "return, limit" | x] AS tags
+RETURN {name: l.name, year: l.year} AS info, coalesce(l.creator, 'unknown') AS creator, tags
+"#;
+
+        // WHEN
+        let result = return_columns(query);
+
+        // THEN
+        assert_debug_snapshot!(result, @r#"
+        [
+            "info",
+            "creator",
+            "tags",
+        ]
+        "#);
+    }
+
+    #[test]
+    fn return_columns_ignores_comments() {
+        // GIVEN
+        let query = r#"
+MATCH (l:Language)
+// RETURN l.comment
+RETURN l.name, /* l.ignored, */ l.year
+"#;
+
+        // WHEN
+        let result = return_columns(query);
+
+        // THEN
+        assert_debug_snapshot!(result, @r#"
+        [
+            "l.name",
+            "l.year",
+        ]
+        "#);
+    }
+
+    #[test]
+    fn is_statement_complete_requires_a_terminating_semicolon() {
+        // GIVEN
+        let inputs = [
+            ("MATCH (n) RETURN n", false),
+            ("MATCH (n) RETURN n;", true),
+            ("MATCH (n)\nRETURN n;  // all nodes", true),
+            ("MATCH (n) RETURN n; MATCH (m)", false),
+            ("RETURN 1; RETURN 2;", true),
+            ("MATCH (n {name: 'a;'}) RETURN n;", true),
+        ];
+
+        // WHEN
+        // THEN
+        for (input, expected) in inputs {
+            assert_eq!(is_statement_complete(input), expected, "input: {input}");
+        }
+    }
+
+    #[test]
+    fn is_statement_complete_ignores_semicolons_in_unterminated_input() {
+        // GIVEN
+        let inputs = [
+            "MATCH (n:Language) WHERE n.name IN ['Rust';",
+            "MATCH (n {name: \"Rust;",
+            "MATCH (n) /* RETURN n; */",
+            "MATCH (n) RETURN n // ;",
+            "RETURN {a: 1;",
+        ];
+
+        // WHEN
+        // THEN
+        for input in inputs {
+            assert!(
+                !is_statement_complete(input),
+                "{input} shouldn't have been complete"
+            );
+        }
+    }
+
+    #[test]
+    fn split_statements_splits_on_top_level_semicolons() {
+        // GIVEN
+        let input = r#"
+MATCH (l:Language {name: "Rust; Go"})
+RETURN l;
+// a comment; with a semicolon
+MATCH (l:Language) RETURN count(l);
+;  /* only a comment */
+"#;
+
+        // WHEN
+        let result = split_statements(input);
+
+        // THEN
+        assert_debug_snapshot!(result, @r#"
+        [
+            "MATCH (l:Language {name: \"Rust; Go\"})\nRETURN l",
+            "// a comment; with a semicolon\nMATCH (l:Language) RETURN count(l)",
+        ]
+        "#);
+    }
+
+    #[test]
+    fn split_plan_prefix_detects_explain_and_profile() {
+        // GIVEN
+        let inputs = [
+            "EXPLAIN MATCH (n) RETURN n",
+            "  profile\n  MATCH (n) RETURN n",
+            "MATCH (n) RETURN n",
+            "EXPLAINED MATCH (n) RETURN n",
+            "explain",
+        ];
+
+        // WHEN
+        let result: Vec<_> = inputs.iter().map(|i| split_plan_prefix(i)).collect();
+
+        // THEN
+        assert_debug_snapshot!(result, @r#"
+        [
+            Some(
+                (
+                    Explain,
+                    "MATCH (n) RETURN n",
+                ),
+            ),
+            Some(
+                (
+                    Profile,
+                    "MATCH (n) RETURN n",
+                ),
+            ),
+            None,
+            None,
+            None,
+        ]
+        "#);
+    }
+
+    #[test]
+    fn tokenize_classifies_tokens() {
+        // GIVEN
+        let query = r#"
+MATCH (l:Language {name: $name, `first release`: 1.5e3})-[r:INFLUENCED|INSPIRED]->(o)
+WHERE o.year > 0x7d0 AND NOT o:Esoteric // recent ones
+/* multi-line
+comment */ RETURN o.name AS name, 'it\'s done'
+"#;
+
+        // WHEN
+        let result: Vec<String> = tokenize(query)
+            .into_iter()
+            .map(|t| format!("{:?}: {}", t.kind, &query[t.start..t.end]))
+            .collect();
+
+        // THEN
+        assert_debug_snapshot!(result);
+    }
+
+    #[test]
+    fn tokenize_handles_unterminated_input() {
+        // GIVEN
+        let query = r#"MATCH (l {name: "Ru"#;
+
+        // WHEN
+        let result: Vec<String> = tokenize(query)
+            .into_iter()
+            .map(|t| format!("{:?}: {}", t.kind, &query[t.start..t.end]))
+            .collect();
+
+        // THEN
+        assert_debug_snapshot!(result, @r#"
+        [
+            "Keyword: MATCH",
+            "Bracket: (",
+            "Bracket: {",
+            "PropertyKey: name",
+            "String: \"Ru",
+        ]
+        "#);
+    }
+
+    #[test]
+    fn matching_bracket_finds_brackets_in_either_direction() {
+        // GIVEN
+        let query = "RETURN [(1), {a: ')'}]";
+        let tokens = tokenize(query);
+
+        // WHEN
+        let forward = matching_bracket(&tokens, query, 7);
+        let backward = matching_bracket(&tokens, query, 21);
+        let inside_string = matching_bracket(&tokens, query, 18);
+
+        // THEN
+        assert_eq!(forward, Some(21));
+        assert_eq!(backward, Some(7));
+        assert_eq!(inside_string, None);
+    }
+
+    #[test]
+    fn return_columns_returns_nothing_for_star_projection() {
+        // GIVEN
+        let query = "MATCH (l:Language) RETURN *";
+
+        // WHEN
+        let result = return_columns(query);
+
+        // THEN
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn return_columns_returns_nothing_without_return_clause() {
+        // GIVEN
+        let query = "CREATE (l:Language {name: 'Rust', returning: true})";
+
+        // WHEN
+        let result = return_columns(query);
+
+        // THEN
+        assert!(result.is_empty());
+    }
+}
@@ -0,0 +1,96 @@
+use std::str::FromStr;
+
+/// Whether results are displayed as a record per block of `column | value` lines, rather than as
+/// a table (like psql's `\x`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExpandedMode {
+    #[default]
+    Off,
+    On,
+    // only when the table would be wider than the terminal
+    Auto,
+}
+
+impl FromStr for ExpandedMode {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "off" => Ok(Self::Off),
+            "on" => Ok(Self::On),
+            "auto" => Ok(Self::Auto),
+            _ => Err("invalid mode provided; allowed values: [on, off, auto]"),
+        }
+    }
+}
+
+impl std::fmt::Display for ExpandedMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = match self {
+            ExpandedMode::Off => "off",
+            ExpandedMode::On => "on",
+            ExpandedMode::Auto => "auto",
+        };
+        write!(f, "{value}")
+    }
+}
+
+/// What happens to table cells that are wider than their column is allowed to be.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum CellOverflow {
+    // cut them short, with an ellipsis
+    #[default]
+    Truncate,
+    // wrap them over several lines (with nested values pretty-printed as JSON)
+    Wrap,
+}
+
+impl FromStr for CellOverflow {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "truncate" => Ok(Self::Truncate),
+            "wrap" => Ok(Self::Wrap),
+            _ => Err("invalid overflow provided; allowed values: [truncate, wrap]"),
+        }
+    }
+}
+
+impl std::fmt::Display for CellOverflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = match self {
+            CellOverflow::Truncate => "truncate",
+            CellOverflow::Wrap => "wrap",
+        };
+        write!(f, "{value}")
+    }
+}
+
+/// Settings for displaying results in a terminal.
+///
+/// Tables are fit to the terminal's width (when it's known); `max_column_width` caps every
+/// column's width on top of that, and `max_rows` the number of rows displayed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DisplayOptions {
+    pub expanded: ExpandedMode,
+    pub max_column_width: Option<usize>,
+    pub overflow: CellOverflow,
+    pub max_rows: Option<usize>,
+}
+
+/// Parses a display limit (a column's width, or a number of rows), where `unset` turns the limit
+/// off.
+pub fn parse_display_limit(value: &str, unset: &str) -> Result<Option<usize>, String> {
+    let value = value.trim();
+    if value == unset {
+        return Ok(None);
+    }
+
+    match value.parse::<usize>() {
+        Ok(limit) if limit > 0 => Ok(Some(limit)),
+        _ => Err(format!(
+            "invalid limit provided; needs to be a positive number, or \"{unset}\""
+        )),
+    }
+}
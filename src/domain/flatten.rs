@@ -0,0 +1,219 @@
+use super::GraphValue;
+use std::collections::BTreeMap;
+
+/// What flattening results does with arrays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ArrayFlattening {
+    // keep them as JSON, in a single column
+    Json,
+    // spread them over indexed columns, eg. "tags.0", "tags.1"
+    Index,
+    // emit a row per element
+    Explode,
+}
+
+impl std::fmt::Display for ArrayFlattening {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = match self {
+            ArrayFlattening::Json => "json",
+            ArrayFlattening::Index => "index",
+            ArrayFlattening::Explode => "explode",
+        };
+        write!(f, "{value}")
+    }
+}
+
+type FlatRow = Vec<(String, GraphValue)>;
+
+/// Flattens nested values in rows into dotted columns, eg. `{"l": {"name": "Rust"}}` turns into
+/// `{"l.name": "Rust"}`; nodes and relationships are flattened the same way they're serialized,
+/// ie. into `id`, `labels`/`type`, and `properties.*` columns.
+///
+/// The returned columns are the union of the flattened keys across all rows, grouped by the
+/// column they come from (in `columns`' order), and in the order they were first seen in.
+///
+/// Rows that aren't objects are flattened as if they were the value of `value_column`.
+///
+/// Exploding arrays multiplies rows: a row with two 3-element arrays turns into 9 rows.
+pub(super) fn flatten_rows(
+    columns: &[String],
+    rows: &[GraphValue],
+    value_column: &str,
+    arrays: ArrayFlattening,
+) -> (Vec<String>, Vec<GraphValue>) {
+    let mut column_keys: Vec<Vec<String>> = vec![vec![]; columns.len()];
+    let mut flattened_rows = vec![];
+
+    for row in rows {
+        let wrapped;
+        let map = match row.as_map() {
+            Some(map) => map,
+            None => {
+                wrapped = BTreeMap::from([(value_column.to_string(), row.clone())]);
+                &wrapped
+            }
+        };
+
+        let mut variants: Vec<FlatRow> = vec![vec![]];
+        for (column, keys) in columns.iter().zip(column_keys.iter_mut()) {
+            let column_variants = match map.get(column) {
+                Some(value) => flatten_value(column, value, arrays),
+                None => vec![vec![]],
+            };
+            for (key, _) in column_variants.iter().flatten() {
+                if !keys.contains(key) {
+                    keys.push(key.clone());
+                }
+            }
+            variants = cartesian_product(variants, &column_variants);
+        }
+
+        flattened_rows.extend(
+            variants
+                .into_iter()
+                .map(|pairs| GraphValue::Map(pairs.into_iter().collect())),
+        );
+    }
+
+    (column_keys.into_iter().flatten().collect(), flattened_rows)
+}
+
+/// Returns the variants a value flattens into (more than one only when exploding arrays).
+fn flatten_value(prefix: &str, value: &GraphValue, arrays: ArrayFlattening) -> Vec<FlatRow> {
+    match value {
+        GraphValue::Map(map) if !map.is_empty() => {
+            flatten_entries(prefix, map.iter().map(|(k, v)| (k.clone(), v)), arrays)
+        }
+        GraphValue::Node(_) | GraphValue::Relationship(_) => {
+            match serde_json::to_value(value).map(GraphValue::from) {
+                Ok(map @ GraphValue::Map(_)) => flatten_value(prefix, &map, arrays),
+                _ => vec![vec![(prefix.to_string(), value.clone())]],
+            }
+        }
+        // an empty array doesn't have any indexed columns to fill
+        GraphValue::List(items) if arrays == ArrayFlattening::Index => flatten_entries(
+            prefix,
+            items.iter().enumerate().map(|(i, v)| (i.to_string(), v)),
+            arrays,
+        ),
+        GraphValue::List(items) if arrays == ArrayFlattening::Explode => {
+            if items.is_empty() {
+                return vec![vec![(prefix.to_string(), GraphValue::Null)]];
+            }
+            items
+                .iter()
+                .flat_map(|item| flatten_value(prefix, item, arrays))
+                .collect()
+        }
+        _ => vec![vec![(prefix.to_string(), value.clone())]],
+    }
+}
+
+fn flatten_entries<'a, I>(prefix: &str, entries: I, arrays: ArrayFlattening) -> Vec<FlatRow>
+where
+    I: Iterator<Item = (String, &'a GraphValue)>,
+{
+    entries.fold(vec![vec![]], |variants, (key, value)| {
+        cartesian_product(
+            variants,
+            &flatten_value(&format!("{prefix}.{key}"), value, arrays),
+        )
+    })
+}
+
+fn cartesian_product(left: Vec<FlatRow>, right: &[FlatRow]) -> Vec<FlatRow> {
+    left.into_iter()
+        .flat_map(|l| {
+            right.iter().map(move |r| {
+                let mut row = l.clone();
+                row.extend(r.iter().cloned());
+                row
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::VALUE_COLUMN;
+    use insta::assert_snapshot;
+
+    fn flatten(rows: Vec<serde_json::Value>, arrays: ArrayFlattening) -> String {
+        let columns = vec!["language".to_string(), "features".to_string()];
+        let rows: Vec<GraphValue> = rows.into_iter().map(Into::into).collect();
+
+        let (columns, rows) = flatten_rows(&columns, &rows, VALUE_COLUMN, arrays);
+
+        let mut lines = vec![columns.join(",")];
+        lines.extend(rows.iter().map(|row| {
+            columns
+                .iter()
+                .map(|c| {
+                    row.as_map()
+                        .and_then(|m| m.get(c))
+                        .map(|v| v.to_string())
+                        .unwrap_or_default()
+                })
+                .collect::<Vec<_>>()
+                .join(",")
+        }));
+        lines.join("\n")
+    }
+
+    fn sample_rows() -> Vec<serde_json::Value> {
+        vec![
+            serde_json::json!({
+                "language": "Rust",
+                "features": {"garbage_collection": false, "paradigms": ["functional", "imperative"]},
+            }),
+            serde_json::json!({
+                "language": "Go",
+                "features": {"garbage_collection": true, "static_typing": true, "paradigms": []},
+            }),
+        ]
+    }
+
+    #[test]
+    fn flatten_rows_expands_objects_into_dotted_columns() {
+        // GIVEN
+        // WHEN
+        let result = flatten(sample_rows(), ArrayFlattening::Json);
+
+        // THEN
+        assert_snapshot!(result, @r#"
+        language,features.garbage_collection,features.paradigms,features.static_typing
+        Rust,false,["functional", "imperative"],
+        Go,true,[],true
+        "#);
+    }
+
+    #[test]
+    fn flatten_rows_spreads_arrays_over_indexed_columns() {
+        // GIVEN
+        // WHEN
+        let result = flatten(sample_rows(), ArrayFlattening::Index);
+
+        // THEN
+        assert_snapshot!(result, @r"
+        language,features.garbage_collection,features.paradigms.0,features.paradigms.1,features.static_typing
+        Rust,false,functional,imperative,
+        Go,true,,,true
+        ");
+    }
+
+    #[test]
+    fn flatten_rows_explodes_arrays_into_rows() {
+        // GIVEN
+        // WHEN
+        let result = flatten(sample_rows(), ArrayFlattening::Explode);
+
+        // THEN
+        assert_snapshot!(result, @r"
+        language,features.garbage_collection,features.paradigms,features.static_typing
+        Rust,false,functional,
+        Rust,false,imperative,
+        Go,true,null,true
+        ");
+    }
+}
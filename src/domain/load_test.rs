@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// How long a load test runs for, eg. `30s`, `500ms`, `2m`, or `1h`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoadTestDuration(Duration);
+
+impl LoadTestDuration {
+    pub fn value(&self) -> Duration {
+        self.0
+    }
+}
+
+impl std::fmt::Display for LoadTestDuration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}s", self.0.as_secs_f64())
+    }
+}
+
+impl FromStr for LoadTestDuration {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let number_end = s
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(s.len());
+        let (number, unit) = s.split_at(number_end);
+
+        let number: f64 = number
+            .parse()
+            .map_err(|_| "value needs to be a number followed by a unit, eg. 30s")?;
+        let seconds = match unit {
+            "ms" => number / 1000.0,
+            "s" => number,
+            "m" => number * 60.0,
+            "h" => number * 3600.0,
+            _ => return Err("unit needs to be one of: ms, s, m, h"),
+        };
+
+        if seconds <= 0.0 {
+            return Err("needs to be greater than 0");
+        }
+
+        Duration::try_from_secs_f64(seconds)
+            .map(LoadTestDuration)
+            .map_err(|_| "value is too large")
+    }
+}
+
+/// Outcomes of the queries run as part of a load test.
+#[derive(Debug, Default)]
+pub struct LoadTestResults {
+    timings: Vec<Duration>,
+    errors: HashMap<String, usize>,
+}
+
+impl LoadTestResults {
+    pub fn record_success(&mut self, timing: Duration) {
+        self.timings.push(timing);
+    }
+
+    pub fn record_failure(&mut self, message: String) {
+        *self.errors.entry(message).or_default() += 1;
+    }
+
+    /// Timings of the queries that succeeded, in the order they finished in.
+    pub fn timings(&self) -> &[Duration] {
+        &self.timings
+    }
+
+    pub fn num_succeeded(&self) -> usize {
+        self.timings.len()
+    }
+
+    pub fn num_failed(&self) -> usize {
+        self.errors.values().sum()
+    }
+
+    /// Number of queries that succeeded per second.
+    pub fn throughput(&self, elapsed: Duration) -> f64 {
+        if elapsed.is_zero() {
+            return 0.0;
+        }
+
+        self.num_succeeded() as f64 / elapsed.as_secs_f64()
+    }
+
+    /// Error messages along with how many times they occurred, most frequent first.
+    pub fn errors(&self) -> Vec<(&str, usize)> {
+        let mut errors: Vec<(&str, usize)> = self
+            .errors
+            .iter()
+            .map(|(message, count)| (message.as_str(), *count))
+            .collect();
+        errors.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_debug_snapshot;
+
+    //-------------//
+    //  SUCCESSES  //
+    //-------------//
+
+    #[test]
+    fn load_test_duration_parses_supported_units() {
+        // GIVEN
+        let inputs = ["500ms", "30s", "1.5m", "1h"];
+
+        // WHEN
+        let result: Vec<_> = inputs
+            .iter()
+            .map(|i| LoadTestDuration::from_str(i).map(|d| d.value()))
+            .collect();
+
+        // THEN
+        assert_debug_snapshot!(result, @r"
+        [
+            Ok(
+                500ms,
+            ),
+            Ok(
+                30s,
+            ),
+            Ok(
+                90s,
+            ),
+            Ok(
+                3600s,
+            ),
+        ]
+        ");
+    }
+
+    #[test]
+    fn load_test_results_group_errors_by_message() {
+        // GIVEN
+        let mut results = LoadTestResults::default();
+        results.record_success(Duration::from_millis(10));
+        results.record_failure("couldn't execute query: timed out".to_string());
+        results.record_success(Duration::from_millis(20));
+        results.record_failure("couldn't execute query: connection reset".to_string());
+        results.record_failure("couldn't execute query: timed out".to_string());
+        results.record_success(Duration::from_millis(30));
+
+        // WHEN
+        let errors = results.errors();
+
+        // THEN
+        assert_eq!(results.num_succeeded(), 3);
+        assert_eq!(results.num_failed(), 3);
+        assert_eq!(results.throughput(Duration::from_millis(1500)), 2.0);
+        assert_debug_snapshot!(errors, @r#"
+        [
+            (
+                "couldn't execute query: timed out",
+                2,
+            ),
+            (
+                "couldn't execute query: connection reset",
+                1,
+            ),
+        ]
+        "#);
+    }
+
+    //------------//
+    //  FAILURES  //
+    //------------//
+
+    #[test]
+    fn load_test_duration_fails_for_invalid_input() {
+        // GIVEN
+        let inputs = [
+            "30",
+            "s",
+            "30d",
+            "0s",
+            "-1s",
+            "1000000000000000000000000000000s",
+        ];
+
+        // WHEN
+        let result: Vec<_> = inputs
+            .iter()
+            .map(|i| LoadTestDuration::from_str(i))
+            .collect();
+
+        // THEN
+        assert_debug_snapshot!(result, @r#"
+        [
+            Err(
+                "unit needs to be one of: ms, s, m, h",
+            ),
+            Err(
+                "value needs to be a number followed by a unit, eg. 30s",
+            ),
+            Err(
+                "unit needs to be one of: ms, s, m, h",
+            ),
+            Err(
+                "needs to be greater than 0",
+            ),
+            Err(
+                "value needs to be a number followed by a unit, eg. 30s",
+            ),
+            Err(
+                "value is too large",
+            ),
+        ]
+        "#);
+    }
+}
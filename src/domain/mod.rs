@@ -1,7 +1,27 @@
 mod benchmark;
+mod benchmark_report;
+mod cypher;
+mod display;
+mod flatten;
+mod load_test;
 mod pager;
+mod param_sets;
+mod params;
+mod plan;
 mod result;
+mod schema;
+mod value;
 
 pub use benchmark::*;
+pub use benchmark_report::*;
+pub use cypher::*;
+pub use display::*;
+pub use flatten::*;
+pub use load_test::*;
 pub use pager::*;
+pub use param_sets::*;
+pub use params::*;
+pub use plan::*;
 pub use result::*;
+pub use schema::*;
+pub use value::*;
@@ -1,36 +1,58 @@
 use anyhow::Context;
+use std::path::Path;
 use std::process::Command;
 
+const DEFAULT_PAGER: &str = "less";
+// pagers known to read from stdin; any other pager is given a file (unless passed "-")
+const STDIN_PAGERS: [&str; 4] = ["less", "more", "most", "bat"];
+
 pub struct Pager(PagerInner);
 
 impl Pager {
     pub fn default() -> anyhow::Result<Self> {
-        let pager = PagerInner::Default;
-
-        let binary = pager.binary();
-        which::which(&binary).with_context(|| {
-            format!(r#"couldn't find executable for grafq's default pager "{binary}""#)
+        which::which(DEFAULT_PAGER).with_context(|| {
+            format!(r#"couldn't find executable for grafq's default pager "{DEFAULT_PAGER}""#)
         })?;
 
-        Ok(Self(pager))
+        Ok(Self(PagerInner::Default))
     }
 
     pub fn custom(cmd: &str) -> anyhow::Result<Self> {
-        let pager = PagerInner::Custom(
-            CustomPager::try_from(cmd)
-                .with_context(|| format!(r#"couldn't build a pager from the command "{cmd}""#))?,
-        );
+        let custom_pager = CustomPager::try_from(cmd)
+            .with_context(|| format!(r#"couldn't build a pager from the command "{cmd}""#))?;
+
+        which::which(&custom_pager.binary).with_context(|| {
+            format!(
+                r#"couldn't find pager executable "{}""#,
+                custom_pager.binary
+            )
+        })?;
 
-        let binary = pager.binary();
-        which::which(&binary)
-            .with_context(|| format!(r#"couldn't find pager executable "{binary}""#))?;
+        Ok(Self(PagerInner::Custom(custom_pager)))
+    }
 
-        Ok(Self(pager))
+    /// grafq's own full-screen results browser, which is run in-process rather than as a command.
+    pub fn builtin() -> Self {
+        Self(PagerInner::Builtin)
     }
 }
 
 impl Pager {
-    pub fn get_command(&self) -> Command {
+    pub fn is_builtin(&self) -> bool {
+        matches!(self.0, PagerInner::Builtin)
+    }
+
+    /// Whether the pager reads what it displays from stdin, rather than from a file.
+    pub fn reads_stdin(&self) -> bool {
+        match &self.0 {
+            PagerInner::Default => true,
+            PagerInner::Custom(custom_pager) => custom_pager.reads_stdin(),
+            PagerInner::Builtin => false,
+        }
+    }
+
+    /// Returns the command to run the pager with; `None` for the built-in pager.
+    pub fn get_command(&self) -> Option<Command> {
         self.0.get_command()
     }
 }
@@ -38,30 +60,25 @@ impl Pager {
 enum PagerInner {
     Default,
     Custom(CustomPager),
+    Builtin,
 }
 
 impl PagerInner {
-    fn binary(&self) -> String {
-        match self {
-            Self::Default => "less".to_string(),
-            Self::Custom(custom_pager) => custom_pager.binary.clone(),
-        }
-    }
-
-    fn get_command(&self) -> Command {
+    fn get_command(&self) -> Option<Command> {
         match self {
             PagerInner::Default => {
-                let mut cmd = Command::new("less");
+                let mut cmd = Command::new(DEFAULT_PAGER);
                 cmd.arg("-+F");
-                cmd
+                Some(cmd)
             }
             PagerInner::Custom(custom_pager) => {
                 let mut cmd = Command::new(&custom_pager.binary);
                 if !custom_pager.args.is_empty() {
                     cmd.args(&custom_pager.args);
                 }
-                cmd
+                Some(cmd)
             }
+            PagerInner::Builtin => None,
         }
     }
 }
@@ -72,6 +89,17 @@ struct CustomPager {
     args: Vec<String>,
 }
 
+impl CustomPager {
+    fn reads_stdin(&self) -> bool {
+        let name = Path::new(&self.binary)
+            .file_stem()
+            .map(|s| s.to_string_lossy())
+            .unwrap_or_default();
+
+        STDIN_PAGERS.contains(&name.as_ref()) || self.args.iter().any(|a| a == "-")
+    }
+}
+
 impl TryFrom<&str> for CustomPager {
     type Error = anyhow::Error;
 
@@ -145,6 +173,57 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn custom_pager_reads_stdin_if_it_is_a_known_stdin_pager() -> anyhow::Result<()> {
+        // GIVEN
+        let pager_cmds = [
+            "bat -p --paging always",
+            "/usr/bin/less -S",
+            "nvim",
+            "/usr/bin/vim -R",
+            "nvim -R -",
+        ];
+
+        // WHEN
+        let result = pager_cmds
+            .iter()
+            .map(|cmd| CustomPager::try_from(*cmd).map(|p| format!("{cmd}: {}", p.reads_stdin())))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        // THEN
+        assert_snapshot!(result.join("\n"), @r"
+        bat -p --paging always: true
+        /usr/bin/less -S: true
+        nvim: false
+        /usr/bin/vim -R: false
+        nvim -R -: true
+        ");
+
+        Ok(())
+    }
+
+    #[test]
+    fn custom_pager_reads_file_if_it_is_unknown() -> anyhow::Result<()> {
+        // GIVEN
+        let pager_cmds = ["code --wait", "vd", "subl -w", "vd -"];
+
+        // WHEN
+        let result = pager_cmds
+            .iter()
+            .map(|cmd| CustomPager::try_from(*cmd).map(|p| format!("{cmd}: {}", p.reads_stdin())))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        // THEN
+        assert_snapshot!(result.join("\n"), @r"
+        code --wait: false
+        vd: false
+        subl -w: false
+        vd -: true
+        ");
+
+        Ok(())
+    }
+
     //------------//
     //  FAILURES  //
     //------------//
@@ -0,0 +1,226 @@
+use super::QueryParams;
+use super::params::parse_param_value;
+use anyhow::Context;
+use rand::Rng;
+use std::path::Path;
+
+/// Order in which a benchmark's runs go through parameter sets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ParamSetOrder {
+    // one after the other, starting over once all of them have been used
+    RoundRobin,
+    // a randomly picked one for every run
+    Random,
+}
+
+impl std::fmt::Display for ParamSetOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = match self {
+            ParamSetOrder::RoundRobin => "round-robin",
+            ParamSetOrder::Random => "random",
+        };
+        write!(f, "{value}")
+    }
+}
+
+/// Sets of query params for a benchmark's runs to bind, read from a CSV file (with a column per
+/// param, and a set per row) or an NDJSON file (with a JSON object per line).
+///
+/// CSV values are parsed the same way `--param` values are, ie. as JSON, falling back to plain
+/// strings.
+#[derive(Debug, Clone)]
+pub struct ParamSets {
+    sets: Vec<QueryParams>,
+    order: ParamSetOrder,
+    next: usize,
+}
+
+impl ParamSets {
+    pub fn from_file<P>(path: P, order: ParamSetOrder) -> anyhow::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let parse = match path.extension().and_then(|e| e.to_str()) {
+            Some("csv") => parse_csv,
+            Some("ndjson" | "jsonl") => parse_ndjson,
+            _ => anyhow::bail!(
+                "parameter sets file needs to have a .csv, .ndjson or .jsonl extension: {}",
+                path.display()
+            ),
+        };
+
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("couldn't read parameter sets file: {}", path.display()))?;
+        let sets = parse(&contents)
+            .with_context(|| format!("couldn't parse parameter sets file: {}", path.display()))?;
+
+        Self::new(sets, order)
+            .with_context(|| format!("couldn't use parameter sets file: {}", path.display()))
+    }
+
+    pub fn new(sets: Vec<QueryParams>, order: ParamSetOrder) -> anyhow::Result<Self> {
+        if sets.is_empty() {
+            anyhow::bail!("there are no parameter sets");
+        }
+
+        Ok(Self {
+            sets,
+            order,
+            next: 0,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.sets.len()
+    }
+
+    pub fn order(&self) -> ParamSetOrder {
+        self.order
+    }
+
+    pub fn get(&self, index: usize) -> Option<&QueryParams> {
+        self.sets.get(index)
+    }
+
+    /// Returns the index of the parameter set the next run is to use.
+    pub fn next_index(&mut self) -> usize {
+        match self.order {
+            ParamSetOrder::RoundRobin => {
+                let index = self.next % self.sets.len();
+                self.next += 1;
+                index
+            }
+            ParamSetOrder::Random => rand::thread_rng().gen_range(0..self.sets.len()),
+        }
+    }
+}
+
+fn parse_csv(contents: &str) -> anyhow::Result<Vec<QueryParams>> {
+    let mut reader = csv::Reader::from_reader(contents.as_bytes());
+    let keys: Vec<String> = reader
+        .headers()
+        .context("couldn't read CSV header")?
+        .iter()
+        .map(|h| h.trim().trim_start_matches('$').to_string())
+        .collect();
+
+    reader
+        .records()
+        .enumerate()
+        .map(|(i, record)| {
+            let record = record.with_context(|| format!("couldn't read CSV record #{}", i + 1))?;
+            Ok(keys
+                .iter()
+                .cloned()
+                .zip(record.iter().map(parse_param_value))
+                .collect())
+        })
+        .collect()
+}
+
+fn parse_ndjson(contents: &str) -> anyhow::Result<Vec<QueryParams>> {
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            QueryParams::from_json(line)
+                .with_context(|| format!("line {} is not a valid parameter set", i + 1))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_snapshot;
+
+    //-------------//
+    //  SUCCESSES  //
+    //-------------//
+
+    #[test]
+    fn parse_csv_parses_values_as_json() -> anyhow::Result<()> {
+        // GIVEN
+        let contents = r#"$name,year,tags
+Rust,2010,"[""systems""]"
+Python,1991,
+"#;
+
+        // WHEN
+        let result = parse_csv(contents)?;
+
+        // THEN
+        let result: Vec<String> = result.iter().map(|p| p.to_json()).collect();
+        assert_snapshot!(result.join("\n"), @r#"
+        {"name":"Rust","tags":["systems"],"year":2010}
+        {"name":"Python","tags":"","year":1991}
+        "#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_ndjson_skips_blank_lines() -> anyhow::Result<()> {
+        // GIVEN
+        let contents = r#"{"name": "Rust", "year": 2010}
+
+{"name": "Python", "year": 1991}
+"#;
+
+        // WHEN
+        let result = parse_ndjson(contents)?;
+
+        // THEN
+        let result: Vec<String> = result.iter().map(|p| p.to_json()).collect();
+        assert_snapshot!(result.join("\n"), @r#"
+        {"name":"Rust","year":2010}
+        {"name":"Python","year":1991}
+        "#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn round_robin_order_cycles_through_param_sets() -> anyhow::Result<()> {
+        // GIVEN
+        let sets = vec![QueryParams::default(); 3];
+        let mut param_sets = ParamSets::new(sets, ParamSetOrder::RoundRobin)?;
+
+        // WHEN
+        let result: Vec<usize> = (0..7).map(|_| param_sets.next_index()).collect();
+
+        // THEN
+        assert_eq!(result, vec![0, 1, 2, 0, 1, 2, 0]);
+
+        Ok(())
+    }
+
+    //------------//
+    //  FAILURES  //
+    //------------//
+
+    #[test]
+    fn parse_ndjson_fails_for_non_object_lines() {
+        // GIVEN
+        let contents = "{\"name\": \"Rust\"}\n[1, 2]\n";
+
+        // WHEN
+        let result = parse_ndjson(contents).expect_err("result should've been an error");
+
+        // THEN
+        assert_snapshot!(format!("{result:#}"), @"line 2 is not a valid parameter set: params need to be a JSON object");
+    }
+
+    #[test]
+    fn param_sets_cannot_be_empty() {
+        // GIVEN
+        // WHEN
+        let result = ParamSets::new(vec![], ParamSetOrder::Random)
+            .expect_err("result should've been an error");
+
+        // THEN
+        assert_snapshot!(result, @"there are no parameter sets");
+    }
+}
@@ -0,0 +1,233 @@
+use anyhow::Context;
+use serde_json::{Map, Value};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Parameters bound to `$name` placeholders in a query.
+#[derive(Debug, Clone, Default)]
+pub struct QueryParams(Map<String, Value>);
+
+impl QueryParams {
+    pub fn from_json_file<P>(path: P) -> anyhow::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("couldn't read params file: {}", path.display()))?;
+
+        Self::from_json(&contents)
+            .with_context(|| format!("couldn't parse params file: {}", path.display()))
+    }
+
+    pub fn from_json(contents: &str) -> anyhow::Result<Self> {
+        let value: Value = serde_json::from_str(contents).context("params are not valid JSON")?;
+
+        match value {
+            Value::Object(map) => Ok(Self(map)),
+            _ => anyhow::bail!("params need to be a JSON object"),
+        }
+    }
+
+    pub fn set(&mut self, param: QueryParam) {
+        self.0.insert(param.key, param.value);
+    }
+
+    pub fn unset(&mut self, key: &str) -> Option<Value> {
+        self.0.remove(key)
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.0.iter()
+    }
+
+    pub fn to_json(&self) -> String {
+        Value::Object(self.0.clone()).to_string()
+    }
+
+    /// Returns a copy of these params, with the values in `overrides` taking precedence.
+    pub fn merged_with(&self, overrides: &QueryParams) -> QueryParams {
+        let mut merged = self.0.clone();
+        merged.extend(overrides.0.clone());
+
+        Self(merged)
+    }
+}
+
+impl FromIterator<(String, Value)> for QueryParams {
+    fn from_iter<T: IntoIterator<Item = (String, Value)>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl Extend<QueryParam> for QueryParams {
+    fn extend<T: IntoIterator<Item = QueryParam>>(&mut self, iter: T) {
+        for param in iter {
+            self.set(param);
+        }
+    }
+}
+
+/// A single `key=value` parameter; the value is parsed as JSON, falling back to a plain string.
+#[derive(Debug, Clone)]
+pub struct QueryParam {
+    key: String,
+    value: Value,
+}
+
+impl std::fmt::Display for QueryParam {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}={}", self.key, self.value)
+    }
+}
+
+impl FromStr for QueryParam {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or("parameter needs to be in the form key=value")?;
+
+        let key = key.trim().trim_start_matches('$');
+        if key.is_empty() {
+            return Err("parameter key is empty");
+        }
+
+        Ok(Self {
+            key: key.to_string(),
+            value: parse_param_value(value),
+        })
+    }
+}
+
+/// Parses a param's value as JSON, falling back to a plain string.
+pub(super) fn parse_param_value(value: &str) -> Value {
+    let value = value.trim();
+    serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_snapshot;
+
+    //-------------//
+    //  SUCCESSES  //
+    //-------------//
+
+    #[test]
+    fn query_param_parses_values_as_json() -> anyhow::Result<()> {
+        // GIVEN
+        let inputs = [
+            "limit=5",
+            "ratio=0.5",
+            "active=true",
+            "name=\"Alice\"",
+            "ids=[1, 2, 3]",
+            "filter={\"age\": 30}",
+            "nothing=null",
+        ];
+
+        // WHEN
+        let result = inputs
+            .iter()
+            .map(|i| QueryParam::from_str(i).map(|p| p.to_string()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(anyhow::Error::msg)?
+            .join("\n");
+
+        // THEN
+        assert_snapshot!(result, @r#"
+        limit=5
+        ratio=0.5
+        active=true
+        name="Alice"
+        ids=[1,2,3]
+        filter={"age":30}
+        nothing=null
+        "#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn query_param_falls_back_to_strings() -> anyhow::Result<()> {
+        // GIVEN
+        let inputs = ["name=Alice", "id=007", "$title = The Matrix", "empty="];
+
+        // WHEN
+        let result = inputs
+            .iter()
+            .map(|i| QueryParam::from_str(i).map(|p| p.to_string()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(anyhow::Error::msg)?
+            .join("\n");
+
+        // THEN
+        assert_snapshot!(result, @r#"
+        name="Alice"
+        id="007"
+        title="The Matrix"
+        empty=""
+        "#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn query_params_later_values_override_earlier_ones() -> anyhow::Result<()> {
+        // GIVEN
+        let mut params = QueryParams::from_json(r#"{"limit": 5, "name": "Alice"}"#)?;
+
+        // WHEN
+        params.extend([QueryParam::from_str("limit=10").map_err(anyhow::Error::msg)?]);
+
+        // THEN
+        assert_snapshot!(params.to_json(), @r#"{"limit":10,"name":"Alice"}"#);
+
+        Ok(())
+    }
+
+    //------------//
+    //  FAILURES  //
+    //------------//
+
+    #[test]
+    fn query_param_fails_without_separator() {
+        // GIVEN
+        // WHEN
+        let result = QueryParam::from_str("limit").expect_err("result should've been an error");
+
+        // THEN
+        assert_snapshot!(result, @"parameter needs to be in the form key=value");
+    }
+
+    #[test]
+    fn query_param_fails_for_empty_key() {
+        // GIVEN
+        // WHEN
+        let result = QueryParam::from_str("=5").expect_err("result should've been an error");
+
+        // THEN
+        assert_snapshot!(result, @"parameter key is empty");
+    }
+
+    #[test]
+    fn query_params_fail_for_non_object_json() {
+        // GIVEN
+        // WHEN
+        let result = QueryParams::from_json("[1, 2]").expect_err("result should've been an error");
+
+        // THEN
+        assert_snapshot!(result, @"params need to be a JSON object");
+    }
+}
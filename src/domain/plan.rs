@@ -0,0 +1,171 @@
+use super::{GraphValue, Properties, QueryResults};
+
+/// How much of a query's execution plan to fetch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum PlanMode {
+    // the plan the database would use, without executing the query
+    Explain,
+    // the plan along with the stats gathered by executing the query
+    Profile,
+    // same as profile, with additional details where the database provides them (Neptune)
+    Details,
+}
+
+impl std::fmt::Display for PlanMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = match self {
+            PlanMode::Explain => "explain",
+            PlanMode::Profile => "profile",
+            PlanMode::Details => "details",
+        };
+        write!(f, "{value}")
+    }
+}
+
+/// A step in a query's execution plan.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanOperator {
+    pub id: String,
+    pub name: String,
+    pub arguments: String,
+    /// Stats reported for the operator (eg. estimated rows, db hits), in the order the database
+    /// reported them in.
+    pub stats: Vec<(String, String)>,
+    /// Operators that consume this operator's output.
+    pub children: Vec<PlanOperator>,
+}
+
+/// A tree of operators; a query's plan can consist of several of these (eg. Neptune reports
+/// subqueries as separate trees).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanTree {
+    pub name: Option<String>,
+    pub root: PlanOperator,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryPlan {
+    pub trees: Vec<PlanTree>,
+}
+
+impl QueryPlan {
+    /// Flattens the plan into one row per operator (in depth first order), so that it can be
+    /// written to the filesystem the same way query results are.
+    pub fn to_results(&self) -> QueryResults {
+        fn push_rows(
+            tree_name: &str,
+            operator: &PlanOperator,
+            parent_id: Option<&str>,
+            rows: &mut Vec<GraphValue>,
+        ) {
+            let mut row = Properties::new();
+            row.insert(
+                "plan".to_string(),
+                GraphValue::String(tree_name.to_string()),
+            );
+            row.insert("id".to_string(), GraphValue::String(operator.id.clone()));
+            row.insert(
+                "parent".to_string(),
+                parent_id.map_or(GraphValue::Null, |id| GraphValue::String(id.to_string())),
+            );
+            row.insert(
+                "operator".to_string(),
+                GraphValue::String(operator.name.clone()),
+            );
+            row.insert(
+                "arguments".to_string(),
+                GraphValue::String(operator.arguments.clone()),
+            );
+            for (key, value) in &operator.stats {
+                row.insert(key.clone(), GraphValue::String(value.clone()));
+            }
+            rows.push(GraphValue::Map(row));
+
+            for child in &operator.children {
+                push_rows(tree_name, child, Some(&operator.id), rows);
+            }
+        }
+
+        let mut rows = Vec::new();
+        for tree in &self.trees {
+            push_rows(
+                tree.name.as_deref().unwrap_or("main"),
+                &tree.root,
+                None,
+                &mut rows,
+            );
+        }
+
+        let mut column_order: Vec<String> = ["plan", "id", "parent", "operator", "arguments"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        if let Some(tree) = self.trees.first() {
+            column_order.extend(tree.root.stats.iter().map(|(key, _)| key.clone()));
+        }
+
+        QueryResults::new(rows, column_order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_debug_snapshot;
+
+    #[test]
+    fn to_results_flattens_operators_in_depth_first_order() {
+        // GIVEN
+        let operator = |id: &str, name: &str, children| PlanOperator {
+            id: id.to_string(),
+            name: name.to_string(),
+            arguments: String::new(),
+            stats: vec![("rows".to_string(), id.to_string())],
+            children,
+        };
+        let plan = QueryPlan {
+            trees: vec![PlanTree {
+                name: None,
+                root: operator(
+                    "0",
+                    "ProduceResults",
+                    vec![
+                        operator(
+                            "1",
+                            "Filter",
+                            vec![operator("2", "NodeByLabelScan", vec![])],
+                        ),
+                        operator("3", "Argument", vec![]),
+                    ],
+                ),
+            }],
+        };
+
+        // WHEN
+        let results = plan.to_results();
+
+        // THEN
+        let QueryResults::NonEmpty(results) = results else {
+            panic!("results should've been non empty");
+        };
+        let rows: Vec<String> = results.list().iter().map(|r| r.to_string()).collect();
+        assert_debug_snapshot!((results.columns(), rows), @r#"
+        (
+            [
+                "plan",
+                "id",
+                "parent",
+                "operator",
+                "arguments",
+                "rows",
+            ],
+            [
+                "{arguments: \"\", id: \"0\", operator: \"ProduceResults\", parent: null, plan: \"main\", rows: \"0\"}",
+                "{arguments: \"\", id: \"1\", operator: \"Filter\", parent: \"0\", plan: \"main\", rows: \"1\"}",
+                "{arguments: \"\", id: \"2\", operator: \"NodeByLabelScan\", parent: \"1\", plan: \"main\", rows: \"2\"}",
+                "{arguments: \"\", id: \"3\", operator: \"Argument\", parent: \"0\", plan: \"main\", rows: \"3\"}",
+            ],
+        )
+        "#);
+    }
+}
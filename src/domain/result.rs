@@ -1,10 +1,19 @@
-use serde_json::Value;
+use super::GraphValue;
+use super::flatten::{ArrayFlattening, flatten_rows};
+use std::path::Path;
 use std::str::FromStr;
 
-#[derive(Debug, Clone, clap::ValueEnum)]
+#[derive(Debug, Clone, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ResultsFormat {
     Csv,
     Json,
+    Ndjson,
+    Tsv,
+    Parquet,
+    Arrow,
+    Markdown,
+    Html,
 }
 
 impl ResultsFormat {
@@ -12,8 +21,61 @@ impl ResultsFormat {
         match self {
             ResultsFormat::Csv => "csv",
             ResultsFormat::Json => "json",
+            ResultsFormat::Ndjson => "ndjson",
+            ResultsFormat::Tsv => "tsv",
+            ResultsFormat::Parquet => "parquet",
+            ResultsFormat::Arrow => "arrow",
+            ResultsFormat::Markdown => "md",
+            ResultsFormat::Html => "html",
         }
     }
+
+    /// The format a file's extension stands for (eg. CSV for `results.csv`), if any.
+    pub fn from_extension(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?;
+
+        Self::from_str(&extension.to_ascii_lowercase()).ok()
+    }
+
+    /// Whether results in this format aren't meant to be read as text (eg. in a pager).
+    pub fn is_binary(&self) -> bool {
+        matches!(self, ResultsFormat::Parquet | ResultsFormat::Arrow)
+    }
+
+    /// Whether this format can hold nested values as they are (as opposed to in a single cell).
+    pub fn is_nested(&self) -> bool {
+        matches!(self, ResultsFormat::Json | ResultsFormat::Ndjson)
+    }
+}
+
+/// Settings for writing results that only apply to some formats.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WriteOptions {
+    /// Compression for columnar formats (parquet and arrow).
+    pub compression: Option<ResultsCompression>,
+    /// Whether to flatten nested values into columns (in formats other than JSON and NDJSON),
+    /// and what to do with arrays while at it.
+    pub flatten: Option<ArrayFlattening>,
+}
+
+/// Compression codec for columnar results formats (parquet and arrow).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResultsCompression {
+    None,
+    Lz4,
+    Zstd,
+}
+
+impl std::fmt::Display for ResultsCompression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = match self {
+            ResultsCompression::None => "none",
+            ResultsCompression::Lz4 => "lz4",
+            ResultsCompression::Zstd => "zstd",
+        };
+        write!(f, "{value}")
+    }
 }
 
 impl FromStr for ResultsFormat {
@@ -24,39 +86,165 @@ impl FromStr for ResultsFormat {
         match trimmed {
             "csv" => Ok(Self::Csv),
             "json" => Ok(Self::Json),
-            _ => Err("invalid format provided; allowed values: [csv, json]"),
+            "ndjson" => Ok(Self::Ndjson),
+            "tsv" => Ok(Self::Tsv),
+            "parquet" => Ok(Self::Parquet),
+            "arrow" => Ok(Self::Arrow),
+            "markdown" | "md" => Ok(Self::Markdown),
+            "html" => Ok(Self::Html),
+            _ => Err(
+                "invalid format provided; allowed values: [csv, json, ndjson, tsv, parquet, arrow, markdown, html]",
+            ),
         }
     }
 }
 
 impl std::fmt::Display for ResultsFormat {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.extension())
+        match self {
+            ResultsFormat::Markdown => write!(f, "markdown"),
+            _ => write!(f, "{}", self.extension()),
+        }
     }
 }
 
-pub struct NonEmptyResults(Vec<Value>);
+/// Format results are displayed in (as opposed to written to the filesystem in).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    Table,
+    Csv,
+    Json,
+    Ndjson,
+    Tsv,
+    Parquet,
+    Arrow,
+    Markdown,
+    Html,
+}
+
+impl OutputFormat {
+    /// The results format to write results in, if this isn't a table.
+    pub fn results_format(&self) -> Option<ResultsFormat> {
+        match self {
+            OutputFormat::Table => None,
+            OutputFormat::Csv => Some(ResultsFormat::Csv),
+            OutputFormat::Json => Some(ResultsFormat::Json),
+            OutputFormat::Ndjson => Some(ResultsFormat::Ndjson),
+            OutputFormat::Tsv => Some(ResultsFormat::Tsv),
+            OutputFormat::Parquet => Some(ResultsFormat::Parquet),
+            OutputFormat::Arrow => Some(ResultsFormat::Arrow),
+            OutputFormat::Markdown => Some(ResultsFormat::Markdown),
+            OutputFormat::Html => Some(ResultsFormat::Html),
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "table" => Ok(Self::Table),
+            other => ResultsFormat::from_str(other)
+                .map(|f| OutputFormat::from(&f))
+                .map_err(|_| {
+                    "invalid format provided; allowed values: [table, csv, json, ndjson, tsv, parquet, arrow, markdown, html]"
+                }),
+        }
+    }
+}
+
+impl From<&ResultsFormat> for OutputFormat {
+    fn from(value: &ResultsFormat) -> Self {
+        match value {
+            ResultsFormat::Csv => OutputFormat::Csv,
+            ResultsFormat::Json => OutputFormat::Json,
+            ResultsFormat::Ndjson => OutputFormat::Ndjson,
+            ResultsFormat::Tsv => OutputFormat::Tsv,
+            ResultsFormat::Parquet => OutputFormat::Parquet,
+            ResultsFormat::Arrow => OutputFormat::Arrow,
+            ResultsFormat::Markdown => OutputFormat::Markdown,
+            ResultsFormat::Html => OutputFormat::Html,
+        }
+    }
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.results_format() {
+            Some(format) => write!(f, "{format}"),
+            None => write!(f, "table"),
+        }
+    }
+}
+
+/// Column that rows which aren't objects (eg. plain numbers or strings) are shown in; it's
+/// suffixed with a number (eg. `value_2`) if the query returns a column by this name as well.
+pub const VALUE_COLUMN: &str = "value";
+
+pub struct NonEmptyResults {
+    columns: Vec<String>,
+    rows: Vec<GraphValue>,
+    /// The column rows that aren't objects are shown in (see `VALUE_COLUMN`).
+    value_column: String,
+}
 
 impl NonEmptyResults {
-    pub fn list(&self) -> &[Value] {
-        &self.0
+    /// Column names, in the order the query returned them in.
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    pub fn list(&self) -> &[GraphValue] {
+        &self.rows
     }
 
-    pub fn first(&self) -> &Value {
-        &self.0[0]
+    /// Each row's values, in the order of `columns()` (with `None` for keys a row doesn't have);
+    /// rows that aren't objects only have a value in the `value` column.
+    pub fn cells(&self) -> impl Iterator<Item = Vec<Option<&GraphValue>>> {
+        self.rows.iter().map(|row| {
+            self.columns
+                .iter()
+                .map(|column| match row.as_map() {
+                    Some(map) => map.get(column),
+                    None => (column == &self.value_column).then_some(row),
+                })
+                .collect()
+        })
+    }
+
+    /// Returns the results with nested values flattened into dotted columns.
+    pub fn flattened(&self, arrays: ArrayFlattening) -> NonEmptyResults {
+        let (columns, rows) = flatten_rows(&self.columns, &self.rows, &self.value_column, arrays);
+
+        NonEmptyResults {
+            columns,
+            rows,
+            value_column: self.value_column.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl NonEmptyResults {
+    pub fn with_columns(columns: &[&str], rows: Vec<serde_json::Value>) -> Self {
+        Self {
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            rows: rows.into_iter().map(Into::into).collect(),
+            value_column: VALUE_COLUMN.to_string(),
+        }
     }
 }
 
 #[cfg(test)]
-impl TryFrom<Vec<Value>> for NonEmptyResults {
+impl TryFrom<Vec<serde_json::Value>> for NonEmptyResults {
     type Error = &'static str;
 
-    fn try_from(value: Vec<Value>) -> Result<Self, Self::Error> {
-        if value.is_empty() {
-            return Err("list is empty");
+    fn try_from(value: Vec<serde_json::Value>) -> Result<Self, Self::Error> {
+        match QueryResults::new(value.into_iter().map(Into::into).collect(), vec![]) {
+            QueryResults::Empty => Err("list is empty"),
+            QueryResults::NonEmpty(results) => Ok(results),
         }
-
-        Ok(Self(value))
     }
 }
 
@@ -65,12 +253,287 @@ pub enum QueryResults {
     NonEmpty(NonEmptyResults),
 }
 
-impl From<Vec<Value>> for QueryResults {
-    fn from(value: Vec<Value>) -> Self {
-        if value.is_empty() {
+impl QueryResults {
+    /// Builds results from the rows returned by a database.
+    ///
+    /// Columns are the union of the rows' keys, ordered as per `column_order` (usually the
+    /// query's projection); keys that aren't present in it are placed after those, in the order
+    /// they're first seen in. If any of the rows isn't an object, a `value` column is added for
+    /// it at the end (named so that it doesn't clash with the other columns).
+    pub fn new(rows: Vec<GraphValue>, column_order: Vec<String>) -> Self {
+        if rows.is_empty() {
             return QueryResults::Empty;
         }
 
-        QueryResults::NonEmpty(NonEmptyResults(value))
+        let mut keys: Vec<&String> = vec![];
+        let mut has_non_object_rows = false;
+        for row in &rows {
+            match row.as_map() {
+                Some(map) => {
+                    for key in map.keys() {
+                        if !keys.contains(&key) {
+                            keys.push(key);
+                        }
+                    }
+                }
+                None => has_non_object_rows = true,
+            }
+        }
+
+        let mut columns: Vec<String> = column_order
+            .into_iter()
+            .filter(|c| keys.contains(&c))
+            .collect();
+        for key in keys {
+            if !columns.contains(key) {
+                columns.push(key.clone());
+            }
+        }
+        let value_column = value_column_name(&columns);
+        if has_non_object_rows {
+            columns.push(value_column.clone());
+        }
+
+        QueryResults::NonEmpty(NonEmptyResults {
+            columns,
+            rows,
+            value_column,
+        })
+    }
+
+    pub fn num_rows(&self) -> usize {
+        match self {
+            QueryResults::Empty => 0,
+            QueryResults::NonEmpty(results) => results.rows.len(),
+        }
+    }
+}
+
+/// Returns `VALUE_COLUMN`, suffixed with the first number that makes it distinct from `columns`
+/// if need be.
+fn value_column_name(columns: &[String]) -> String {
+    let mut name = VALUE_COLUMN.to_string();
+    let mut suffix = 1;
+    while columns.contains(&name) {
+        suffix += 1;
+        name = format!("{VALUE_COLUMN}_{suffix}");
+    }
+
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_debug_snapshot;
+
+    #[test]
+    fn results_format_is_inferred_from_file_extensions() {
+        // GIVEN
+        let paths = [
+            "results.csv",
+            "out/results.TSV",
+            "results.md",
+            "results.parquet",
+            "results.txt",
+            "results",
+            "-",
+        ];
+
+        // WHEN
+        let result: Vec<_> = paths
+            .iter()
+            .map(|p| ResultsFormat::from_extension(Path::new(p)))
+            .collect();
+
+        // THEN
+        assert_debug_snapshot!(result, @r"
+        [
+            Some(
+                Csv,
+            ),
+            Some(
+                Tsv,
+            ),
+            Some(
+                Markdown,
+            ),
+            Some(
+                Parquet,
+            ),
+            None,
+            None,
+            None,
+        ]
+        ");
+    }
+
+    #[test]
+    fn query_results_orders_columns_as_per_column_order() {
+        // GIVEN
+        let rows = vec![
+            serde_json::json!({"language": "Rust", "creator": "Graydon Hoare", "year": 2010})
+                .into(),
+            serde_json::json!({"language": "Python", "creator": "Guido van Rossum", "year": 1991})
+                .into(),
+        ];
+        let column_order = vec!["year".to_string(), "language".to_string()];
+
+        // WHEN
+        let result = QueryResults::new(rows, column_order);
+
+        // THEN
+        let QueryResults::NonEmpty(results) = result else {
+            panic!("results should've been non empty");
+        };
+        assert_debug_snapshot!(results.columns(), @r#"
+        [
+            "year",
+            "language",
+            "creator",
+        ]
+        "#);
+    }
+
+    #[test]
+    fn query_results_ignores_columns_not_present_in_rows() {
+        // GIVEN
+        let rows = vec![serde_json::json!({"language": "Rust", "year": 2010}).into()];
+        let column_order = vec!["l.year".to_string(), "language".to_string()];
+
+        // WHEN
+        let result = QueryResults::new(rows, column_order);
+
+        // THEN
+        let QueryResults::NonEmpty(results) = result else {
+            panic!("results should've been non empty");
+        };
+        assert_debug_snapshot!(results.columns(), @r#"
+        [
+            "language",
+            "year",
+        ]
+        "#);
+    }
+
+    #[test]
+    fn query_results_takes_columns_from_all_rows() {
+        // GIVEN
+        let rows = vec![
+            serde_json::json!({"language": "Rust", "year": 2010}).into(),
+            serde_json::json!({"language": "Python", "creator": "Guido van Rossum"}).into(),
+            serde_json::json!({"language": "Go", "year": 2009, "paradigm": "concurrent"}).into(),
+        ];
+        let column_order = vec!["language".to_string()];
+
+        // WHEN
+        let result = QueryResults::new(rows, column_order);
+
+        // THEN
+        let QueryResults::NonEmpty(results) = result else {
+            panic!("results should've been non empty");
+        };
+        assert_debug_snapshot!(results.columns(), @r#"
+        [
+            "language",
+            "year",
+            "creator",
+            "paradigm",
+        ]
+        "#);
+    }
+
+    #[test]
+    fn query_results_adds_a_value_column_for_non_object_rows() {
+        // GIVEN
+        let rows = vec![
+            serde_json::json!({"language": "Rust"}).into(),
+            serde_json::json!(42).into(),
+            serde_json::json!(["a", "list"]).into(),
+        ];
+
+        // WHEN
+        let result = QueryResults::new(rows, vec![]);
+
+        // THEN
+        let QueryResults::NonEmpty(results) = result else {
+            panic!("results should've been non empty");
+        };
+        let cells: Vec<Vec<String>> = results
+            .cells()
+            .map(|row| {
+                row.iter()
+                    .map(|c| c.map(|v| v.to_string()).unwrap_or_default())
+                    .collect()
+            })
+            .collect();
+        assert_debug_snapshot!((results.columns(), cells), @r#"
+        (
+            [
+                "language",
+                "value",
+            ],
+            [
+                [
+                    "Rust",
+                    "",
+                ],
+                [
+                    "",
+                    "42",
+                ],
+                [
+                    "",
+                    "[\"a\", \"list\"]",
+                ],
+            ],
+        )
+        "#);
+    }
+
+    #[test]
+    fn query_results_keeps_value_column_apart_from_returned_one() {
+        // GIVEN
+        let rows = vec![
+            serde_json::json!({"value": "Rust", "value_2": 2010}).into(),
+            serde_json::json!(42).into(),
+        ];
+
+        // WHEN
+        let result = QueryResults::new(rows, vec![]);
+
+        // THEN
+        let QueryResults::NonEmpty(results) = result else {
+            panic!("results should've been non empty");
+        };
+        let cells: Vec<Vec<String>> = results
+            .cells()
+            .map(|row| {
+                row.iter()
+                    .map(|c| c.map(|v| v.to_string()).unwrap_or_default())
+                    .collect()
+            })
+            .collect();
+        assert_debug_snapshot!((results.columns(), cells), @r#"
+        (
+            [
+                "value",
+                "value_2",
+                "value_3",
+            ],
+            [
+                [
+                    "Rust",
+                    "2010",
+                    "",
+                ],
+                [
+                    "",
+                    "",
+                    "42",
+                ],
+            ],
+        )
+        "#);
     }
 }
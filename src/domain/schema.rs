@@ -0,0 +1,30 @@
+/// The labels, relationship types and property keys present in a database.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Schema {
+    pub labels: Vec<String>,
+    pub relationship_types: Vec<String>,
+    pub property_keys: Vec<String>,
+}
+
+impl Schema {
+    /// Builds a schema with each of its lists sorted and deduplicated.
+    pub fn new<L, R, P>(labels: L, relationship_types: R, property_keys: P) -> Self
+    where
+        L: IntoIterator<Item = String>,
+        R: IntoIterator<Item = String>,
+        P: IntoIterator<Item = String>,
+    {
+        let sorted = |items: Vec<String>| {
+            let mut items = items;
+            items.sort();
+            items.dedup();
+            items
+        };
+
+        Self {
+            labels: sorted(labels.into_iter().collect()),
+            relationship_types: sorted(relationship_types.into_iter().collect()),
+            property_keys: sorted(property_keys.into_iter().collect()),
+        }
+    }
+}
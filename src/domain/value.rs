@@ -0,0 +1,681 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_json::{Number, Value};
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter, Write};
+
+pub type Properties = BTreeMap<String, GraphValue>;
+
+/// A value returned by a graph database.
+///
+/// Both database backends map their responses into this type, so that nodes, relationships,
+/// paths, and temporal/spatial values keep their identity, and look the same regardless of the
+/// database they came from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphValue {
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(String),
+    Bytes(Vec<u8>),
+    List(Vec<GraphValue>),
+    Map(Properties),
+    Node(Node),
+    Relationship(Relationship),
+    Path(Path),
+    Date(NaiveDate),
+    Time(NaiveTime, FixedOffset),
+    LocalTime(NaiveTime),
+    DateTime(DateTime<FixedOffset>),
+    /// A datetime along with the ID of its time zone (eg. "Europe/Berlin").
+    ZonedDateTime(DateTime<FixedOffset>, String),
+    LocalDateTime(NaiveDateTime),
+    Duration(Duration),
+    Point(Point),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub id: String,
+    pub labels: Vec<String>,
+    pub properties: Properties,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Relationship {
+    pub id: String,
+    pub rel_type: String,
+    pub start_id: String,
+    pub end_id: String,
+    pub properties: Properties,
+}
+
+/// An alternating sequence of nodes and relationships; `nodes` always has one element more than
+/// `relationships`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path {
+    pub nodes: Vec<Node>,
+    pub relationships: Vec<Relationship>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Point {
+    pub srid: i64,
+    pub x: f64,
+    pub y: f64,
+    pub z: Option<f64>,
+}
+
+/// A duration the way graph databases model it: months and days don't have a fixed length, so
+/// they're kept apart from the seconds, and each of them can be negative.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Duration {
+    pub months: i64,
+    pub days: i64,
+    pub seconds: i64,
+    /// Added to `seconds`; between 0 and 999,999,999.
+    pub nanos: i64,
+}
+
+impl GraphValue {
+    pub fn as_map(&self) -> Option<&Properties> {
+        match self {
+            GraphValue::Map(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    /// Writes the value the way Cypher would express it, ie. with strings quoted.
+    fn write_literal(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphValue::String(s) => write_quoted(f, s),
+            GraphValue::List(items) => {
+                f.write_char('[')?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    item.write_literal(f)?;
+                }
+                f.write_char(']')
+            }
+            GraphValue::Map(map) => write_properties(f, map),
+            _ => write!(f, "{self}"),
+        }
+    }
+}
+
+/// Top level strings are displayed without quotes; everything else is displayed as a Cypher
+/// literal, eg. `(:Language {name: "Rust"})` for a node, and `[:CREATED_BY {year: 2010}]` for a
+/// relationship.
+impl Display for GraphValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphValue::Null => f.write_str("null"),
+            GraphValue::Bool(b) => write!(f, "{b}"),
+            GraphValue::Number(n) => write!(f, "{n}"),
+            GraphValue::String(s) => f.write_str(s),
+            GraphValue::Bytes(bytes) => {
+                f.write_str("0x")?;
+                for byte in bytes {
+                    write!(f, "{byte:02x}")?;
+                }
+                Ok(())
+            }
+            GraphValue::List(_) | GraphValue::Map(_) => self.write_literal(f),
+            GraphValue::Node(node) => write!(f, "{node}"),
+            GraphValue::Relationship(rel) => write!(f, "{rel}"),
+            GraphValue::Path(path) => write!(f, "{path}"),
+            GraphValue::Date(date) => write!(f, "{date}"),
+            GraphValue::Time(time, offset) => write!(f, "{time}{offset}"),
+            GraphValue::LocalTime(time) => write!(f, "{time}"),
+            GraphValue::DateTime(datetime) => f.write_str(&datetime.to_rfc3339()),
+            GraphValue::ZonedDateTime(datetime, zone_id) => {
+                write!(f, "{}[{zone_id}]", datetime.to_rfc3339())
+            }
+            GraphValue::LocalDateTime(datetime) => {
+                write!(f, "{}", datetime.format("%Y-%m-%dT%H:%M:%S%.f"))
+            }
+            GraphValue::Duration(duration) => write!(f, "{duration}"),
+            GraphValue::Point(point) => write!(f, "{point}"),
+        }
+    }
+}
+
+impl Display for Node {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_char('(')?;
+        for label in &self.labels {
+            write!(f, ":{}", escape_name(label))?;
+        }
+        if !self.properties.is_empty() {
+            if !self.labels.is_empty() {
+                f.write_char(' ')?;
+            }
+            write_properties(f, &self.properties)?;
+        }
+        f.write_char(')')
+    }
+}
+
+impl Display for Relationship {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "[:{}", escape_name(&self.rel_type))?;
+        if !self.properties.is_empty() {
+            f.write_char(' ')?;
+            write_properties(f, &self.properties)?;
+        }
+        f.write_char(']')
+    }
+}
+
+impl Display for Path {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let Some(first) = self.nodes.first() else {
+            return Ok(());
+        };
+
+        write!(f, "{first}")?;
+        for (rel, (from, to)) in self
+            .relationships
+            .iter()
+            .zip(self.nodes.iter().zip(self.nodes.iter().skip(1)))
+        {
+            if rel.start_id == to.id && rel.end_id == from.id {
+                write!(f, "<-{rel}-{to}")?;
+            } else {
+                write!(f, "-{rel}->{to}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Point {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "point({{srid: {}, x: {}, y: {}",
+            self.srid, self.x, self.y
+        )?;
+        if let Some(z) = self.z {
+            write!(f, ", z: {z}")?;
+        }
+        f.write_str("})")
+    }
+}
+
+/// Durations are displayed in the ISO 8601 format, the way Neo4j displays them (eg. `P1Y2M3DT1M30.5S`,
+/// or `P-1D`).
+impl Display for Duration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_char('P')?;
+        let (years, months) = (self.months / 12, self.months % 12);
+        if years != 0 {
+            write!(f, "{years}Y")?;
+        }
+        if months != 0 {
+            write!(f, "{months}M")?;
+        }
+        if self.days != 0 {
+            write!(f, "{}D", self.days)?;
+        }
+
+        let nanos = i128::from(self.seconds) * 1_000_000_000 + i128::from(self.nanos);
+        if nanos == 0 {
+            return match (self.months, self.days) {
+                (0, 0) => f.write_str("T0S"),
+                _ => Ok(()),
+            };
+        }
+
+        f.write_char('T')?;
+        let sign = if nanos < 0 { "-" } else { "" };
+        let (secs, fraction) = (
+            nanos.unsigned_abs() / 1_000_000_000,
+            nanos.unsigned_abs() % 1_000_000_000,
+        );
+        let (hours, minutes, secs) = (secs / 3600, secs / 60 % 60, secs % 60);
+        if hours != 0 {
+            write!(f, "{sign}{hours}H")?;
+        }
+        if minutes != 0 {
+            write!(f, "{sign}{minutes}M")?;
+        }
+        match fraction {
+            0 if secs == 0 => Ok(()),
+            0 => write!(f, "{sign}{secs}S"),
+            _ => {
+                let fraction = format!("{fraction:09}");
+                write!(f, "{sign}{secs}.{}S", fraction.trim_end_matches('0'))
+            }
+        }
+    }
+}
+
+fn write_quoted(f: &mut Formatter<'_>, s: &str) -> fmt::Result {
+    match serde_json::to_string(s) {
+        Ok(quoted) => f.write_str(&quoted),
+        Err(_) => write!(f, "{s:?}"),
+    }
+}
+
+fn write_properties(f: &mut Formatter<'_>, properties: &Properties) -> fmt::Result {
+    f.write_char('{')?;
+    for (i, (key, value)) in properties.iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{}: ", escape_name(key))?;
+        value.write_literal(f)?;
+    }
+    f.write_char('}')
+}
+
+/// Backticks names (labels, relationship types, property keys) that aren't plain identifiers.
+fn escape_name(name: &str) -> String {
+    let is_identifier = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+
+    if is_identifier {
+        name.to_string()
+    } else {
+        format!("`{}`", name.replace('`', "``"))
+    }
+}
+
+/// Graph entities are serialized as objects (eg. `{"id": .., "labels": [..], "properties": {..}}`
+/// for a node), and temporal values as ISO 8601 strings.
+impl Serialize for GraphValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            GraphValue::Null => serializer.serialize_unit(),
+            GraphValue::Bool(b) => serializer.serialize_bool(*b),
+            GraphValue::Number(n) => n.serialize(serializer),
+            GraphValue::String(s) => serializer.serialize_str(s),
+            GraphValue::Bytes(bytes) => serializer.collect_seq(bytes),
+            GraphValue::List(items) => serializer.collect_seq(items),
+            GraphValue::Map(map) => serializer.collect_map(map),
+            GraphValue::Node(node) => node.serialize(serializer),
+            GraphValue::Relationship(rel) => rel.serialize(serializer),
+            GraphValue::Path(path) => path.serialize(serializer),
+            GraphValue::Point(point) => point.serialize(serializer),
+            GraphValue::Date(_)
+            | GraphValue::Time(_, _)
+            | GraphValue::LocalTime(_)
+            | GraphValue::DateTime(_)
+            | GraphValue::ZonedDateTime(_, _)
+            | GraphValue::LocalDateTime(_)
+            | GraphValue::Duration(_) => serializer.collect_str(self),
+        }
+    }
+}
+
+impl Serialize for Node {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(3))?;
+        map.serialize_entry("id", &self.id)?;
+        map.serialize_entry("labels", &self.labels)?;
+        map.serialize_entry("properties", &self.properties)?;
+        map.end()
+    }
+}
+
+impl Serialize for Relationship {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(5))?;
+        map.serialize_entry("id", &self.id)?;
+        map.serialize_entry("type", &self.rel_type)?;
+        map.serialize_entry("start", &self.start_id)?;
+        map.serialize_entry("end", &self.end_id)?;
+        map.serialize_entry("properties", &self.properties)?;
+        map.end()
+    }
+}
+
+impl Serialize for Path {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("nodes", &self.nodes)?;
+        map.serialize_entry("relationships", &self.relationships)?;
+        map.end()
+    }
+}
+
+impl Serialize for Point {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("srid", &self.srid)?;
+        map.serialize_entry("x", &self.x)?;
+        map.serialize_entry("y", &self.y)?;
+        if let Some(z) = self.z {
+            map.serialize_entry("z", &z)?;
+        }
+        map.end()
+    }
+}
+
+impl From<Value> for GraphValue {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Null => GraphValue::Null,
+            Value::Bool(b) => GraphValue::Bool(b),
+            Value::Number(n) => GraphValue::Number(n),
+            Value::String(s) => GraphValue::String(s),
+            Value::Array(items) => GraphValue::List(items.into_iter().map(Into::into).collect()),
+            Value::Object(obj) => {
+                GraphValue::Map(obj.into_iter().map(|(k, v)| (k, v.into())).collect())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use insta::assert_snapshot;
+
+    //-------------//
+    //  SUCCESSES  //
+    //-------------//
+
+    #[test]
+    fn graph_entities_are_displayed_as_cypher_patterns() {
+        // GIVEN
+        let rust = language_node("1", "Rust");
+        let person = Node {
+            id: "2".to_string(),
+            labels: vec!["Person".to_string(), "Language Designer".to_string()],
+            properties: Properties::from([(
+                "name".to_string(),
+                GraphValue::String("Graydon Hoare".to_string()),
+            )]),
+        };
+        let created_by = created_by_relationship("3", "1", "2");
+        let values = [
+            GraphValue::Node(rust.clone()),
+            GraphValue::Relationship(created_by.clone()),
+            GraphValue::Path(Path {
+                nodes: vec![rust.clone(), person.clone()],
+                relationships: vec![created_by.clone()],
+            }),
+            GraphValue::Path(Path {
+                nodes: vec![person, rust],
+                relationships: vec![created_by],
+            }),
+            GraphValue::Node(Node {
+                id: "4".to_string(),
+                labels: vec![],
+                properties: Properties::new(),
+            }),
+        ];
+
+        // WHEN
+        let result = values
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        // THEN
+        assert_snapshot!(result, @r#"
+        (:Language {name: "Rust", tags: ["systems", "compiled"], year: 2010})
+        [:CREATED_BY {`first release`: 2015}]
+        (:Language {name: "Rust", tags: ["systems", "compiled"], year: 2010})-[:CREATED_BY {`first release`: 2015}]->(:Person:`Language Designer` {name: "Graydon Hoare"})
+        (:Person:`Language Designer` {name: "Graydon Hoare"})<-[:CREATED_BY {`first release`: 2015}]-(:Language {name: "Rust", tags: ["systems", "compiled"], year: 2010})
+        ()
+        "#);
+    }
+
+    #[test]
+    fn temporal_spatial_and_binary_values_are_displayed_correctly() -> anyhow::Result<()> {
+        // GIVEN
+        let offset = FixedOffset::east_opt(5 * 3600 + 1800).expect("offset should be valid");
+        let date = NaiveDate::from_ymd_opt(2015, 5, 15).expect("date should be valid");
+        let time = NaiveTime::from_hms_milli_opt(13, 45, 30, 250).expect("time should be valid");
+        let datetime = offset
+            .from_local_datetime(&date.and_time(time))
+            .single()
+            .expect("datetime should be valid");
+        let values = [
+            GraphValue::Date(date),
+            GraphValue::Time(time, offset),
+            GraphValue::LocalTime(time),
+            GraphValue::DateTime(datetime),
+            GraphValue::ZonedDateTime(datetime, "Asia/Kolkata".to_string()),
+            GraphValue::LocalDateTime(date.and_time(time)),
+            GraphValue::Duration(Duration {
+                months: 0,
+                days: 0,
+                seconds: 90,
+                nanos: 500_000_000,
+            }),
+            GraphValue::Duration(Duration {
+                months: 0,
+                days: 0,
+                seconds: 3600,
+                nanos: 0,
+            }),
+            GraphValue::Point(Point {
+                srid: 4326,
+                x: 12.5,
+                y: 55.0,
+                z: None,
+            }),
+            GraphValue::Point(Point {
+                srid: 9157,
+                x: 1.0,
+                y: 2.0,
+                z: Some(3.5),
+            }),
+            GraphValue::Bytes(vec![0, 15, 255]),
+        ];
+
+        // WHEN
+        let result = values
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        // THEN
+        assert_snapshot!(result, @r"
+        2015-05-15
+        13:45:30.250+05:30
+        13:45:30.250
+        2015-05-15T13:45:30.250+05:30
+        2015-05-15T13:45:30.250+05:30[Asia/Kolkata]
+        2015-05-15T13:45:30.250
+        PT1M30.5S
+        PT1H
+        point({srid: 4326, x: 12.5, y: 55})
+        point({srid: 9157, x: 1, y: 2, z: 3.5})
+        0x000fff
+        ");
+
+        Ok(())
+    }
+
+    #[test]
+    fn durations_are_displayed_in_iso_8601_format() {
+        // GIVEN
+        let durations = [
+            (0, 0, 0, 0),
+            (1, 0, 0, 0),
+            (14, 3, 0, 0),
+            (0, -1, 0, 0),
+            (-13, 0, 0, 0),
+            (0, 1, 90, 0),
+            (0, 0, -90, 0),
+            (0, 0, -1, 500_000_000),
+            (1, -2, 3661, 250_000_000),
+        ];
+
+        // WHEN
+        let result = durations
+            .iter()
+            .map(|&(months, days, seconds, nanos)| {
+                let duration = Duration {
+                    months,
+                    days,
+                    seconds,
+                    nanos,
+                };
+                format!("{months} months, {days} days, {seconds}s, {nanos}ns: {duration}")
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        // THEN
+        assert_snapshot!(result, @r"
+        0 months, 0 days, 0s, 0ns: PT0S
+        1 months, 0 days, 0s, 0ns: P1M
+        14 months, 3 days, 0s, 0ns: P1Y2M3D
+        0 months, -1 days, 0s, 0ns: P-1D
+        -13 months, 0 days, 0s, 0ns: P-1Y-1M
+        0 months, 1 days, 90s, 0ns: P1DT1M30S
+        0 months, 0 days, -90s, 0ns: PT-1M-30S
+        0 months, 0 days, -1s, 500000000ns: PT-0.5S
+        1 months, -2 days, 3661s, 250000000ns: P1M-2DT1H1M1.25S
+        ");
+    }
+
+    #[test]
+    fn graph_values_are_serialized_to_json_correctly() -> anyhow::Result<()> {
+        // GIVEN
+        let rust = language_node("1", "Rust");
+        let value = GraphValue::Map(Properties::from([
+            ("language".to_string(), GraphValue::Node(rust.clone())),
+            (
+                "path".to_string(),
+                GraphValue::Path(Path {
+                    nodes: vec![rust.clone(), rust],
+                    relationships: vec![created_by_relationship("3", "1", "1")],
+                }),
+            ),
+            (
+                "released".to_string(),
+                GraphValue::Date(
+                    NaiveDate::from_ymd_opt(2015, 5, 15).expect("date should be valid"),
+                ),
+            ),
+        ]));
+
+        // WHEN
+        let result = serde_json::to_string_pretty(&value)?;
+
+        // THEN
+        assert_snapshot!(result, @r#"
+        {
+          "language": {
+            "id": "1",
+            "labels": [
+              "Language"
+            ],
+            "properties": {
+              "name": "Rust",
+              "tags": [
+                "systems",
+                "compiled"
+              ],
+              "year": 2010
+            }
+          },
+          "path": {
+            "nodes": [
+              {
+                "id": "1",
+                "labels": [
+                  "Language"
+                ],
+                "properties": {
+                  "name": "Rust",
+                  "tags": [
+                    "systems",
+                    "compiled"
+                  ],
+                  "year": 2010
+                }
+              },
+              {
+                "id": "1",
+                "labels": [
+                  "Language"
+                ],
+                "properties": {
+                  "name": "Rust",
+                  "tags": [
+                    "systems",
+                    "compiled"
+                  ],
+                  "year": 2010
+                }
+              }
+            ],
+            "relationships": [
+              {
+                "id": "3",
+                "type": "CREATED_BY",
+                "start": "1",
+                "end": "1",
+                "properties": {
+                  "first release": 2015
+                }
+              }
+            ]
+          },
+          "released": "2015-05-15"
+        }
+        "#);
+
+        Ok(())
+    }
+
+    fn language_node(id: &str, name: &str) -> Node {
+        Node {
+            id: id.to_string(),
+            labels: vec!["Language".to_string()],
+            properties: Properties::from([
+                ("name".to_string(), GraphValue::String(name.to_string())),
+                ("year".to_string(), GraphValue::Number(2010.into())),
+                (
+                    "tags".to_string(),
+                    serde_json::json!(["systems", "compiled"]).into(),
+                ),
+            ]),
+        }
+    }
+
+    fn created_by_relationship(id: &str, start_id: &str, end_id: &str) -> Relationship {
+        Relationship {
+            id: id.to_string(),
+            rel_type: "CREATED_BY".to_string(),
+            start_id: start_id.to_string(),
+            end_id: end_id.to_string(),
+            properties: Properties::from([(
+                "first release".to_string(),
+                GraphValue::Number(2015.into()),
+            )]),
+        }
+    }
+}
@@ -8,6 +8,8 @@ pub enum AppError {
     XdgError(#[from] HomeDirError),
     #[error("{0}")]
     InvalidCLIUsage(&'static str),
+    #[error("couldn't load profile")]
+    CouldntLoadProfile(#[source] DbClientError),
     #[error(transparent)]
     ConsoleCmdError(#[from] ConsoleCmdError),
     #[error(transparent)]
@@ -21,12 +23,14 @@ impl AppError {
         match self {
             AppError::XdgError(_) => None,
             AppError::InvalidCLIUsage(_) => None,
+            AppError::CouldntLoadProfile(e) => follow_up_db_client_error(e),
             AppError::ConsoleCmdError(e) => match e {
                 ConsoleCmdError::CouldntBuildDbClient(e) => follow_up_db_client_error(e),
                 ConsoleCmdError::Uncategorised(_) => None,
             },
             AppError::QueryCmdError(e) => match e {
                 QueryCmdError::CouldntBuildDbClient(e) => follow_up_db_client_error(e),
+                QueryCmdError::BenchmarkRegressed { .. } => None,
                 QueryCmdError::Uncategorised(_) => None,
             },
             AppError::Uncategorised(_) => None,
@@ -37,6 +41,7 @@ impl AppError {
         match self {
             AppError::XdgError(_) => true,
             AppError::InvalidCLIUsage(_) => false,
+            AppError::CouldntLoadProfile(_) => false,
             AppError::ConsoleCmdError(_) => false,
             AppError::QueryCmdError(_) => false,
             AppError::Uncategorised(_) => false,
@@ -44,12 +49,13 @@ impl AppError {
     }
 }
 
-fn follow_up_db_client_error(err: &DbClientError) -> Option<String> {
+pub fn follow_up_db_client_error(err: &DbClientError) -> Option<String> {
     match err {
         DbClientError::CouldntReadEnvVar(_) => None,
         DbClientError::DBUriNotSet => Some(
             r#"
-grafq requires the environment variable DB_URI to be set.
+grafq requires the environment variable DB_URI to be set (or a profile with "uri"
+to be selected via --profile).
 
 - For an AWS Neptune database, use the https scheme. Neptune uses IAM
     authentication, so ensure your AWS credentials are configured correctly (via
@@ -88,10 +94,42 @@ The URI needs to be in the form <scheme>://<host>:<port>. For example:
             "
 The environment variables NEO4J_USER, NEO4J_PASSWORD, and NEO4J_DB need to be set when connecting
 to a neo4j database (which was determined by the bolt scheme in DB_URI).
+
+These can also be provided via the neo4j_user, neo4j_password, and neo4j_db keys of a profile.
             "
             .trim()
             .into(),
         ),
+        DbClientError::ProfileNotFound(_, config_file_path) => Some(
+            format!(
+                r#"
+grafq reads profiles from {}. A profile looks like this:
+
+[profiles.dev]
+uri = "bolt://127.0.0.1:7687"
+neo4j_user = "neo4j"
+neo4j_password = "your-password"
+neo4j_db = "neo4j"
+
+[profiles.prod]
+uri = "https://abc.xyz.us-east-1.neptune.amazonaws.com:8182"
+aws_region = "us-east-1"
+aws_profile = "prod"
+"#,
+                config_file_path.display()
+            )
+            .trim()
+            .into(),
+        ),
+        DbClientError::ProfileIsMalformed(_, _) => Some(
+            "
+A profile supports the following keys: uri, neo4j_user, neo4j_password, neo4j_db, aws_region,
+aws_profile, results_dir, results_format.
+"
+            .trim()
+            .into(),
+        ),
+        DbClientError::ConfigFileIsMalformed(_, _) => None,
         DbClientError::Uncategorised(_) => None,
     }
 }
@@ -1,14 +1,65 @@
 use super::NeptuneClient;
 use super::{Neo4jClient, Neo4jConfig};
-use crate::domain::QueryResults;
+use crate::config::{Profile, get_profile};
+use crate::domain::{PlanMode, QueryParams, QueryPlan, QueryResults, Schema};
 use crate::utils::{EnvVarError, get_env_var};
 use anyhow::Context;
-use aws_config::BehaviorVersion;
+use aws_config::{BehaviorVersion, Region};
 use aws_sdk_neptunedata::config::ProvideCredentials;
+use std::path::{Path, PathBuf};
 
 pub trait QueryExecutor {
-    async fn execute_query(&self, query: &str) -> anyhow::Result<QueryResults>;
+    async fn execute_query(
+        &self,
+        query: &str,
+        params: &QueryParams,
+    ) -> anyhow::Result<QueryResults>;
     fn db_uri(&self) -> String;
+    /// Name of the kind of database the client is connected to.
+    fn backend(&self) -> &'static str;
+    /// Fetches the labels, relationship types and property keys present in the database.
+    async fn fetch_schema(&self) -> anyhow::Result<Schema>;
+    /// Fetches the plan the database uses to execute a query; the query is executed as well
+    /// unless `mode` is `PlanMode::Explain`.
+    async fn explain_query(
+        &self,
+        query: &str,
+        params: &QueryParams,
+        mode: PlanMode,
+    ) -> anyhow::Result<QueryPlan>;
+
+    async fn verify_connectivity(&self) -> anyhow::Result<()> {
+        self.execute_query("RETURN 1", &QueryParams::default())
+            .await
+            .with_context(|| format!("couldn't verify connection to {}", self.db_uri()))?;
+
+        Ok(())
+    }
+}
+
+/// Lets a client be (re)built from a target chosen at runtime, eg. via the console's `use`
+/// command.
+pub trait Connect: Sized {
+    async fn connect(
+        target: &ConnectionTarget,
+        config_file_path: &Path,
+    ) -> Result<Self, DbClientError>;
+}
+
+#[derive(Debug, Clone)]
+pub enum ConnectionTarget {
+    Profile(String),
+    Uri(String),
+}
+
+impl From<&str> for ConnectionTarget {
+    fn from(value: &str) -> Self {
+        if value.contains("://") {
+            Self::Uri(value.to_string())
+        } else {
+            Self::Profile(value.to_string())
+        }
+    }
 }
 
 pub enum DbClient {
@@ -17,10 +68,14 @@ pub enum DbClient {
 }
 
 impl QueryExecutor for DbClient {
-    async fn execute_query(&self, query: &str) -> anyhow::Result<QueryResults> {
+    async fn execute_query(
+        &self,
+        query: &str,
+        params: &QueryParams,
+    ) -> anyhow::Result<QueryResults> {
         match self {
-            DbClient::Neptune(c) => c.execute_query(query).await,
-            DbClient::Neo4j(c) => c.execute_query(query).await,
+            DbClient::Neptune(c) => c.execute_query(query, params).await,
+            DbClient::Neo4j(c) => c.execute_query(query, params).await,
         }
     }
 
@@ -30,15 +85,51 @@ impl QueryExecutor for DbClient {
             DbClient::Neo4j(c) => c.db_uri(),
         }
     }
+
+    fn backend(&self) -> &'static str {
+        match self {
+            DbClient::Neptune(_) => "neptune",
+            DbClient::Neo4j(_) => "neo4j",
+        }
+    }
+
+    async fn fetch_schema(&self) -> anyhow::Result<Schema> {
+        match self {
+            DbClient::Neptune(c) => c.fetch_schema().await,
+            DbClient::Neo4j(c) => c.fetch_schema().await,
+        }
+    }
+
+    async fn explain_query(
+        &self,
+        query: &str,
+        params: &QueryParams,
+        mode: PlanMode,
+    ) -> anyhow::Result<QueryPlan> {
+        match self {
+            DbClient::Neptune(c) => c.explain_query(query, params, mode).await,
+            DbClient::Neo4j(c) => c.explain_query(query, params, mode).await,
+        }
+    }
 }
 
-impl DbClient {
-    pub async fn verify_connectivity(&self) -> anyhow::Result<()> {
-        self.execute_query("RETURN 1")
-            .await
-            .with_context(|| format!("couldn't verify connection to {}", self.db_uri()))?;
+impl Connect for DbClient {
+    async fn connect(
+        target: &ConnectionTarget,
+        config_file_path: &Path,
+    ) -> Result<Self, DbClientError> {
+        // the target is what the user explicitly switched to, so its values take precedence over
+        // env vars here (unlike in get_db_client); otherwise, credentials exported for one
+        // database would be sent to the one switched to
+        let profile = match target {
+            ConnectionTarget::Profile(name) => get_profile(config_file_path, name)?,
+            ConnectionTarget::Uri(uri) => Profile {
+                uri: Some(uri.clone()),
+                ..Default::default()
+            },
+        };
 
-        Ok(())
+        build_db_client(with_env_fallbacks(profile, get_env_var)?).await
     }
 }
 
@@ -54,20 +145,82 @@ pub enum DbClientError {
     DBUriIsInvalid(String),
     #[error(r#"environment variable "{0}" is missing"#)]
     Neo4jConnectionInfoMissing(String),
+    #[error(r#"profile "{0}" not found"#)]
+    ProfileNotFound(String, PathBuf),
+    #[error(r#"profile "{0}" is malformed"#)]
+    ProfileIsMalformed(String, #[source] toml::de::Error),
+    #[error("config file is malformed: {}", .0.display())]
+    ConfigFileIsMalformed(PathBuf, #[source] toml::de::Error),
     #[error(transparent)]
     Uncategorised(#[from] anyhow::Error),
 }
 
-pub async fn get_db_client() -> Result<DbClient, DbClientError> {
-    let db_uri = get_env_var("DB_URI")?.ok_or(DbClientError::DBUriNotSet)?;
+/// Builds a client for the database pointed to by `DB_URI`, or by the profile's `uri`.
+///
+/// Environment variables take precedence over the corresponding profile values.
+pub async fn get_db_client(profile: Option<&Profile>) -> Result<DbClient, DbClientError> {
+    let profile = profile.cloned().unwrap_or_default();
+
+    build_db_client(with_env_overrides(profile, get_env_var)?).await
+}
+
+/// Overrides a profile's connection settings with their environment variables, where set.
+fn with_env_overrides<F>(mut profile: Profile, get_env_var: F) -> Result<Profile, DbClientError>
+where
+    F: Fn(&str) -> Result<Option<String>, EnvVarError>,
+{
+    for (env_var, value) in env_settings(&mut profile) {
+        if let Some(env_value) = get_env_var(env_var)? {
+            *value = Some(env_value);
+        }
+    }
+
+    Ok(profile)
+}
+
+/// Fills in the connection settings a profile doesn't set from their environment variables.
+fn with_env_fallbacks<F>(mut profile: Profile, get_env_var: F) -> Result<Profile, DbClientError>
+where
+    F: Fn(&str) -> Result<Option<String>, EnvVarError>,
+{
+    for (env_var, value) in env_settings(&mut profile) {
+        if value.is_none() {
+            *value = get_env_var(env_var)?;
+        }
+    }
+
+    Ok(profile)
+}
+
+/// A profile's connection settings, along with the environment variables they correspond to.
+fn env_settings(profile: &mut Profile) -> [(&'static str, &mut Option<String>); 6] {
+    [
+        ("DB_URI", &mut profile.uri),
+        ("NEO4J_USER", &mut profile.neo4j_user),
+        ("NEO4J_PASSWORD", &mut profile.neo4j_password),
+        ("NEO4J_DB", &mut profile.neo4j_db),
+        ("AWS_REGION", &mut profile.aws_region),
+        ("AWS_PROFILE", &mut profile.aws_profile),
+    ]
+}
 
-    fn get_neo4j_env_var(key: &str) -> Result<String, DbClientError> {
-        get_env_var(key)?.ok_or_else(|| DbClientError::Neo4jConnectionInfoMissing(key.to_string()))
+async fn build_db_client(profile: Profile) -> Result<DbClient, DbClientError> {
+    fn get_neo4j_setting(key: &str, value: Option<String>) -> Result<String, DbClientError> {
+        value.ok_or_else(|| DbClientError::Neo4jConnectionInfoMissing(key.to_string()))
     }
 
+    let db_uri = profile.uri.ok_or(DbClientError::DBUriNotSet)?;
     let db_client = match db_uri.split_once("://") {
         Some(("https", _)) => {
-            let sdk_config = aws_config::load_defaults(BehaviorVersion::latest()).await;
+            let mut loader = aws_config::defaults(BehaviorVersion::latest());
+            if let Some(region) = profile.aws_region {
+                loader = loader.region(Region::new(region));
+            }
+            if let Some(aws_profile) = profile.aws_profile {
+                loader = loader.profile_name(aws_profile);
+            }
+
+            let sdk_config = loader.load().await;
             if let Some(provider) = sdk_config.credentials_provider() {
                 provider
                     .provide_credentials()
@@ -79,9 +232,9 @@ pub async fn get_db_client() -> Result<DbClient, DbClientError> {
             Ok(DbClient::Neptune(neptune_client))
         }
         Some(("bolt", _)) => {
-            let user = get_neo4j_env_var("NEO4J_USER")?;
-            let password = get_neo4j_env_var("NEO4J_PASSWORD")?;
-            let database_name = get_neo4j_env_var("NEO4J_DB")?;
+            let user = get_neo4j_setting("NEO4J_USER", profile.neo4j_user)?;
+            let password = get_neo4j_setting("NEO4J_PASSWORD", profile.neo4j_password)?;
+            let database_name = get_neo4j_setting("NEO4J_DB", profile.neo4j_db)?;
 
             let config = Neo4jConfig {
                 db_uri,
@@ -99,3 +252,133 @@ pub async fn get_db_client() -> Result<DbClient, DbClientError> {
 
     Ok(db_client)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_debug_snapshot;
+    use std::collections::HashMap;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Result<Option<String>, EnvVarError> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |key| Ok(vars.get(key).cloned())
+    }
+
+    #[test]
+    fn switched_to_profile_values_take_precedence_over_env_vars() -> anyhow::Result<()> {
+        // GIVEN
+        let profile = Profile {
+            uri: Some("bolt://prod:7687".to_string()),
+            neo4j_user: Some("prod-user".to_string()),
+            neo4j_password: Some("prod-password".to_string()),
+            ..Default::default()
+        };
+        let env = env(&[
+            ("DB_URI", "bolt://dev:7687"),
+            ("NEO4J_USER", "dev-user"),
+            ("NEO4J_PASSWORD", "dev-password"),
+            ("NEO4J_DB", "neo4j"),
+        ]);
+
+        // WHEN
+        let result = with_env_fallbacks(profile, env)?;
+
+        // THEN
+        assert_debug_snapshot!(
+            (result.uri, result.neo4j_user, result.neo4j_password, result.neo4j_db),
+            @r#"
+        (
+            Some(
+                "bolt://prod:7687",
+            ),
+            Some(
+                "prod-user",
+            ),
+            Some(
+                "prod-password",
+            ),
+            Some(
+                "neo4j",
+            ),
+        )
+        "#
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn env_vars_take_precedence_over_selected_profile_values() -> anyhow::Result<()> {
+        // GIVEN
+        let profile = Profile {
+            uri: Some("bolt://prod:7687".to_string()),
+            neo4j_user: Some("prod-user".to_string()),
+            neo4j_password: Some("prod-password".to_string()),
+            ..Default::default()
+        };
+        let env = env(&[
+            ("DB_URI", "bolt://dev:7687"),
+            ("NEO4J_USER", "dev-user"),
+            ("NEO4J_DB", "neo4j"),
+        ]);
+
+        // WHEN
+        let result = with_env_overrides(profile, env)?;
+
+        // THEN
+        assert_debug_snapshot!(
+            (result.uri, result.neo4j_user, result.neo4j_password, result.neo4j_db),
+            @r#"
+        (
+            Some(
+                "bolt://dev:7687",
+            ),
+            Some(
+                "dev-user",
+            ),
+            Some(
+                "prod-password",
+            ),
+            Some(
+                "neo4j",
+            ),
+        )
+        "#
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn env_vars_are_used_without_a_profile() -> anyhow::Result<()> {
+        // GIVEN
+        let env = env(&[
+            ("DB_URI", "https://neptune:8182"),
+            ("AWS_REGION", "us-east-1"),
+        ]);
+
+        // WHEN
+        let result = with_env_overrides(Profile::default(), env)?;
+
+        // THEN
+        assert_debug_snapshot!(
+            (result.uri, result.aws_region, result.aws_profile),
+            @r#"
+        (
+            Some(
+                "https://neptune:8182",
+            ),
+            Some(
+                "us-east-1",
+            ),
+            None,
+        )
+        "#
+        );
+
+        Ok(())
+    }
+}
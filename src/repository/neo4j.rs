@@ -1,8 +1,17 @@
 use anyhow::Context;
-use neo4rs::{ConfigBuilder, Graph, query as neo4j_query};
-use serde_json::Value;
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
+use neo4rs::{
+    BoltNode, BoltPath, BoltRelation, BoltType, BoltUnboundedRelation, ConfigBuilder, Graph, Row,
+    query as neo4j_query,
+};
+use serde::Deserialize;
+use serde::de::IntoDeserializer;
+use std::collections::HashMap;
 
-use crate::domain::QueryResults;
+use crate::domain::{
+    Duration, GraphValue, Node, Path, PlanMode, Point, Properties, QueryParams, QueryPlan,
+    QueryResults, Relationship, Schema, return_columns,
+};
 
 pub struct Neo4jClient {
     inner: Graph,
@@ -25,7 +34,7 @@ impl Neo4jClient {
             .db(config.database_name.as_str())
             .build()?;
 
-        let graph = Graph::connect(cfg).await?;
+        let graph = Graph::connect(cfg)?;
 
         Ok(Self {
             inner: graph,
@@ -37,10 +46,21 @@ impl Neo4jClient {
         self.db_uri.clone()
     }
 
-    pub(super) async fn execute_query(&self, query: &str) -> anyhow::Result<QueryResults> {
+    pub(super) async fn execute_query(
+        &self,
+        query: &str,
+        params: &QueryParams,
+    ) -> anyhow::Result<QueryResults> {
+        let mut neo4j_query = neo4j_query(query);
+        for (key, value) in params.iter() {
+            let value = BoltType::try_from(value.clone())
+                .with_context(|| format!(r#"couldn't convert parameter "{key}" to a bolt type"#))?;
+            neo4j_query = neo4j_query.param(key, value);
+        }
+
         let mut result = self
             .inner
-            .execute(neo4j_query(query))
+            .execute(neo4j_query)
             .await
             .context("couldn't execute query")?;
 
@@ -51,10 +71,418 @@ impl Neo4jClient {
             .await
             .context("couldn't get row from results")?
         {
-            let row_value = row.to::<Value>().context("couldn't parse row as value")?;
-            results.push(row_value);
+            results.push(row_to_value(&row)?);
+        }
+
+        Ok(QueryResults::new(results, return_columns(query)))
+    }
+
+    pub(super) async fn fetch_schema(&self) -> anyhow::Result<Schema> {
+        let labels = self
+            .fetch_strings("CALL db.labels() YIELD label RETURN label", "label")
+            .await
+            .context("couldn't fetch labels")?;
+        let relationship_types = self
+            .fetch_strings(
+                "CALL db.relationshipTypes() YIELD relationshipType RETURN relationshipType",
+                "relationshipType",
+            )
+            .await
+            .context("couldn't fetch relationship types")?;
+        let property_keys = self
+            .fetch_strings(
+                "CALL db.propertyKeys() YIELD propertyKey RETURN propertyKey",
+                "propertyKey",
+            )
+            .await
+            .context("couldn't fetch property keys")?;
+
+        Ok(Schema::new(labels, relationship_types, property_keys))
+    }
+
+    /// Query plans can't be fetched from Neo4j as of now: the database returns them in the
+    /// summary that follows a query's records, which neo4rs keeps to itself.
+    pub(super) async fn explain_query(
+        &self,
+        _query: &str,
+        _params: &QueryParams,
+        _mode: PlanMode,
+    ) -> anyhow::Result<QueryPlan> {
+        anyhow::bail!(
+            "query plans aren't exposed by neo4rs (the driver grafq uses for Neo4j), so they can't be shown for Neo4j yet"
+        )
+    }
+
+    async fn fetch_strings(&self, query: &str, column: &str) -> anyhow::Result<Vec<String>> {
+        let mut result = self
+            .inner
+            .execute(neo4j_query(query))
+            .await
+            .context("couldn't execute query")?;
+
+        let mut values = Vec::new();
+        while let Some(row) = result
+            .next()
+            .await
+            .context("couldn't get row from results")?
+        {
+            let value = row
+                .get::<String>(column)
+                .with_context(|| format!(r#"couldn't get "{column}" from row"#))?;
+            values.push(value);
+        }
+
+        Ok(values)
+    }
+}
+
+/// Maps a row to a `GraphValue::Map` of its columns.
+fn row_to_value(row: &Row) -> anyhow::Result<GraphValue> {
+    let columns = row
+        .to::<HashMap<String, BoltType>>()
+        .context("couldn't parse row as value")?;
+    let columns = columns
+        .iter()
+        .map(|(key, value)| Ok((key.clone(), bolt_to_value(value)?)))
+        .collect::<anyhow::Result<Properties>>()?;
+
+    Ok(GraphValue::Map(columns))
+}
+
+/// Maps a bolt value to a `GraphValue`.
+fn bolt_to_value(value: &BoltType) -> anyhow::Result<GraphValue> {
+    let graph_value = match value {
+        BoltType::Null(_) => GraphValue::Null,
+        BoltType::String(s) => GraphValue::String(s.value.clone()),
+        BoltType::Boolean(b) => GraphValue::Bool(b.value),
+        BoltType::Integer(i) => GraphValue::Number(i.value.into()),
+        BoltType::Float(f) => serde_json::Number::from_f64(f.value)
+            .map(GraphValue::Number)
+            .unwrap_or(GraphValue::Null),
+        BoltType::Bytes(b) => GraphValue::Bytes(b.value.to_vec()),
+        BoltType::List(l) => GraphValue::List(
+            l.value
+                .iter()
+                .map(bolt_to_value)
+                .collect::<anyhow::Result<_>>()?,
+        ),
+        BoltType::Map(m) => GraphValue::Map(bolt_map_to_properties(&m.value)?),
+        BoltType::Node(n) => GraphValue::Node(bolt_to_node(n)?),
+        BoltType::Relation(r) => GraphValue::Relationship(bolt_to_relationship(r)?),
+        BoltType::UnboundedRelation(r) => {
+            GraphValue::Relationship(unbounded_to_relationship(r, String::new(), String::new())?)
+        }
+        BoltType::Path(p) => GraphValue::Path(bolt_to_path(p)?),
+        BoltType::Point2D(p) => GraphValue::Point(Point {
+            srid: p.sr_id.value,
+            x: p.x.value,
+            y: p.y.value,
+            z: None,
+        }),
+        BoltType::Point3D(p) => GraphValue::Point(Point {
+            srid: p.sr_id.value,
+            x: p.x.value,
+            y: p.y.value,
+            z: Some(p.z.value),
+        }),
+        // neo4rs only converts durations into std's unsigned ones (with months of a fixed length),
+        // but deserializes them into their components as a tuple of 4
+        BoltType::Duration(_) => {
+            let (months, days, seconds, nanos) =
+                <(i64, i64, i64, i64)>::deserialize(value.into_deserializer())
+                    .context("couldn't convert duration")?;
+            GraphValue::Duration(Duration {
+                months,
+                days,
+                seconds,
+                nanos,
+            })
         }
+        BoltType::Date(d) => {
+            GraphValue::Date(NaiveDate::try_from(d).context("couldn't convert date")?)
+        }
+        BoltType::Time(t) => {
+            let (time, offset) = t.into();
+            GraphValue::Time(time, offset)
+        }
+        BoltType::LocalTime(t) => GraphValue::LocalTime(NaiveTime::from(t)),
+        BoltType::DateTime(d) => GraphValue::DateTime(
+            DateTime::<FixedOffset>::try_from(d).context("couldn't convert datetime")?,
+        ),
+        BoltType::LocalDateTime(d) => GraphValue::LocalDateTime(
+            NaiveDateTime::try_from(d).context("couldn't convert local datetime")?,
+        ),
+        BoltType::DateTimeZoneId(d) => GraphValue::ZonedDateTime(
+            DateTime::<FixedOffset>::try_from(d).context("couldn't convert zoned datetime")?,
+            d.tz_id().to_string(),
+        ),
+    };
+
+    Ok(graph_value)
+}
+
+fn bolt_map_to_properties(
+    map: &HashMap<neo4rs::BoltString, BoltType>,
+) -> anyhow::Result<Properties> {
+    map.iter()
+        .map(|(key, value)| Ok((key.value.clone(), bolt_to_value(value)?)))
+        .collect()
+}
+
+fn bolt_to_node(node: &BoltNode) -> anyhow::Result<Node> {
+    Ok(Node {
+        id: node.id.value.to_string(),
+        labels: node
+            .labels
+            .iter()
+            .filter_map(|label| match label {
+                BoltType::String(s) => Some(s.value.clone()),
+                _ => None,
+            })
+            .collect(),
+        properties: bolt_map_to_properties(&node.properties.value)?,
+    })
+}
+
+fn bolt_to_relationship(rel: &BoltRelation) -> anyhow::Result<Relationship> {
+    Ok(Relationship {
+        id: rel.id.value.to_string(),
+        rel_type: rel.typ.value.clone(),
+        start_id: rel.start_node_id.value.to_string(),
+        end_id: rel.end_node_id.value.to_string(),
+        properties: bolt_map_to_properties(&rel.properties.value)?,
+    })
+}
+
+fn unbounded_to_relationship(
+    rel: &BoltUnboundedRelation,
+    start_id: String,
+    end_id: String,
+) -> anyhow::Result<Relationship> {
+    Ok(Relationship {
+        id: rel.id.value.to_string(),
+        rel_type: rel.typ.value.clone(),
+        start_id,
+        end_id,
+        properties: bolt_map_to_properties(&rel.properties.value)?,
+    })
+}
+
+/// Bolt paths hold their unique nodes and relationships separately, along with a list of
+/// `[relationship index, node index]` pairs that describe the path's sequence; relationship
+/// indices are 1-based, and are negative when the relationship is traversed against its
+/// direction.
+fn bolt_to_path(path: &BoltPath) -> anyhow::Result<Path> {
+    let nodes = path
+        .nodes()
+        .iter()
+        .map(bolt_to_node)
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let rels = path.rels();
+    let indices = path.indices();
+
+    let Some(first) = nodes.first() else {
+        anyhow::bail!("path has no nodes");
+    };
+
+    let mut path_nodes = vec![first.clone()];
+    let mut relationships = vec![];
+    for pair in indices.chunks(2) {
+        let [rel_index, node_index] = pair else {
+            anyhow::bail!("path has an odd number of indices");
+        };
+
+        let rel = usize::try_from(rel_index.value.unsigned_abs())
+            .ok()
+            .and_then(|i| i.checked_sub(1))
+            .and_then(|i| rels.get(i))
+            .context("path has an invalid relationship index")?;
+        let node = usize::try_from(node_index.value)
+            .ok()
+            .and_then(|i| nodes.get(i))
+            .context("path has an invalid node index")?;
+        let previous = path_nodes.last().unwrap_or(first);
+
+        let (start_id, end_id) = if rel_index.value > 0 {
+            (previous.id.clone(), node.id.clone())
+        } else {
+            (node.id.clone(), previous.id.clone())
+        };
+
+        relationships.push(unbounded_to_relationship(rel, start_id, end_id)?);
+        path_nodes.push(node.clone());
+    }
+
+    Ok(Path {
+        nodes: path_nodes,
+        relationships,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_snapshot;
+    use neo4rs::{BoltDuration, BoltInteger, BoltList, BoltMap, BoltString};
+
+    //-------------//
+    //  SUCCESSES  //
+    //-------------//
+
+    #[test]
+    fn bolt_to_value_handles_nodes_and_relationships() -> anyhow::Result<()> {
+        // GIVEN
+        let rust = language_node(1, "Rust");
+        let go = language_node(2, "Go");
+        let influenced = BoltRelation {
+            id: BoltInteger::new(3),
+            start_node_id: BoltInteger::new(1),
+            end_node_id: BoltInteger::new(2),
+            typ: BoltString::from("INFLUENCED"),
+            properties: BoltMap::new(),
+        };
+        let value = BoltType::List(BoltList::from(vec![
+            BoltType::Node(rust),
+            BoltType::Relation(influenced),
+            BoltType::Node(go),
+        ]));
+
+        // WHEN
+        let result = bolt_to_value(&value)?;
+
+        // THEN
+        assert_snapshot!(result, @r#"[(:Language {name: "Rust"}), [:INFLUENCED], (:Language {name: "Go"})]"#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn bolt_to_value_handles_paths_in_both_directions() -> anyhow::Result<()> {
+        // GIVEN
+        // (rust)-[:INFLUENCED]->(go)<-[:INFLUENCED]-(python)
+        let path = BoltPath {
+            nodes: BoltList::from(vec![
+                BoltType::Node(language_node(1, "Rust")),
+                BoltType::Node(language_node(2, "Go")),
+                BoltType::Node(language_node(3, "Python")),
+            ]),
+            rels: BoltList::from(vec![
+                BoltType::UnboundedRelation(BoltUnboundedRelation::new(
+                    BoltInteger::new(10),
+                    BoltString::from("INFLUENCED"),
+                    BoltMap::new(),
+                )),
+                BoltType::UnboundedRelation(BoltUnboundedRelation::new(
+                    BoltInteger::new(11),
+                    BoltString::from("INFLUENCED"),
+                    BoltMap::new(),
+                )),
+            ]),
+            indices: BoltList::from(vec![
+                BoltType::Integer(BoltInteger::new(1)),
+                BoltType::Integer(BoltInteger::new(1)),
+                BoltType::Integer(BoltInteger::new(-2)),
+                BoltType::Integer(BoltInteger::new(2)),
+            ]),
+        };
+
+        // WHEN
+        let result = bolt_to_value(&BoltType::Path(path))?;
+
+        // THEN
+        assert_snapshot!(result, @r#"(:Language {name: "Rust"})-[:INFLUENCED]->(:Language {name: "Go"})<-[:INFLUENCED]-(:Language {name: "Python"})"#);
+        let GraphValue::Path(path) = result else {
+            panic!("result should've been a path");
+        };
+        assert_eq!(path.relationships[1].start_id, "3");
+        assert_eq!(path.relationships[1].end_id, "2");
+
+        Ok(())
+    }
+
+    #[test]
+    fn row_to_value_keeps_durations() -> anyhow::Result<()> {
+        // GIVEN
+        let duration = || {
+            BoltType::Duration(BoltDuration::new(
+                BoltInteger::new(0),
+                BoltInteger::new(1),
+                BoltInteger::new(90),
+                BoltInteger::new(0),
+            ))
+        };
+        let row = Row::new(
+            BoltList::from(vec![
+                BoltType::String(BoltString::from("uptime")),
+                BoltType::String(BoltString::from("timeouts")),
+                BoltType::String(BoltString::from("offsets")),
+            ]),
+            BoltList::from(vec![
+                duration(),
+                BoltType::List(BoltList::from(vec![duration()])),
+                BoltType::List(BoltList::from(vec![
+                    BoltType::Integer(BoltInteger::new(86490)),
+                    BoltType::Integer(BoltInteger::new(0)),
+                ])),
+            ]),
+        );
+
+        // WHEN
+        let result = row_to_value(&row)?;
+
+        // THEN
+        let GraphValue::Map(columns) = result else {
+            panic!("result should've been a map");
+        };
+        assert!(matches!(columns["uptime"], GraphValue::Duration(_)));
+        assert_snapshot!(columns["uptime"], @"P1DT1M30S");
+        assert_snapshot!(columns["timeouts"], @"[P1DT1M30S]");
+        assert_snapshot!(columns["offsets"], @"[86490, 0]");
+
+        Ok(())
+    }
+
+    #[test]
+    fn bolt_to_value_keeps_months_days_and_signs_of_durations() -> anyhow::Result<()> {
+        // GIVEN
+        let durations = [(1, 0, 0, 0), (14, 3, 0, 0), (0, -1, 0, 0), (0, 0, -90, 0)];
+
+        // WHEN
+        let result = durations
+            .iter()
+            .map(|&(months, days, seconds, nanos)| {
+                bolt_to_value(&BoltType::Duration(BoltDuration::new(
+                    BoltInteger::new(months),
+                    BoltInteger::new(days),
+                    BoltInteger::new(seconds),
+                    BoltInteger::new(nanos),
+                )))
+                .map(|v| v.to_string())
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        // THEN
+        assert_snapshot!(result.join("\n"), @r"
+        P1M
+        P1Y2M3D
+        P-1D
+        PT-1M-30S
+        ");
+
+        Ok(())
+    }
+
+    fn language_node(id: i64, name: &str) -> BoltNode {
+        let mut properties = BoltMap::new();
+        properties.put(
+            BoltString::from("name"),
+            BoltType::String(BoltString::from(name)),
+        );
 
-        Ok(results.into())
+        BoltNode::new(
+            BoltInteger::new(id),
+            BoltList::from(vec![BoltType::String(BoltString::from("Language"))]),
+            properties,
+        )
     }
 }
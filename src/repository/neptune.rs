@@ -1,10 +1,14 @@
 use anyhow::Context;
 use aws_config::SdkConfig;
 use aws_sdk_neptunedata::Client as NeptuneDataClient;
+use aws_sdk_neptunedata::types::OpenCypherExplainMode;
 use aws_smithy_types::{Document, Number};
-use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
 
-use crate::domain::QueryResults;
+use crate::domain::{
+    GraphValue, Node, PlanMode, PlanOperator, PlanTree, Properties, QueryParams, QueryPlan,
+    QueryResults, Relationship, Schema, return_columns,
+};
 
 pub struct NeptuneClient {
     inner: NeptuneDataClient,
@@ -28,11 +32,18 @@ impl NeptuneClient {
         self.db_uri.clone()
     }
 
-    pub(super) async fn execute_query(&self, query: &str) -> anyhow::Result<QueryResults> {
+    pub(super) async fn execute_query(
+        &self,
+        query: &str,
+        params: &QueryParams,
+    ) -> anyhow::Result<QueryResults> {
+        let parameters = (!params.is_empty()).then(|| params.to_json());
+
         let output = self
             .inner
             .execute_open_cypher_query()
             .open_cypher_query(query)
+            .set_parameters(parameters)
             .send()
             .await
             .context("couldn't execute query")?;
@@ -42,49 +53,306 @@ impl NeptuneClient {
         let result_value = document_to_value(document);
 
         let results = match result_value {
-            Value::Array(arr) => arr,
+            GraphValue::List(arr) => arr,
             _ => anyhow::bail!("unexpected response received, was expecting an array"),
         };
 
-        Ok(results.into())
+        Ok(QueryResults::new(results, return_columns(query)))
+    }
+
+    pub(super) async fn fetch_schema(&self) -> anyhow::Result<Schema> {
+        let output = self
+            .inner
+            .get_propertygraph_summary()
+            .send()
+            .await
+            .context("couldn't get property graph summary")?;
+
+        let summary = output
+            .payload()
+            .and_then(|p| p.graph_summary())
+            .context("property graph summary is missing from the response")?;
+
+        let property_keys = summary
+            .node_properties()
+            .iter()
+            .chain(summary.edge_properties())
+            .flat_map(|properties| properties.keys().cloned());
+
+        Ok(Schema::new(
+            summary.node_labels().to_vec(),
+            summary.edge_labels().to_vec(),
+            property_keys,
+        ))
+    }
+
+    pub(super) async fn explain_query(
+        &self,
+        query: &str,
+        params: &QueryParams,
+        mode: PlanMode,
+    ) -> anyhow::Result<QueryPlan> {
+        let parameters = (!params.is_empty()).then(|| params.to_json());
+        let explain_mode = match mode {
+            PlanMode::Explain => OpenCypherExplainMode::Static,
+            PlanMode::Profile => OpenCypherExplainMode::Dynamic,
+            PlanMode::Details => OpenCypherExplainMode::Details,
+        };
+
+        let output = self
+            .inner
+            .execute_open_cypher_explain_query()
+            .open_cypher_query(query)
+            .set_parameters(parameters)
+            .explain_mode(explain_mode)
+            .send()
+            .await
+            .context("couldn't explain query")?;
+
+        let explain_output = std::str::from_utf8(output.results().as_ref())
+            .context("explain output is not valid UTF-8")?;
+
+        parse_explain_output(explain_output)
     }
 }
 
-fn document_to_value(doc: &Document) -> Value {
-    match doc {
-        Document::Object(map) => {
-            let mut obj = Map::new();
-            for (key, value) in map {
-                obj.insert(key.clone(), document_to_value(value));
+/// Parses the plain text output of Neptune's openCypher explain API, which consists of a table
+/// of operators for the query, followed by a table for each of its subqueries (if any), eg.
+///
+/// ```text
+/// subQuery1
+/// ╔════╤════════╤════════╤═════════════════╤═══════════╗
+/// ║ ID │ Out #1 │ Out #2 │ Name            │ Arguments ║
+/// ╠════╪════════╪════════╪═════════════════╪═══════════╣
+/// ║ 0  │ 1      │ -      │ DFEPipelineScan │ ...       ║
+/// ╟────┼────────┼────────┼─────────────────┼───────────╢
+/// ...
+/// ```
+///
+/// Operators pass their output to the ones in the "Out" columns, which is how the tree is
+/// formed; an operator that's the output of several others is only included once.
+fn parse_explain_output(output: &str) -> anyhow::Result<QueryPlan> {
+    let mut trees = vec![];
+    let mut lines = output.lines();
+    let mut last_text_line = None;
+
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        if !line.starts_with('╔') {
+            if !line.is_empty() {
+                last_text_line = Some(line);
             }
-            Value::Object(obj)
+            continue;
         }
-        Document::Array(items) => {
-            let arr = items.iter().map(document_to_value).collect();
-            Value::Array(arr)
+
+        let mut header: Option<Vec<&str>> = None;
+        let mut rows: Vec<Vec<String>> = vec![];
+        for line in lines.by_ref() {
+            let line = line.trim();
+            if line.starts_with('╚') {
+                break;
+            }
+            let Some(inner) = line.strip_prefix('║').and_then(|l| l.strip_suffix('║')) else {
+                continue;
+            };
+
+            let cells: Vec<&str> = inner.split('│').map(str::trim).collect();
+            if header.is_none() {
+                header = Some(cells);
+                continue;
+            }
+
+            // long cells are wrapped over several lines, with the ID left empty on the
+            // continuation lines
+            match rows.last_mut() {
+                Some(row) if cells.first().is_some_and(|id| id.is_empty()) => {
+                    for (cell, extra) in row.iter_mut().zip(cells) {
+                        if !extra.is_empty() {
+                            if !cell.is_empty() {
+                                cell.push(' ');
+                            }
+                            cell.push_str(extra);
+                        }
+                    }
+                }
+                _ => rows.push(cells.into_iter().map(String::from).collect()),
+            }
         }
-        Document::String(s) => Value::String(s.clone()),
-        Document::Bool(b) => Value::Bool(*b),
-        Document::Null => Value::Null,
+
+        let header = header.context("explain output has a table without a header")?;
+        // the text before the first table is the query itself
+        let name = if trees.is_empty() {
+            None
+        } else {
+            last_text_line.map(String::from)
+        };
+        trees.push(PlanTree {
+            name,
+            root: build_plan_tree(&header, rows)?,
+        });
+        last_text_line = None;
+    }
+
+    if trees.is_empty() {
+        anyhow::bail!("couldn't find a plan in the explain output:\n{output}");
+    }
+
+    Ok(QueryPlan { trees })
+}
+
+fn build_plan_tree(header: &[&str], rows: Vec<Vec<String>>) -> anyhow::Result<PlanOperator> {
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|c| *c == name)
+            .with_context(|| format!(r#"explain output doesn't have an "{name}" column"#))
+    };
+    let id_col = column("ID")?;
+    let name_col = column("Name")?;
+    let arguments_col = column("Arguments")?;
+    let out_cols: Vec<usize> = header
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| c.starts_with("Out #"))
+        .map(|(i, _)| i)
+        .collect();
+
+    let root_id = rows
+        .first()
+        .and_then(|r| r.get(id_col))
+        .cloned()
+        .context("explain output has a table without any operators")?;
+
+    let mut operators = HashMap::new();
+    for row in rows {
+        let cell = |i: usize| row.get(i).cloned().unwrap_or_default();
+        let outputs: Vec<String> = out_cols
+            .iter()
+            .map(|i| cell(*i))
+            .filter(|out| !out.is_empty() && out != "-")
+            .collect();
+        let stats = header
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != id_col && *i != name_col && *i != arguments_col)
+            .filter(|(i, _)| !out_cols.contains(i))
+            .map(|(i, key)| (key.to_string(), cell(i)))
+            .collect();
+
+        let operator = PlanOperator {
+            id: cell(id_col),
+            name: cell(name_col),
+            arguments: cell(arguments_col),
+            stats,
+            children: vec![],
+        };
+        operators.insert(operator.id.clone(), (operator, outputs));
+    }
+
+    fn build(
+        id: &str,
+        operators: &HashMap<String, (PlanOperator, Vec<String>)>,
+        visited: &mut HashSet<String>,
+    ) -> Option<PlanOperator> {
+        if !visited.insert(id.to_string()) {
+            return None;
+        }
+
+        let (operator, outputs) = operators.get(id)?;
+        let mut operator = operator.clone();
+        operator.children = outputs
+            .iter()
+            .filter_map(|out| build(out, operators, visited))
+            .collect();
+
+        Some(operator)
+    }
+
+    build(&root_id, &operators, &mut HashSet::new())
+        .context("couldn't build plan from explain output")
+}
+
+/// Maps a document returned by Neptune to a `GraphValue`.
+///
+/// Neptune returns nodes and relationships as objects with special keys (`~entityType`, `~id`,
+/// `~labels`, `~properties`, etc.); these are mapped to `GraphValue::Node` and
+/// `GraphValue::Relationship`. Paths come back as lists of nodes and relationships, and temporal
+/// values as strings, so those can't be told apart from other lists and strings.
+fn document_to_value(doc: &Document) -> GraphValue {
+    match doc {
+        Document::Object(map) => document_to_entity(map).unwrap_or_else(|| {
+            GraphValue::Map(
+                map.iter()
+                    .map(|(key, value)| (key.clone(), document_to_value(value)))
+                    .collect(),
+            )
+        }),
+        Document::Array(items) => GraphValue::List(items.iter().map(document_to_value).collect()),
+        Document::String(s) => GraphValue::String(s.clone()),
+        Document::Bool(b) => GraphValue::Bool(*b),
+        Document::Null => GraphValue::Null,
         Document::Number(num) => number_to_value(*num),
     }
 }
 
-fn number_to_value(num: Number) -> Value {
+fn document_to_entity(map: &HashMap<String, Document>) -> Option<GraphValue> {
+    let as_str = |key: &str| match map.get(key) {
+        Some(Document::String(s)) => Some(s.clone()),
+        _ => None,
+    };
+
+    let properties: Properties = match map.get("~properties") {
+        Some(Document::Object(props)) => props
+            .iter()
+            .map(|(key, value)| (key.clone(), document_to_value(value)))
+            .collect(),
+        _ => Properties::new(),
+    };
+
+    match as_str("~entityType")?.as_str() {
+        "node" => {
+            let labels = match map.get("~labels") {
+                Some(Document::Array(labels)) => labels
+                    .iter()
+                    .filter_map(|label| match label {
+                        Document::String(s) => Some(s.clone()),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => vec![],
+            };
+
+            Some(GraphValue::Node(Node {
+                id: as_str("~id")?,
+                labels,
+                properties,
+            }))
+        }
+        "relationship" => Some(GraphValue::Relationship(Relationship {
+            id: as_str("~id")?,
+            rel_type: as_str("~type")?,
+            start_id: as_str("~start")?,
+            end_id: as_str("~end")?,
+            properties,
+        })),
+        _ => None,
+    }
+}
+
+fn number_to_value(num: Number) -> GraphValue {
     match num {
-        Number::PosInt(u) => Value::Number(u.into()),
-        Number::NegInt(i) => Value::Number(i.into()),
+        Number::PosInt(u) => GraphValue::Number(u.into()),
+        Number::NegInt(i) => GraphValue::Number(i.into()),
         Number::Float(f) => serde_json::Number::from_f64(f)
-            .map(Value::Number)
-            .unwrap_or(Value::Null),
+            .map(GraphValue::Number)
+            .unwrap_or(GraphValue::Null),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use insta::assert_yaml_snapshot;
-    use std::collections::HashMap;
+    use insta::{assert_snapshot, assert_yaml_snapshot};
 
     //-------------//
     //  SUCCESSES  //
@@ -266,6 +534,143 @@ mod tests {
         ");
     }
 
+    #[test]
+    fn document_to_value_maps_nodes_and_relationships() {
+        // GIVEN
+        let node = |id: &str, name: &str| {
+            Document::Object(HashMap::from([
+                ("~id".to_string(), Document::String(id.to_string())),
+                (
+                    "~entityType".to_string(),
+                    Document::String("node".to_string()),
+                ),
+                (
+                    "~labels".to_string(),
+                    Document::Array(vec![Document::String("Language".to_string())]),
+                ),
+                (
+                    "~properties".to_string(),
+                    Document::Object(HashMap::from([(
+                        "name".to_string(),
+                        Document::String(name.to_string()),
+                    )])),
+                ),
+            ]))
+        };
+        let relationship = Document::Object(HashMap::from([
+            ("~id".to_string(), Document::String("3".to_string())),
+            (
+                "~entityType".to_string(),
+                Document::String("relationship".to_string()),
+            ),
+            ("~start".to_string(), Document::String("1".to_string())),
+            ("~end".to_string(), Document::String("2".to_string())),
+            (
+                "~type".to_string(),
+                Document::String("INFLUENCED".to_string()),
+            ),
+            (
+                "~properties".to_string(),
+                Document::Object(HashMap::from([(
+                    "since".to_string(),
+                    Document::Number(Number::PosInt(2009)),
+                )])),
+            ),
+        ]));
+        let doc = Document::Array(vec![node("1", "Rust"), relationship, node("2", "Go")]);
+
+        // WHEN
+        let result = document_to_value(&doc);
+
+        // THEN
+        assert_snapshot!(result, @r#"[(:Language {name: "Rust"}), [:INFLUENCED {since: 2009}], (:Language {name: "Go"})]"#);
+    }
+
+    #[test]
+    fn document_to_value_keeps_objects_with_unknown_entity_types_as_maps() {
+        // GIVEN
+        let doc = Document::Object(HashMap::from([
+            (
+                "~entityType".to_string(),
+                Document::String("vertex".to_string()),
+            ),
+            ("~id".to_string(), Document::String("1".to_string())),
+        ]));
+
+        // WHEN
+        let result = document_to_value(&doc);
+
+        // THEN
+        assert_snapshot!(result, @r#"{`~entityType`: "vertex", `~id`: "1"}"#);
+    }
+
+    #[test]
+    fn parse_explain_output_builds_trees_for_query_and_subqueries() -> anyhow::Result<()> {
+        // GIVEN
+        let output = include_str!("testdata/neptune-explain-dynamic.txt");
+
+        // WHEN
+        let result = parse_explain_output(output)?;
+
+        // THEN
+        fn describe(operator: &PlanOperator, depth: usize, lines: &mut Vec<String>) {
+            let stats: Vec<String> = operator
+                .stats
+                .iter()
+                .map(|(key, value)| format!("{key}={value}"))
+                .collect();
+            lines.push(format!(
+                "{}#{} {} ({}) {}",
+                "  ".repeat(depth),
+                operator.id,
+                operator.name,
+                operator.arguments,
+                stats.join(", ")
+            ));
+            for child in &operator.children {
+                describe(child, depth + 1, lines);
+            }
+        }
+        let mut lines = vec![];
+        for tree in &result.trees {
+            lines.push(format!("[{}]", tree.name.as_deref().unwrap_or("-")));
+            describe(&tree.root, 0, &mut lines);
+        }
+        assert_snapshot!(lines.join("\n"), @r"
+        [-]
+        #0 SolutionInjection (solutions=[{}]) Mode=-, Units In=0, Units Out=1, Ratio=0.00, Time (ms)=0
+          #1 DFESubquery (subQuery=subQuery1) Mode=-, Units In=0, Units Out=2, Ratio=0.00, Time (ms)=3.00
+            #2 TermResolution (vars=[?l.name]) Mode=id2value_opencypher, Units In=2, Units Out=2, Ratio=1.00, Time (ms)=1.00
+        [subQuery1]
+        #0 DFEPipelineScan (pattern=Node(?l) with property 'ALL' and label 'Language') Mode=-, Units In=0, Units Out=5, Ratio=0.00, Time (ms)=0.31
+          #1 DFEFilter (?l.year > 2000) Mode=-, Units In=5, Units Out=3, Ratio=0.60, Time (ms)=0.12
+            #3 DFESlice (limit=2) Mode=-, Units In=3, Units Out=2, Ratio=0.67, Time (ms)=0.01
+          #2 DFEProject (columns=[?l]) Mode=-, Units In=5, Units Out=5, Ratio=1.00, Time (ms)=0.02
+        ");
+
+        Ok(())
+    }
+
+    //------------//
+    //  FAILURES  //
+    //------------//
+
+    #[test]
+    fn parse_explain_output_fails_if_there_is_no_table() {
+        // GIVEN
+        let output = "Query:\nMATCH (n) RETURN n";
+
+        // WHEN
+        let result = parse_explain_output(output).expect_err("result should've been an error");
+
+        // THEN
+        assert_snapshot!(result, @r"
+        couldn't find a plan in the explain output:
+        Query:
+        MATCH (n) RETURN n
+        ");
+    }
+
     fn get_document_with_numbers(values: Vec<(&str, Number)>) -> Document {
         let mut map = HashMap::new();
         for (key, value) in values {
@@ -0,0 +1,251 @@
+use crate::domain::{
+    BenchmarkNumRuns, BenchmarkReport, BenchmarkStats, BenchmarkSummary, ParamSetReport, ParamSets,
+    QueryParams, format_duration, param_set_breakdown,
+};
+use crate::repository::QueryExecutor;
+use anyhow::Context;
+use chrono::Utc;
+use colored::Colorize;
+use std::borrow::Cow;
+use std::time::{Duration, Instant};
+
+const HISTOGRAM_BAR_WIDTH: usize = 30;
+const MAX_RUNS_TO_LIST: usize = 100;
+const MAX_OUTLIERS_TO_LIST: usize = 10;
+const MAX_PARAM_SETS_TO_LIST: usize = 10;
+
+/// Runs a query `num_warmup_runs` times, and then `num_runs` times, printing the timing of each
+/// run as it goes, followed by the benchmark's statistics.
+///
+/// If `param_sets` are provided, every run (warmups included) binds the next one of them, on top
+/// of `params`, and the latencies are broken down per parameter set as well.
+///
+/// The first failed run fails the benchmark. Returns `None` if there weren't any runs to
+/// compute statistics for.
+pub async fn benchmark_query<D: QueryExecutor>(
+    db_client: &D,
+    query: &str,
+    params: &QueryParams,
+    mut param_sets: Option<&mut ParamSets>,
+    num_runs: BenchmarkNumRuns,
+    num_warmup_runs: u16,
+) -> anyhow::Result<Option<BenchmarkSummary>> {
+    let warmup_times = run_warmups(
+        db_client,
+        query,
+        params,
+        param_sets.as_deref_mut(),
+        num_warmup_runs,
+    )
+    .await?;
+
+    let param_sets_info = param_sets
+        .as_deref()
+        .map(|p| format!(", {} parameter sets, {}", p.len(), p.order()))
+        .unwrap_or_default();
+    println!(
+        "{}",
+        format!(
+            "Benchmarking ({} runs{param_sets_info}) ...",
+            num_runs.value()
+        )
+        .yellow()
+        .bold()
+    );
+
+    let mut times = vec![];
+    let mut run_param_sets = vec![];
+    for i in 0..num_runs.value() {
+        let (param_set, run_params) = params_for_run(params, param_sets.as_deref_mut());
+        let start = Instant::now();
+        db_client
+            .execute_query(query, &run_params)
+            .await
+            .with_context(|| format!("couldn't execute query for benchmark run #{}", i + 1))?;
+        let elapsed = start.elapsed();
+        print_run(i, elapsed, param_set);
+        times.push(elapsed);
+        run_param_sets.extend(param_set);
+    }
+
+    let Some(stats) = BenchmarkStats::new(times) else {
+        return Ok(None);
+    };
+    print_benchmark_stats(&stats);
+
+    let mut report = BenchmarkReport::new(
+        query,
+        &db_client.db_uri(),
+        db_client.backend(),
+        &warmup_times,
+        &stats,
+        Utc::now(),
+    );
+    if let Some(param_sets) = param_sets {
+        report.param_sets = param_set_breakdown(param_sets, &run_param_sets, stats.timings());
+        print_param_set_breakdown(&report.param_sets);
+    }
+
+    Ok(Some(BenchmarkSummary { stats, report }))
+}
+
+pub async fn run_warmups<D: QueryExecutor>(
+    db_client: &D,
+    query: &str,
+    params: &QueryParams,
+    mut param_sets: Option<&mut ParamSets>,
+    num_warmup_runs: u16,
+) -> anyhow::Result<Vec<Duration>> {
+    if num_warmup_runs > 0 {
+        println!(
+            "{}",
+            format!("Warming up ({num_warmup_runs} runs) ...")
+                .yellow()
+                .bold()
+        );
+    }
+
+    let mut warmup_times = vec![];
+    for i in 0..num_warmup_runs {
+        let (param_set, run_params) = params_for_run(params, param_sets.as_deref_mut());
+        let start = Instant::now();
+        db_client
+            .execute_query(query, &run_params)
+            .await
+            .with_context(|| format!("couldn't get results for warmup run #{}", i + 1))?;
+        let elapsed = start.elapsed();
+        print_run(i, elapsed, param_set);
+        warmup_times.push(elapsed);
+    }
+
+    if num_warmup_runs > 0 {
+        println!();
+    }
+
+    Ok(warmup_times)
+}
+
+/// Returns the params for a run to bind, along with the index of the parameter set they come from
+/// (if any).
+fn params_for_run<'a>(
+    params: &'a QueryParams,
+    param_sets: Option<&mut ParamSets>,
+) -> (Option<usize>, Cow<'a, QueryParams>) {
+    let Some(param_sets) = param_sets else {
+        return (None, Cow::Borrowed(params));
+    };
+
+    let index = param_sets.next_index();
+    match param_sets.get(index) {
+        Some(set) => (Some(index), Cow::Owned(params.merged_with(set))),
+        None => (None, Cow::Borrowed(params)),
+    }
+}
+
+fn print_run(index: u16, elapsed: Duration, param_set: Option<usize>) {
+    let param_set_info = param_set
+        .map(|i| format!("  (param set {})", i + 1).dimmed().to_string())
+        .unwrap_or_default();
+    println!(
+        "run {:03}:      {}{}",
+        index + 1,
+        format_duration(elapsed).cyan(),
+        param_set_info
+    );
+}
+
+fn print_param_set_breakdown(breakdown: &[ParamSetReport]) {
+    println!("\n{}", "Parameter sets (slowest first):".yellow().bold());
+    for param_set in breakdown.iter().take(MAX_PARAM_SETS_TO_LIST) {
+        println!(
+            "{:>5}  median {}  mean {}  max {}  runs {:<4} {}",
+            format!("#{}", param_set.param_set),
+            format_duration(Duration::from_micros(param_set.median_us)).cyan(),
+            format_duration(Duration::from_micros(param_set.mean_us)).cyan(),
+            format_duration(Duration::from_micros(param_set.max_us)).cyan(),
+            param_set.runs,
+            param_set.params,
+        );
+    }
+    if breakdown.len() > MAX_PARAM_SETS_TO_LIST {
+        println!(
+            "... and {} more (see the benchmark report for all of them)",
+            breakdown.len() - MAX_PARAM_SETS_TO_LIST
+        );
+    }
+}
+
+pub fn print_benchmark_stats(stats: &BenchmarkStats) {
+    let mut outliers = if stats.outliers.is_empty() {
+        "none".to_string()
+    } else {
+        stats
+            .outliers
+            .iter()
+            .take(MAX_OUTLIERS_TO_LIST)
+            .map(|i| {
+                format!(
+                    "run {:03} ({})",
+                    i + 1,
+                    format_duration(stats.timings()[*i])
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    if stats.outliers.len() > MAX_OUTLIERS_TO_LIST {
+        outliers.push_str(&format!(
+            ", and {} more",
+            stats.outliers.len() - MAX_OUTLIERS_TO_LIST
+        ));
+    }
+    // a sparkline stops being readable once it spans more than a line
+    let sparkline = if stats.timings().len() <= MAX_RUNS_TO_LIST {
+        stats.sparkline()
+    } else {
+        format!("{} runs", stats.timings().len())
+    };
+
+    print!(
+        "
+{}
+min:          {}
+max:          {}
+mean:         {}
+median:       {}
+p90:          {}
+p95:          {}
+p99:          {}
+std dev:      {}
+cv:           {}
+outliers:     {}
+runs:         {}
+",
+        "Statistics:".yellow().bold(),
+        format_duration(stats.min).cyan(),
+        format_duration(stats.max).cyan(),
+        format_duration(stats.mean).cyan(),
+        format_duration(stats.median).cyan(),
+        format_duration(stats.p90).cyan(),
+        format_duration(stats.p95).cyan(),
+        format_duration(stats.p99).cyan(),
+        format_duration(stats.std_dev).cyan(),
+        format!("{:.2}%", stats.coefficient_of_variation * 100.0).cyan(),
+        outliers.cyan(),
+        sparkline.cyan(),
+    );
+
+    let histogram = stats.histogram();
+    let max_count = histogram.iter().map(|b| b.count).max().unwrap_or_default();
+    println!("\n{}", "Histogram:".yellow().bold());
+    for bucket in histogram {
+        let bar_width = (bucket.count * HISTOGRAM_BAR_WIDTH).div_ceil(max_count.max(1));
+        println!(
+            "{:>12} - {:<12} {} {}",
+            format_duration(bucket.start),
+            format_duration(bucket.end),
+            "█".repeat(bar_width).cyan(),
+            bucket.count
+        );
+    }
+}
@@ -0,0 +1,459 @@
+use crate::domain::{BenchmarkReport, BenchmarkReportStats, ParamSetReport};
+use anyhow::Context;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+enum ReportFormat {
+    Json,
+    Csv,
+}
+
+impl ReportFormat {
+    fn from_path(path: &Path) -> anyhow::Result<Self> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Ok(Self::Json),
+            Some("csv") => Ok(Self::Csv),
+            _ => anyhow::bail!(
+                "benchmark report file needs to have a .json or .csv extension: {}",
+                path.to_string_lossy()
+            ),
+        }
+    }
+}
+
+/// Writes a benchmark report to a file, in JSON or CSV as per the file's extension.
+///
+/// The CSV version has a row per value, with the columns `section`, `key` and `value`; sections
+/// are `meta`, `warmup` and `run` (keyed by run number), `stats`, and `param_set` (keyed by
+/// `<param set number>.<field>`).
+pub fn write_benchmark_report<P>(report: &BenchmarkReport, path: P) -> anyhow::Result<()>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let format = ReportFormat::from_path(path)?;
+
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("couldn't create directory: {}", parent.to_string_lossy()))?;
+    }
+
+    let file = File::create(path).with_context(|| {
+        format!(
+            "couldn't create benchmark report file: {}",
+            path.to_string_lossy()
+        )
+    })?;
+
+    match format {
+        ReportFormat::Json => write_json_report(report, file),
+        ReportFormat::Csv => write_csv_report(report, file),
+    }
+}
+
+/// Reads a benchmark report written by `write_benchmark_report`.
+pub fn read_benchmark_report<P>(path: P) -> anyhow::Result<BenchmarkReport>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let format = ReportFormat::from_path(path)?;
+
+    let file = File::open(path).with_context(|| {
+        format!(
+            "couldn't open benchmark report file: {}",
+            path.to_string_lossy()
+        )
+    })?;
+
+    match format {
+        ReportFormat::Json => read_json_report(file),
+        ReportFormat::Csv => read_csv_report(file),
+    }
+    .with_context(|| format!("couldn't read benchmark report: {}", path.to_string_lossy()))
+}
+
+fn write_json_report<W>(report: &BenchmarkReport, mut writer: W) -> anyhow::Result<()>
+where
+    W: Write,
+{
+    let json_string = serde_json::to_string_pretty(report)
+        .context("couldn't serialize benchmark report to JSON")?;
+    writer
+        .write_all(json_string.as_bytes())
+        .context("couldn't write bytes to file")?;
+
+    Ok(())
+}
+
+fn read_json_report<R>(mut reader: R) -> anyhow::Result<BenchmarkReport>
+where
+    R: Read,
+{
+    let mut contents = String::new();
+    reader
+        .read_to_string(&mut contents)
+        .context("couldn't read file")?;
+
+    serde_json::from_str(&contents).context("couldn't parse benchmark report as JSON")
+}
+
+fn write_csv_report<W>(report: &BenchmarkReport, writer: W) -> anyhow::Result<()>
+where
+    W: Write,
+{
+    let mut csv_writer = csv::Writer::from_writer(writer);
+    csv_writer.write_record(["section", "key", "value"])?;
+
+    for (key, value) in [
+        ("query", &report.query),
+        ("db_uri", &report.db_uri),
+        ("backend", &report.backend),
+        ("timestamp", &report.timestamp),
+    ] {
+        csv_writer.write_record(["meta", key, value])?;
+    }
+
+    for (section, timings) in [
+        ("warmup", &report.warmup_timings_us),
+        ("run", &report.timings_us),
+    ] {
+        for (i, timing) in timings.iter().enumerate() {
+            csv_writer.write_record([section, &(i + 1).to_string(), &timing.to_string()])?;
+        }
+    }
+
+    let stats = &report.stats;
+    let outlier_runs = stats
+        .outlier_runs
+        .iter()
+        .map(|r| r.to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+    for (key, value) in [
+        ("min_us", stats.min_us.to_string()),
+        ("max_us", stats.max_us.to_string()),
+        ("mean_us", stats.mean_us.to_string()),
+        ("median_us", stats.median_us.to_string()),
+        ("p90_us", stats.p90_us.to_string()),
+        ("p95_us", stats.p95_us.to_string()),
+        ("p99_us", stats.p99_us.to_string()),
+        ("std_dev_us", stats.std_dev_us.to_string()),
+        (
+            "coefficient_of_variation",
+            stats.coefficient_of_variation.to_string(),
+        ),
+        ("outlier_runs", outlier_runs),
+    ] {
+        csv_writer.write_record(["stats", key, &value])?;
+    }
+
+    for param_set in &report.param_sets {
+        for (field, value) in [
+            ("params", param_set.params.clone()),
+            ("runs", param_set.runs.to_string()),
+            ("median_us", param_set.median_us.to_string()),
+            ("mean_us", param_set.mean_us.to_string()),
+            ("max_us", param_set.max_us.to_string()),
+        ] {
+            let key = format!("{}.{field}", param_set.param_set);
+            csv_writer.write_record(["param_set", &key, &value])?;
+        }
+    }
+
+    csv_writer.flush()?;
+    Ok(())
+}
+
+fn read_csv_report<R>(reader: R) -> anyhow::Result<BenchmarkReport>
+where
+    R: Read,
+{
+    let mut csv_reader = csv::Reader::from_reader(reader);
+
+    let mut meta = HashMap::new();
+    let mut stats = HashMap::new();
+    let mut warmup_timings_us = vec![];
+    let mut timings_us = vec![];
+    let mut param_set_fields: BTreeMap<usize, HashMap<String, String>> = BTreeMap::new();
+    for record in csv_reader.records() {
+        let record = record.context("couldn't read CSV record")?;
+        let (Some(section), Some(key), Some(value)) = (record.get(0), record.get(1), record.get(2))
+        else {
+            anyhow::bail!("expected each record to have a section, key and value");
+        };
+
+        match section {
+            "meta" => {
+                meta.insert(key.to_string(), value.to_string());
+            }
+            "stats" => {
+                stats.insert(key.to_string(), value.to_string());
+            }
+            "warmup" | "run" => {
+                let timing: u64 = value
+                    .parse()
+                    .with_context(|| format!(r#"invalid timing for {section} {key}: "{value}""#))?;
+                if section == "warmup" {
+                    warmup_timings_us.push(timing);
+                } else {
+                    timings_us.push(timing);
+                }
+            }
+            "param_set" => {
+                let (number, field) = key
+                    .split_once('.')
+                    .and_then(|(n, f)| Some((n.parse::<usize>().ok()?, f)))
+                    .with_context(|| format!(r#"invalid param set key: "{key}""#))?;
+                param_set_fields
+                    .entry(number)
+                    .or_default()
+                    .insert(field.to_string(), value.to_string());
+            }
+            _ => anyhow::bail!(r#"unknown section: "{section}""#),
+        }
+    }
+
+    let mut param_sets = param_set_fields
+        .into_iter()
+        .map(|(number, mut fields)| {
+            let mut field = |name: &str| {
+                fields
+                    .remove(name)
+                    .with_context(|| format!(r#"param set {number} is missing "{name}""#))
+            };
+            let mut number_field = |name: &str| -> anyhow::Result<u64> {
+                field(name)?.parse().with_context(|| {
+                    format!(r#"param set {number} has an invalid value for "{name}""#)
+                })
+            };
+
+            Ok(ParamSetReport {
+                param_set: number,
+                runs: number_field("runs")? as usize,
+                median_us: number_field("median_us")?,
+                mean_us: number_field("mean_us")?,
+                max_us: number_field("max_us")?,
+                params: field("params")?,
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    // slowest first, the way they're written
+    param_sets.sort_by(|a, b| {
+        b.median_us
+            .cmp(&a.median_us)
+            .then(a.param_set.cmp(&b.param_set))
+    });
+
+    let mut meta_value = |key: &str| {
+        meta.remove(key)
+            .with_context(|| format!(r#"meta value "{key}" is missing"#))
+    };
+    let stat = |key: &str| -> anyhow::Result<u64> {
+        stats
+            .get(key)
+            .with_context(|| format!(r#"stat "{key}" is missing"#))?
+            .parse()
+            .with_context(|| format!(r#"stat "{key}" is not a valid number"#))
+    };
+
+    Ok(BenchmarkReport {
+        query: meta_value("query")?,
+        db_uri: meta_value("db_uri")?,
+        backend: meta_value("backend")?,
+        timestamp: meta_value("timestamp")?,
+        warmup_timings_us,
+        timings_us,
+        stats: BenchmarkReportStats {
+            min_us: stat("min_us")?,
+            max_us: stat("max_us")?,
+            mean_us: stat("mean_us")?,
+            median_us: stat("median_us")?,
+            p90_us: stat("p90_us")?,
+            p95_us: stat("p95_us")?,
+            p99_us: stat("p99_us")?,
+            std_dev_us: stat("std_dev_us")?,
+            coefficient_of_variation: stats
+                .get("coefficient_of_variation")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or_default(),
+            outlier_runs: stats
+                .get("outlier_runs")
+                .map(|v| {
+                    v.split_whitespace()
+                        .filter_map(|r| r.parse().ok())
+                        .collect()
+                })
+                .unwrap_or_default(),
+        },
+        param_sets,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_snapshot;
+
+    //-------------//
+    //  SUCCESSES  //
+    //-------------//
+
+    #[test]
+    fn write_csv_report_writes_a_row_per_value() -> anyhow::Result<()> {
+        // GIVEN
+        let report = sample_report();
+        let mut buffer = Vec::new();
+
+        // WHEN
+        write_csv_report(&report, &mut buffer)?;
+
+        // THEN
+        let result = String::from_utf8(buffer)?;
+        assert_snapshot!(result, @r#"
+        section,key,value
+        meta,query,"MATCH (l:Language) RETURN l.name, l.year"
+        meta,db_uri,bolt://127.0.0.1:7687
+        meta,backend,neo4j
+        meta,timestamp,2025-01-16T12:00:00+00:00
+        warmup,1,20500
+        run,1,12500
+        run,2,10250
+        run,3,48750
+        stats,min_us,10250
+        stats,max_us,48750
+        stats,mean_us,23833
+        stats,median_us,12500
+        stats,p90_us,41500
+        stats,p95_us,45125
+        stats,p99_us,48025
+        stats,std_dev_us,21612
+        stats,coefficient_of_variation,0.9068
+        stats,outlier_runs,3
+        param_set,2.params,"{""name"":""Python""}"
+        param_set,2.runs,1
+        param_set,2.median_us,48750
+        param_set,2.mean_us,48750
+        param_set,2.max_us,48750
+        param_set,1.params,"{""name"":""Rust""}"
+        param_set,1.runs,2
+        param_set,1.median_us,11375
+        param_set,1.mean_us,11375
+        param_set,1.max_us,12500
+        "#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn csv_report_can_be_read_back() -> anyhow::Result<()> {
+        // GIVEN
+        let report = sample_report();
+        let mut buffer = Vec::new();
+        write_csv_report(&report, &mut buffer)?;
+
+        // WHEN
+        let result = read_csv_report(buffer.as_slice())?;
+
+        // THEN
+        assert_eq!(result, report);
+
+        Ok(())
+    }
+
+    #[test]
+    fn json_report_can_be_read_back() -> anyhow::Result<()> {
+        // GIVEN
+        let report = sample_report();
+        let mut buffer = Vec::new();
+        write_json_report(&report, &mut buffer)?;
+
+        // WHEN
+        let result = read_json_report(buffer.as_slice())?;
+
+        // THEN
+        assert_eq!(result, report);
+
+        Ok(())
+    }
+
+    //------------//
+    //  FAILURES  //
+    //------------//
+
+    #[test]
+    fn reading_a_report_fails_for_unsupported_extension() {
+        // GIVEN
+        let path = Path::new("path/to/report.txt");
+
+        // WHEN
+        let result = read_benchmark_report(path).expect_err("result should've been an error");
+
+        // THEN
+        assert_snapshot!(result, @"benchmark report file needs to have a .json or .csv extension: path/to/report.txt");
+    }
+
+    #[test]
+    fn reading_a_csv_report_fails_if_stats_are_missing() {
+        // GIVEN
+        let contents = r#"section,key,value
+meta,query,RETURN 1
+meta,db_uri,bolt://127.0.0.1:7687
+meta,backend,neo4j
+meta,timestamp,2025-01-16T12:00:00+00:00
+run,1,1000
+"#;
+
+        // WHEN
+        let result =
+            read_csv_report(contents.as_bytes()).expect_err("result should've been an error");
+
+        // THEN
+        assert_snapshot!(result, @r#"stat "min_us" is missing"#);
+    }
+
+    fn sample_report() -> BenchmarkReport {
+        BenchmarkReport {
+            query: "MATCH (l:Language) RETURN l.name, l.year".to_string(),
+            db_uri: "bolt://127.0.0.1:7687".to_string(),
+            backend: "neo4j".to_string(),
+            timestamp: "2025-01-16T12:00:00+00:00".to_string(),
+            warmup_timings_us: vec![20_500],
+            timings_us: vec![12_500, 10_250, 48_750],
+            stats: BenchmarkReportStats {
+                min_us: 10_250,
+                max_us: 48_750,
+                mean_us: 23_833,
+                median_us: 12_500,
+                p90_us: 41_500,
+                p95_us: 45_125,
+                p99_us: 48_025,
+                std_dev_us: 21_612,
+                coefficient_of_variation: 0.9068,
+                outlier_runs: vec![3],
+            },
+            param_sets: vec![
+                ParamSetReport {
+                    param_set: 2,
+                    params: r#"{"name":"Python"}"#.to_string(),
+                    runs: 1,
+                    median_us: 48_750,
+                    mean_us: 48_750,
+                    max_us: 48_750,
+                },
+                ParamSetReport {
+                    param_set: 1,
+                    params: r#"{"name":"Rust"}"#.to_string(),
+                    runs: 2,
+                    median_us: 11_375,
+                    mean_us: 11_375,
+                    max_us: 12_500,
+                },
+            ],
+        }
+    }
+}
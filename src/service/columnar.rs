@@ -0,0 +1,389 @@
+use super::write::value_to_csv_field;
+use crate::domain::{GraphValue, NonEmptyResults, ResultsCompression};
+use anyhow::Context;
+use arrow::array::{
+    ArrayBuilder, ArrayRef, BooleanArray, BooleanBuilder, Float64Array, Float64Builder, Int64Array,
+    Int64Builder, ListBuilder, StringArray, StringBuilder,
+};
+use arrow::datatypes::{Field, Schema};
+use arrow::ipc::CompressionType;
+use arrow::ipc::writer::{FileWriter, IpcWriteOptions};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+use std::io::Write;
+use std::sync::Arc;
+
+/// Type of the values in a column of results.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ScalarType {
+    Bool,
+    Int,
+    Float,
+    String,
+}
+
+/// Type of a column of results, as inferred from its values.
+///
+/// Values that don't fit a single scalar type (maps, nodes, lists of mixed values, temporal
+/// values, or columns mixing types) end up in a string column, the same way they would in a CSV
+/// file, ie. with nested values as JSON.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ColumnType {
+    Scalar(ScalarType),
+    // `None` if none of the lists had any non-null items
+    List(Option<ScalarType>),
+    Text,
+}
+
+pub(super) fn write_parquet<W>(
+    results: &NonEmptyResults,
+    compression: Option<ResultsCompression>,
+    mut writer: W,
+) -> anyhow::Result<()>
+where
+    W: Write,
+{
+    let batch = to_record_batch(results)?;
+    let compression = match compression.unwrap_or(ResultsCompression::Zstd) {
+        ResultsCompression::None => Compression::UNCOMPRESSED,
+        ResultsCompression::Lz4 => Compression::LZ4_RAW,
+        ResultsCompression::Zstd => Compression::ZSTD(ZstdLevel::default()),
+    };
+    let properties = WriterProperties::builder()
+        .set_compression(compression)
+        .build();
+
+    // parquet's writer needs to be Send, which stdout's isn't; results are held in memory
+    // anyway, so they're encoded into a buffer first
+    let mut buffer = Vec::new();
+    let mut parquet_writer = ArrowWriter::try_new(&mut buffer, batch.schema(), Some(properties))
+        .context("couldn't create parquet writer")?;
+    parquet_writer
+        .write(&batch)
+        .context("couldn't encode results as parquet")?;
+    parquet_writer
+        .close()
+        .context("couldn't encode results as parquet")?;
+
+    writer
+        .write_all(&buffer)
+        .context("couldn't write bytes to file")?;
+
+    Ok(())
+}
+
+pub(super) fn write_arrow<W>(
+    results: &NonEmptyResults,
+    compression: Option<ResultsCompression>,
+    writer: W,
+) -> anyhow::Result<()>
+where
+    W: Write,
+{
+    let batch = to_record_batch(results)?;
+    let compression = match compression.unwrap_or(ResultsCompression::None) {
+        ResultsCompression::None => None,
+        ResultsCompression::Lz4 => Some(CompressionType::LZ4_FRAME),
+        ResultsCompression::Zstd => Some(CompressionType::ZSTD),
+    };
+    let options = IpcWriteOptions::default()
+        .try_with_compression(compression)
+        .context("couldn't set up compression for arrow file")?;
+
+    let mut arrow_writer = FileWriter::try_new_with_options(writer, &batch.schema(), options)
+        .context("couldn't create arrow writer")?;
+    arrow_writer
+        .write(&batch)
+        .context("couldn't write results as arrow")?;
+    arrow_writer
+        .finish()
+        .context("couldn't write results as arrow")?;
+
+    Ok(())
+}
+
+fn to_record_batch(results: &NonEmptyResults) -> anyhow::Result<RecordBatch> {
+    let headers = results.columns();
+    if headers.is_empty() {
+        anyhow::bail!("results don't have any columns");
+    }
+
+    let rows: Vec<Vec<Option<&GraphValue>>> = results.cells().collect();
+
+    let mut fields = Vec::with_capacity(headers.len());
+    let mut columns = Vec::with_capacity(headers.len());
+    for (i, header) in headers.iter().enumerate() {
+        let values: Vec<Option<&GraphValue>> = rows
+            .iter()
+            .map(|row| row[i].filter(|v| !matches!(v, GraphValue::Null)))
+            .collect();
+
+        let column = build_column(&values, infer_column_type(&values));
+        fields.push(Field::new(header, column.data_type().clone(), true));
+        columns.push(column);
+    }
+
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+        .context("couldn't build a columnar batch of the results")
+}
+
+fn infer_column_type(values: &[Option<&GraphValue>]) -> ColumnType {
+    values
+        .iter()
+        .flatten()
+        .map(|value| value_type(value))
+        .reduce(|a, b| match (a, b) {
+            (ColumnType::Scalar(a), ColumnType::Scalar(b)) => {
+                unify(a, b).map_or(ColumnType::Text, ColumnType::Scalar)
+            }
+            (ColumnType::List(None), ColumnType::List(t))
+            | (ColumnType::List(t), ColumnType::List(None)) => ColumnType::List(t),
+            (ColumnType::List(Some(a)), ColumnType::List(Some(b))) => {
+                unify(a, b).map_or(ColumnType::Text, |t| ColumnType::List(Some(t)))
+            }
+            _ => ColumnType::Text,
+        })
+        // a column with nothing but nulls in it
+        .unwrap_or(ColumnType::Text)
+}
+
+fn value_type(value: &GraphValue) -> ColumnType {
+    if let GraphValue::List(items) = value {
+        let item_types = items
+            .iter()
+            .filter(|i| !matches!(i, GraphValue::Null))
+            .map(scalar_type)
+            .collect::<Option<Vec<_>>>();
+
+        return match item_types {
+            Some(types) => match types.into_iter().map(Some).reduce(|a, b| unify(a?, b?)) {
+                None => ColumnType::List(None),
+                Some(Some(t)) => ColumnType::List(Some(t)),
+                Some(None) => ColumnType::Text,
+            },
+            None => ColumnType::Text,
+        };
+    }
+
+    scalar_type(value).map_or(ColumnType::Text, ColumnType::Scalar)
+}
+
+fn scalar_type(value: &GraphValue) -> Option<ScalarType> {
+    match value {
+        GraphValue::Bool(_) => Some(ScalarType::Bool),
+        GraphValue::Number(n) if n.is_i64() => Some(ScalarType::Int),
+        GraphValue::Number(_) => Some(ScalarType::Float),
+        GraphValue::String(_) => Some(ScalarType::String),
+        _ => None,
+    }
+}
+
+fn unify(a: ScalarType, b: ScalarType) -> Option<ScalarType> {
+    match (a, b) {
+        (a, b) if a == b => Some(a),
+        (ScalarType::Int, ScalarType::Float) | (ScalarType::Float, ScalarType::Int) => {
+            Some(ScalarType::Float)
+        }
+        _ => None,
+    }
+}
+
+fn build_column(values: &[Option<&GraphValue>], column_type: ColumnType) -> ArrayRef {
+    match column_type {
+        ColumnType::Scalar(ScalarType::Bool) => Arc::new(
+            values
+                .iter()
+                .map(|v| v.and_then(as_bool))
+                .collect::<BooleanArray>(),
+        ),
+        ColumnType::Scalar(ScalarType::Int) => Arc::new(
+            values
+                .iter()
+                .map(|v| v.and_then(as_i64))
+                .collect::<Int64Array>(),
+        ),
+        ColumnType::Scalar(ScalarType::Float) => Arc::new(
+            values
+                .iter()
+                .map(|v| v.and_then(as_f64))
+                .collect::<Float64Array>(),
+        ),
+        ColumnType::Scalar(ScalarType::String) | ColumnType::Text => Arc::new(
+            values
+                .iter()
+                .map(|v| v.map(value_to_csv_field))
+                .collect::<StringArray>(),
+        ),
+        ColumnType::List(Some(ScalarType::Bool)) => {
+            build_list_column(values, BooleanBuilder::new(), |b, v| {
+                b.append_option(as_bool(v))
+            })
+        }
+        ColumnType::List(Some(ScalarType::Int)) => {
+            build_list_column(values, Int64Builder::new(), |b, v| {
+                b.append_option(as_i64(v))
+            })
+        }
+        ColumnType::List(Some(ScalarType::Float)) => {
+            build_list_column(values, Float64Builder::new(), |b, v| {
+                b.append_option(as_f64(v))
+            })
+        }
+        ColumnType::List(Some(ScalarType::String) | None) => {
+            build_list_column(values, StringBuilder::new(), |b, v| match v {
+                GraphValue::String(s) => b.append_value(s),
+                _ => b.append_null(),
+            })
+        }
+    }
+}
+
+fn build_list_column<B, F>(values: &[Option<&GraphValue>], item_builder: B, append: F) -> ArrayRef
+where
+    B: ArrayBuilder,
+    F: Fn(&mut B, &GraphValue),
+{
+    let mut builder = ListBuilder::new(item_builder);
+    for value in values {
+        match value {
+            Some(GraphValue::List(items)) => {
+                for item in items {
+                    append(builder.values(), item);
+                }
+                builder.append(true);
+            }
+            _ => builder.append(false),
+        }
+    }
+
+    Arc::new(builder.finish())
+}
+
+fn as_bool(value: &GraphValue) -> Option<bool> {
+    match value {
+        GraphValue::Bool(b) => Some(*b),
+        _ => None,
+    }
+}
+
+fn as_i64(value: &GraphValue) -> Option<i64> {
+    match value {
+        GraphValue::Number(n) => n.as_i64(),
+        _ => None,
+    }
+}
+
+fn as_f64(value: &GraphValue) -> Option<f64> {
+    match value {
+        GraphValue::Number(n) => n.as_f64(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_snapshot;
+
+    //-------------//
+    //  SUCCESSES  //
+    //-------------//
+
+    #[test]
+    fn to_record_batch_infers_column_types() -> anyhow::Result<()> {
+        // GIVEN
+        let results = NonEmptyResults::with_columns(
+            &[
+                "name", "year", "score", "typed", "tags", "ratings", "meta", "mixed", "nothing",
+            ],
+            vec![
+                serde_json::json!({
+                    "name": "Rust",
+                    "year": 2010,
+                    "score": 9,
+                    "typed": true,
+                    "tags": ["systems", "safe"],
+                    "ratings": [5, 4.5],
+                    "meta": {"creator": "Graydon Hoare"},
+                    "mixed": 1,
+                    "nothing": null,
+                }),
+                serde_json::json!({
+                    "name": "Python",
+                    "year": 1991,
+                    "score": 8.5,
+                    "typed": null,
+                    "tags": [],
+                    "ratings": null,
+                    "meta": {"creator": "Guido van Rossum"},
+                    "mixed": "one",
+                    "nothing": null,
+                }),
+            ],
+        );
+
+        // WHEN
+        let result = to_record_batch(&results)?;
+
+        // THEN
+        let schema: Vec<String> = result
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| format!("{}: {}", f.name(), f.data_type()))
+            .collect();
+        assert_snapshot!(schema.join("\n"), @r#"
+        name: Utf8
+        year: Int64
+        score: Float64
+        typed: Boolean
+        tags: List(Utf8)
+        ratings: List(Float64)
+        meta: Utf8
+        mixed: Utf8
+        nothing: Utf8
+        "#);
+        assert_eq!(result.num_rows(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn write_parquet_and_write_arrow_produce_readable_files() -> anyhow::Result<()> {
+        // GIVEN
+        let results = NonEmptyResults::with_columns(
+            &["language", "year"],
+            vec![
+                serde_json::json!({"language": "Rust", "year": 2010}),
+                serde_json::json!({"language": "Python", "year": 1991}),
+            ],
+        );
+        let mut parquet_buffer = Vec::new();
+        let mut arrow_buffer = Vec::new();
+
+        // WHEN
+        write_parquet(&results, None, &mut parquet_buffer)?;
+        write_arrow(&results, Some(ResultsCompression::Lz4), &mut arrow_buffer)?;
+
+        // THEN
+        let mut parquet_file = tempfile::tempfile()?;
+        parquet_file.write_all(&parquet_buffer)?;
+        let parquet_reader =
+            parquet::arrow::arrow_reader::ParquetRecordBatchReader::try_new(parquet_file, 1024)?;
+        let parquet_rows: usize = parquet_reader
+            .map(|batch| batch.map(|b| b.num_rows()))
+            .sum::<Result<_, _>>()?;
+        assert_eq!(parquet_rows, 2);
+
+        let arrow_reader =
+            arrow::ipc::reader::FileReader::try_new(std::io::Cursor::new(arrow_buffer), None)?;
+        let arrow_rows: usize = arrow_reader
+            .map(|batch| batch.map(|b| b.num_rows()))
+            .sum::<Result<_, _>>()?;
+        assert_eq!(arrow_rows, 2);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,185 @@
+use crate::domain::NonEmptyResults;
+use tabled::builder::Builder;
+use tabled::settings::style::Style;
+
+const HTML_STYLE: &str = include_str!("assets/results.css");
+
+/// Renders results as a GitHub flavoured markdown table; pipes are escaped, and newlines are
+/// replaced with `<br>`, so that every row stays on a single line.
+pub(super) fn get_results_markdown(results: &NonEmptyResults) -> String {
+    let mut table = Builder::from(results_rows(results, escape_markdown)).build();
+
+    table.with(Style::markdown());
+
+    table.to_string()
+}
+
+/// Renders results as a standalone, styled HTML page.
+pub(super) fn get_results_html(results: &NonEmptyResults) -> String {
+    let mut rows = results_rows(results, escape_html).into_iter();
+
+    let header = rows
+        .next()
+        .map(|cells| {
+            cells
+                .iter()
+                .map(|c| format!("<th>{c}</th>"))
+                .collect::<String>()
+        })
+        .unwrap_or_default();
+    let body = rows
+        .map(|cells| {
+            let cells = cells
+                .iter()
+                .map(|c| format!("<td>{c}</td>"))
+                .collect::<String>();
+            format!("      <tr>{cells}</tr>\n")
+        })
+        .collect::<String>();
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <title>grafq results</title>
+  <style>
+{HTML_STYLE}  </style>
+</head>
+<body>
+  <table>
+    <thead>
+      <tr>{header}</tr>
+    </thead>
+    <tbody>
+{body}    </tbody>
+  </table>
+</body>
+</html>"#
+    )
+}
+
+/// Returns the header and rows of results (missing values being empty), with every value passed
+/// through `cell`.
+fn results_rows<F>(results: &NonEmptyResults, cell: F) -> Vec<Vec<String>>
+where
+    F: Fn(&str) -> String,
+{
+    let headers = results.columns();
+    if headers.is_empty() {
+        return vec![];
+    }
+
+    let header = headers.iter().map(|h| cell(h)).collect();
+    let rows = results.cells().map(|cells| {
+        cells
+            .into_iter()
+            .map(|c| c.map(|v| cell(&v.to_string())).unwrap_or_default())
+            .collect()
+    });
+
+    std::iter::once(header).chain(rows).collect()
+}
+
+fn escape_markdown(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace(['\n', '\r'], "<br>")
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+        .replace("\r\n", "<br>")
+        .replace(['\n', '\r'], "<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_snapshot;
+
+    #[test]
+    fn get_results_markdown_escapes_pipes_and_newlines() {
+        // GIVEN
+        let results = vec![
+            serde_json::json!({"language": "Rust", "tagline": "fast | reliable\nproductive"}),
+            serde_json::json!({"language": "Python", "tagline": null}),
+        ];
+        let results = NonEmptyResults::with_columns(&["language", "tagline"], results);
+
+        // WHEN
+        let result = get_results_markdown(&results);
+
+        // THEN
+        assert_snapshot!(result, @r"
+        | language | tagline                        |
+        |----------|--------------------------------|
+        | Rust     | fast \| reliable<br>productive |
+        | Python   | null                           |
+        ");
+    }
+
+    #[test]
+    fn get_results_html_escapes_entities() {
+        // GIVEN
+        let results = vec![
+            serde_json::json!({"language": "C++", "tagline": "<templates> & \"more\"\nand more"}),
+        ];
+        let results = NonEmptyResults::with_columns(&["language", "tagline"], results);
+
+        // WHEN
+        let result = get_results_html(&results);
+
+        // THEN
+        assert_snapshot!(result, @r#"
+        <!DOCTYPE html>
+        <html lang="en">
+        <head>
+          <meta charset="utf-8">
+          <title>grafq results</title>
+          <style>
+            body {
+              font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Helvetica, Arial, sans-serif;
+              margin: 2rem;
+              color: #1f2328;
+            }
+            table {
+              border-collapse: collapse;
+              font-size: 14px;
+            }
+            th, td {
+              border: 1px solid #d0d7de;
+              padding: 6px 13px;
+              text-align: left;
+              vertical-align: top;
+            }
+            th {
+              background-color: #f6f8fa;
+              font-weight: 600;
+            }
+            tbody tr:nth-child(even) {
+              background-color: #f6f8fa;
+            }
+          </style>
+        </head>
+        <body>
+          <table>
+            <thead>
+              <tr><th>language</th><th>tagline</th></tr>
+            </thead>
+            <tbody>
+              <tr><td>C++</td><td>&lt;templates&gt; &amp; &quot;more&quot;<br>and more</td></tr>
+            </tbody>
+          </table>
+        </body>
+        </html>
+        "#);
+    }
+}
@@ -1,5 +1,11 @@
+mod benchmark;
+mod benchmark_report;
+mod columnar;
+mod markup;
 mod page;
 mod write;
 
+pub use benchmark::*;
+pub use benchmark_report::*;
 pub use page::*;
 pub use write::*;
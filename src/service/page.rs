@@ -1,14 +1,116 @@
-use crate::domain::Pager;
+use super::{write_results, write_results_to};
+use crate::domain::{
+    DisplayOptions, NonEmptyResults, OutputFormat, Pager, ResultsFormat, WriteOptions,
+};
 use anyhow::Context;
+use chrono::Utc;
+use std::io::{ErrorKind, Write};
 use std::path::Path;
+use std::process::Stdio;
 
-pub fn page_results<P>(results_file: P, pager: &Pager) -> anyhow::Result<()>
+/// Displays results via a pager.
+///
+/// Pagers that read from stdin (like `less`) are fed results rendered in `format` (tables are
+/// rendered in full, with every row, as the pager takes care of scrolling). Pagers that need a
+/// file (like `nvim`) get `results_file` if results have been written to one, or else a temporary
+/// file with results in `format` (or in `results_format`, if `format` is a table). The built-in
+/// pager doesn't need either, and browses results directly.
+pub fn page_results(
+    results: &NonEmptyResults,
+    results_file: Option<&Path>,
+    pager: &Pager,
+    format: OutputFormat,
+    results_format: &ResultsFormat,
+    write_options: WriteOptions,
+    display: &DisplayOptions,
+) -> anyhow::Result<()> {
+    if pager.is_builtin() {
+        return crate::view::browse_results(results);
+    }
+
+    if pager.reads_stdin() {
+        return page_via_stdin(results, pager, format, write_options, display);
+    }
+
+    if let Some(results_file) = results_file {
+        return page_file(results_file, pager);
+    }
+
+    let file_format = format
+        .results_format()
+        .unwrap_or_else(|| results_format.clone());
+    if file_format.is_binary() {
+        anyhow::bail!(
+            "results in a binary format (parquet, arrow) cannot be displayed via a pager that reads files"
+        );
+    }
+    let temp_results_directory =
+        tempfile::tempdir().context("couldn't create temporary directory for paging results")?;
+    let results_file = write_results(
+        results,
+        &temp_results_directory,
+        &file_format,
+        write_options,
+        Utc::now(),
+    )
+    .context("couldn't write results to temporary location")?;
+
+    page_file(&results_file, pager)
+}
+
+fn page_via_stdin(
+    results: &NonEmptyResults,
+    pager: &Pager,
+    format: OutputFormat,
+    write_options: WriteOptions,
+    display: &DisplayOptions,
+) -> anyhow::Result<()> {
+    let display = DisplayOptions {
+        max_rows: None,
+        ..*display
+    };
+
+    let mut output = Vec::new();
+    match format.results_format() {
+        None => writeln!(
+            output,
+            "{}",
+            crate::view::get_results_for_display(results, &display, None)
+        )?,
+        Some(f) if f.is_binary() => {
+            anyhow::bail!(
+                "results in a binary format (parquet, arrow) cannot be displayed via a pager"
+            )
+        }
+        Some(f) => write_results_to(results, &f, write_options, &mut output)?,
+    }
+
+    let mut child = pager_command(pager)?
+        .stdin(Stdio::piped())
+        .spawn()
+        .context("couldn't execute pager command")?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // a pager that's quit before reading all of its input closes the pipe, which is fine
+        match stdin.write_all(&output) {
+            Err(e) if e.kind() != ErrorKind::BrokenPipe => {
+                return Err(e).context("couldn't write results to pager");
+            }
+            _ => {}
+        }
+    }
+
+    child.wait().context("pager command failed")?;
+
+    Ok(())
+}
+
+fn page_file<P>(results_file: P, pager: &Pager) -> anyhow::Result<()>
 where
     P: AsRef<Path>,
 {
-    let mut cmd = pager.get_command();
-
-    cmd.arg(results_file.as_ref())
+    pager_command(pager)?
+        .arg(results_file.as_ref())
         .spawn()
         .context("couldn't execute pager command")?
         .wait()
@@ -16,3 +118,53 @@ where
 
     Ok(())
 }
+
+fn pager_command(pager: &Pager) -> anyhow::Result<std::process::Command> {
+    pager
+        .get_command()
+        .context("the built-in pager doesn't run a command")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_snapshot;
+
+    #[test]
+    fn page_results_feeds_every_row_of_rendered_table_to_pager_via_stdin() -> anyhow::Result<()> {
+        // GIVEN
+        let results = vec![
+            serde_json::json!({"language": "Rust", "year": 2010}),
+            serde_json::json!({"language": "Python", "year": 1991}),
+        ];
+        let results = NonEmptyResults::with_columns(&["language", "year"], results);
+        let temp_dir = tempfile::tempdir()?;
+        let paged_file = temp_dir.path().join("paged.txt");
+        let pager = Pager::custom(&format!("sh -c 'cat > {}' -", paged_file.display()))?;
+
+        // WHEN
+        page_results(
+            &results,
+            None,
+            &pager,
+            OutputFormat::Table,
+            &ResultsFormat::Json,
+            WriteOptions::default(),
+            &DisplayOptions {
+                max_rows: Some(1),
+                ..Default::default()
+            },
+        )?;
+
+        // THEN
+        let paged = std::fs::read_to_string(paged_file)?;
+        assert_snapshot!(paged, @r"
+         language | year 
+        ----------+------
+         Rust     | 2010 
+         Python   | 1991
+        ");
+
+        Ok(())
+    }
+}
@@ -1,15 +1,18 @@
-use crate::domain::{NonEmptyResults, ResultsFormat};
+use super::columnar::{write_arrow, write_parquet};
+use super::markup::{get_results_html, get_results_markdown};
+use crate::domain::{GraphValue, NonEmptyResults, ResultsFormat, WriteOptions};
 use anyhow::Context;
 use chrono::{DateTime, Utc};
-use serde_json::Value;
+use serde::ser::{Serialize, SerializeMap, Serializer};
 use std::fs::File;
-use std::io::Write;
+use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
 
 pub fn write_results<P>(
     results: &NonEmptyResults,
     results_directory: P,
     format: &ResultsFormat,
+    options: WriteOptions,
     reference_time: DateTime<Utc>,
 ) -> anyhow::Result<PathBuf>
 where
@@ -35,37 +38,63 @@ where
         )
     })?;
 
-    match format {
-        ResultsFormat::Csv => write_csv(results, file)?,
-        ResultsFormat::Json => write_json(results, file)?,
-    }
+    write_results_to(results, format, options, file)?;
 
     Ok(results_file_path)
 }
 
+/// Writes results in a format to a writer, eg. stdout.
+///
+/// Compression only applies to columnar formats; parquet files are compressed with zstd, and
+/// arrow files aren't compressed, unless specified otherwise.
+pub fn write_results_to<W>(
+    results: &NonEmptyResults,
+    format: &ResultsFormat,
+    options: WriteOptions,
+    writer: W,
+) -> anyhow::Result<()>
+where
+    W: Write,
+{
+    let flattened;
+    let results = match options.flatten {
+        Some(arrays) if !format.is_nested() => {
+            flattened = results.flattened(arrays);
+            &flattened
+        }
+        _ => results,
+    };
+    let compression = options.compression;
+
+    match format {
+        ResultsFormat::Csv => write_csv(results, writer),
+        ResultsFormat::Json => write_json(results, writer),
+        ResultsFormat::Ndjson => write_ndjson(results, writer),
+        ResultsFormat::Tsv => write_tsv(results, writer),
+        ResultsFormat::Parquet => write_parquet(results, compression, writer),
+        ResultsFormat::Arrow => write_arrow(results, compression, writer),
+        ResultsFormat::Markdown => write_rendered(get_results_markdown(results), writer),
+        ResultsFormat::Html => write_rendered(get_results_html(results), writer),
+    }
+}
+
 fn write_csv<W>(results: &NonEmptyResults, writer: W) -> anyhow::Result<()>
 where
     W: Write,
 {
     let mut csv_writer = csv::Writer::from_writer(writer);
 
-    let Some(first) = results.first().as_object() else {
-        // this is alright as the result from the db is expected to be an array of objects, each
-        // having the same keys
-        anyhow::bail!("expected results to be an array of objects");
-    };
-
-    let headers: Vec<&str> = first.keys().map(|s| s.as_str()).collect();
-    csv_writer.write_record(&headers)?;
+    let headers = results.columns();
+    if headers.is_empty() {
+        anyhow::bail!("results don't have any columns");
+    }
 
-    for result in results.list() {
-        let Some(obj) = result.as_object() else {
-            anyhow::bail!("expected each result to be an object");
-        };
+    csv_writer.write_record(headers)?;
 
-        let row: Vec<String> = headers
-            .iter()
-            .map(|&header| obj.get(header).map(value_to_csv_field).unwrap_or_default())
+    for cells in results.cells() {
+        let row: Vec<String> = cells
+            .into_iter()
+            .map(|cell| cell.map(value_to_csv_field).unwrap_or_default())
             .collect();
 
         csv_writer.write_record(&row)?;
@@ -75,13 +104,65 @@ where
     Ok(())
 }
 
-fn value_to_csv_field(value: &Value) -> String {
+/// Writes tab separated values; tabs, newlines and backslashes in values are escaped (as `\t`,
+/// `\n` and `\\`) rather than quoted, so that every row stays on a single line.
+fn write_tsv<W>(results: &NonEmptyResults, writer: W) -> anyhow::Result<()>
+where
+    W: Write,
+{
+    let mut writer = BufWriter::new(writer);
+
+    let headers = results.columns();
+    if headers.is_empty() {
+        anyhow::bail!("results don't have any columns");
+    }
+
+    let header_line: Vec<String> = headers.iter().map(|h| escape_tsv_field(h)).collect();
+    writeln!(writer, "{}", header_line.join("\t")).context("couldn't write bytes to file")?;
+
+    for cells in results.cells() {
+        let row: Vec<String> = cells
+            .into_iter()
+            .map(|cell| {
+                cell.map(|v| escape_tsv_field(&value_to_csv_field(v)))
+                    .unwrap_or_default()
+            })
+            .collect();
+        writeln!(writer, "{}", row.join("\t")).context("couldn't write bytes to file")?;
+    }
+
+    writer.flush().context("couldn't write bytes to file")?;
+    Ok(())
+}
+
+fn escape_tsv_field(field: &str) -> String {
+    field
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// Writes results that have already been rendered as text (eg. as a markdown table).
+fn write_rendered<W>(rendered: String, mut writer: W) -> anyhow::Result<()>
+where
+    W: Write,
+{
+    writeln!(writer, "{rendered}").context("couldn't write bytes to file")?;
+
+    Ok(())
+}
+
+pub(super) fn value_to_csv_field(value: &GraphValue) -> String {
     match value {
-        Value::Null => String::new(),
-        Value::Bool(b) => b.to_string(),
-        Value::Number(n) => n.to_string(),
-        Value::String(s) => s.clone(),
-        _ => serde_json::to_string(value).unwrap_or_default(),
+        GraphValue::Null => String::new(),
+        GraphValue::List(_)
+        | GraphValue::Map(_)
+        | GraphValue::Node(_)
+        | GraphValue::Relationship(_)
+        | GraphValue::Path(_)
+        | GraphValue::Point(_) => serde_json::to_string(value).unwrap_or_default(),
+        _ => value.to_string(),
     }
 }
 
@@ -89,8 +170,17 @@ fn write_json<W>(results: &NonEmptyResults, mut writer: W) -> anyhow::Result<()>
 where
     W: Write,
 {
-    let json_string = serde_json::to_string_pretty(results.list())
-        .context("couldn't serialize results to JSON")?;
+    let rows: Vec<OrderedRow> = results
+        .list()
+        .iter()
+        .map(|row| OrderedRow {
+            columns: results.columns(),
+            row,
+        })
+        .collect();
+
+    let json_string =
+        serde_json::to_string_pretty(&rows).context("couldn't serialize results to JSON")?;
     writer
         .write_all(json_string.as_bytes())
         .context("couldn't write bytes to file")?;
@@ -98,9 +188,63 @@ where
     Ok(())
 }
 
+/// Writes a row per line, each as compact JSON, eg. for piping into `jq -c` or loading into
+/// DuckDB.
+fn write_ndjson<W>(results: &NonEmptyResults, writer: W) -> anyhow::Result<()>
+where
+    W: Write,
+{
+    let mut writer = BufWriter::new(writer);
+    for row in results.list() {
+        let row = OrderedRow {
+            columns: results.columns(),
+            row,
+        };
+        serde_json::to_writer(&mut writer, &row).context("couldn't serialize row to JSON")?;
+        writer
+            .write_all(b"\n")
+            .context("couldn't write bytes to file")?;
+    }
+
+    writer.flush().context("couldn't write bytes to file")?;
+    Ok(())
+}
+
+/// Serializes an object row with its keys in the order of the result's columns (a
+/// `GraphValue::Map` keeps its keys sorted); non-object rows are serialized as is.
+struct OrderedRow<'a> {
+    columns: &'a [String],
+    row: &'a GraphValue,
+}
+
+impl Serialize for OrderedRow<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let GraphValue::Map(obj) = self.row else {
+            return self.row.serialize(serializer);
+        };
+
+        let mut map = serializer.serialize_map(Some(obj.len()))?;
+        for column in self.columns {
+            if let Some(value) = obj.get(column) {
+                map.serialize_entry(column, value)?;
+            }
+        }
+        for (key, value) in obj {
+            if !self.columns.contains(key) {
+                map.serialize_entry(key, value)?;
+            }
+        }
+        map.end()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::ArrayFlattening;
     use insta::assert_snapshot;
 
     #[test]
@@ -151,6 +295,108 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn write_ndjson_writes_a_row_per_line() -> anyhow::Result<()> {
+        // GIVEN
+        let results = results_sample_one();
+        let mut buffer = Vec::new();
+
+        // WHEN
+        write_ndjson(&results, &mut buffer)?;
+
+        // THEN
+        let result = String::from_utf8(buffer)?;
+        assert_snapshot!(result);
+
+        Ok(())
+    }
+
+    #[test]
+    fn write_ndjson_keeps_nested_values_on_a_single_line() -> anyhow::Result<()> {
+        // GIVEN
+        let results = results_sample_two();
+        let mut buffer = Vec::new();
+
+        // WHEN
+        write_ndjson(&results, &mut buffer)?;
+
+        // THEN
+        let result = String::from_utf8(buffer)?;
+        assert_snapshot!(result);
+
+        Ok(())
+    }
+
+    #[test]
+    fn write_tsv_escapes_tabs_and_newlines() -> anyhow::Result<()> {
+        // GIVEN
+        let results = NonEmptyResults::with_columns(
+            &["language", "tagline"],
+            vec![
+                serde_json::json!({"language": "Rust", "tagline": "fast\treliable\nproductive"}),
+                serde_json::json!({"language": "Go", "tagline": "C:\\go"}),
+            ],
+        );
+        let mut buffer = Vec::new();
+
+        // WHEN
+        write_tsv(&results, &mut buffer)?;
+
+        // THEN
+        let result = String::from_utf8(buffer)?;
+        assert_snapshot!(result);
+
+        Ok(())
+    }
+
+    #[test]
+    fn write_results_to_flattens_nested_values_for_csv() -> anyhow::Result<()> {
+        // GIVEN
+        let results = NonEmptyResults::with_columns(
+            &["language", "features"],
+            vec![
+                serde_json::json!({"language": "Rust", "features": {"garbage_collection": false}}),
+                serde_json::json!({"language": "Go", "features": {"garbage_collection": true, "static_typing": true}}),
+            ],
+        );
+        let options = WriteOptions {
+            flatten: Some(ArrayFlattening::Json),
+            ..Default::default()
+        };
+        let mut buffer = Vec::new();
+
+        // WHEN
+        write_results_to(&results, &ResultsFormat::Csv, options, &mut buffer)?;
+
+        // THEN
+        let result = String::from_utf8(buffer)?;
+        assert_snapshot!(result);
+
+        Ok(())
+    }
+
+    #[test]
+    fn write_csv_handles_rows_of_mixed_shapes() -> anyhow::Result<()> {
+        // GIVEN
+        let results = NonEmptyResults::try_from(vec![
+            serde_json::json!({"language": "Rust", "year": 2010}),
+            serde_json::json!({"language": "Python", "creator": "Guido van Rossum"}),
+            serde_json::json!("Go"),
+            serde_json::json!(42),
+        ])
+        .expect("results should've been created");
+        let mut buffer = Vec::new();
+
+        // WHEN
+        write_csv(&results, &mut buffer)?;
+
+        // THEN
+        let result = String::from_utf8(buffer)?;
+        assert_snapshot!(result);
+
+        Ok(())
+    }
+
     fn results_sample_one() -> NonEmptyResults {
         let results = vec![
             serde_json::json!({"language": "Rust", "creator": "Graydon Hoare", "year": 2010}),
@@ -158,7 +404,7 @@ mod tests {
             serde_json::json!({"language": "Go", "creator": "Rob Pike", "year": 2009}),
         ];
 
-        NonEmptyResults::try_from(results).expect("results should've been created")
+        NonEmptyResults::with_columns(&["language", "creator", "year"], results)
     }
 
     fn results_sample_two() -> NonEmptyResults {
@@ -1,8 +1,12 @@
 use crate::domain::Pager;
 
+/// Value of `GRAFQ_PAGER` that selects grafq's own results browser.
+pub const BUILTIN_PAGER: &str = "builtin";
+
 pub fn get_pager() -> anyhow::Result<Pager> {
     let pager_env_var = get_env_var("GRAFQ_PAGER")?;
     let pager = match pager_env_var {
+        Some(p) if p.trim() == BUILTIN_PAGER => Pager::builtin(),
         Some(p) => Pager::custom(&p)?,
         None => Pager::default()?,
     };
@@ -10,6 +14,11 @@ pub fn get_pager() -> anyhow::Result<Pager> {
     Ok(pager)
 }
 
+/// Width of the terminal stdout is connected to, if it is connected to one.
+pub fn terminal_width() -> Option<usize> {
+    terminal_size::terminal_size().map(|(width, _)| width.0 as usize)
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum EnvVarError {
     #[error(r#"environment variable "{0}" is not valid unicode"#)]
@@ -0,0 +1,128 @@
+use crate::domain::{ComparedQuery, Speedup, format_duration};
+use tabled::builder::Builder;
+use tabled::settings::style::Style;
+
+/// Renders a table comparing queries benchmarked together, with the first query acting as the
+/// reference the others' speedups are relative to.
+pub fn get_benchmark_comparison(queries: &[ComparedQuery]) -> String {
+    let mut builder = Builder::default();
+    builder.push_record([
+        "#",
+        "query",
+        "median",
+        "mean",
+        "p95",
+        "rows",
+        "speedup",
+        "significant",
+    ]);
+
+    let reference = queries.first().map(|q| &q.stats);
+    for (i, query) in queries.iter().enumerate() {
+        let (speedup, significance) = match reference {
+            Some(reference) if i > 0 => {
+                let speedup = Speedup::new(reference, &query.stats);
+                (describe_speedup(&speedup), describe_significance(&speedup))
+            }
+            _ => ("reference".to_string(), "-".to_string()),
+        };
+
+        builder.push_record([
+            (i + 1).to_string(),
+            query.label.clone(),
+            format_duration(query.stats.median),
+            format_duration(query.stats.mean),
+            format_duration(query.stats.p95),
+            query
+                .row_counts
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            speedup,
+            significance,
+        ]);
+    }
+
+    let mut table = builder.build();
+    table.with(Style::psql());
+
+    table.to_string()
+}
+
+fn describe_speedup(speedup: &Speedup) -> String {
+    if speedup.ratio >= 1.0 {
+        format!("{:.2}x faster", speedup.ratio)
+    } else {
+        format!("{:.2}x slower", 1.0 / speedup.ratio)
+    }
+}
+
+fn describe_significance(speedup: &Speedup) -> String {
+    let p_value = if speedup.p_value < 0.001 {
+        "p<0.001".to_string()
+    } else {
+        format!("p={:.3}", speedup.p_value)
+    };
+
+    if speedup.is_significant() {
+        format!("yes ({p_value})")
+    } else {
+        format!("no ({p_value})")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::BenchmarkStats;
+    use insta::assert_snapshot;
+    use std::collections::BTreeSet;
+    use std::time::Duration;
+
+    #[test]
+    fn get_benchmark_comparison_shows_speedups_relative_to_the_first_query() {
+        // GIVEN
+        let query = |label: &str, timings: &[u64], row_counts: &[usize]| ComparedQuery {
+            label: label.to_string(),
+            stats: BenchmarkStats::new(timings.iter().map(|t| Duration::from_micros(*t)).collect())
+                .expect("stats should've been computed"),
+            row_counts: row_counts.iter().copied().collect::<BTreeSet<_>>(),
+        };
+        let queries = vec![
+            query(
+                "MATCH (l:Language) RETURN l",
+                &[
+                    20_500, 21_000, 19_750, 22_250, 20_000, 21_500, 20_250, 23_000,
+                ],
+                &[10],
+            ),
+            query(
+                "queries/languages.cypher",
+                &[
+                    10_250, 11_000, 10_500, 12_750, 10_000, 10_750, 11_500, 9_750,
+                ],
+                &[10],
+            ),
+            query(
+                "MATCH (l) WHERE l:Language RETURN l",
+                &[
+                    21_000, 20_250, 22_750, 21_500, 20_500, 23_000, 21_750, 20_000,
+                ],
+                &[9, 10],
+            ),
+        ];
+
+        // WHEN
+        let result = get_benchmark_comparison(&queries);
+
+        // THEN
+        assert_snapshot!(result, @r"
+         # | query                               | median   | mean     | p95      | rows  | speedup      | significant   
+        ---+-------------------------------------+----------+----------+----------+-------+--------------+---------------
+         1 | MATCH (l:Language) RETURN l         | 20.750ms | 21.031ms | 22.738ms | 10    | reference    | -             
+         2 | queries/languages.cypher            | 10.625ms | 10.812ms | 12.312ms | 10    | 1.95x faster | yes (p<0.001) 
+         3 | MATCH (l) WHERE l:Language RETURN l | 21.250ms | 21.344ms | 22.912ms | 9, 10 | 1.02x slower | no (p=0.562)
+        ");
+    }
+}
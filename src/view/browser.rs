@@ -0,0 +1,566 @@
+use crate::domain::{GraphValue, NonEmptyResults};
+use anyhow::Context;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::cmp::Ordering;
+use std::io::Write;
+
+const MAX_COLUMN_WIDTH: usize = 40;
+const COLUMN_SPACING: u16 = 2;
+const KEY_HINTS: &str =
+    "q quit  / search  s sort  enter details  y copy cell  Y copy row  ←→↑↓/hjkl move";
+
+/// Opens a full-screen browser for results, with a scrollable grid (with frozen headers), column
+/// sorting, incremental search, a pane showing a cell's value in full, and copying of cells and
+/// rows (to the clipboard, via the terminal).
+pub fn browse_results(results: &NonEmptyResults) -> anyhow::Result<()> {
+    let mut browser = Browser::new(results);
+
+    let mut terminal = ratatui::try_init().context("couldn't set up the terminal")?;
+    let result = browser.run(&mut terminal);
+    ratatui::try_restore().context("couldn't restore the terminal")?;
+
+    result
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Browse,
+    Search,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Sort {
+    column: usize,
+    descending: bool,
+}
+
+struct Browser<'a> {
+    columns: &'a [String],
+    cells: Vec<Vec<Option<&'a GraphValue>>>,
+    // cells as they're displayed in the grid, ie. on a single line
+    texts: Vec<Vec<String>>,
+    widths: Vec<usize>,
+    // indices of the rows matching the search, in the order they're sorted in
+    visible: Vec<usize>,
+    sort: Option<Sort>,
+    search: String,
+    mode: Mode,
+    selected_row: usize,
+    selected_column: usize,
+    first_column: usize,
+    page_size: usize,
+    table_state: TableState,
+    details: bool,
+    details_scroll: u16,
+    status: Option<String>,
+    to_copy: Option<String>,
+    quit: bool,
+}
+
+impl<'a> Browser<'a> {
+    fn new(results: &'a NonEmptyResults) -> Self {
+        let columns = results.columns();
+        let cells: Vec<Vec<Option<&GraphValue>>> = results.cells().collect();
+        let texts: Vec<Vec<String>> = cells
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|c| {
+                        c.map(|v| v.to_string().replace(['\n', '\r'], " "))
+                            .unwrap_or_default()
+                    })
+                    .collect()
+            })
+            .collect();
+        let widths = columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                texts
+                    .iter()
+                    .map(|row| row[i].chars().count())
+                    // leaving room for the sort marker
+                    .chain([column.chars().count() + 2])
+                    .max()
+                    .unwrap_or_default()
+                    .clamp(1, MAX_COLUMN_WIDTH)
+            })
+            .collect();
+        let visible = (0..cells.len()).collect();
+
+        Self {
+            columns,
+            cells,
+            texts,
+            widths,
+            visible,
+            sort: None,
+            search: String::new(),
+            mode: Mode::Browse,
+            selected_row: 0,
+            selected_column: 0,
+            first_column: 0,
+            page_size: 1,
+            table_state: TableState::default(),
+            details: false,
+            details_scroll: 0,
+            status: None,
+            to_copy: None,
+            quit: false,
+        }
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> anyhow::Result<()> {
+        while !self.quit {
+            terminal
+                .draw(|frame| self.draw(frame))
+                .context("couldn't draw results")?;
+
+            if let Event::Key(key) = event::read().context("couldn't read terminal events")?
+                && key.kind == KeyEventKind::Press
+            {
+                self.handle_key(key);
+            }
+
+            if let Some(text) = self.to_copy.take() {
+                copy_to_clipboard(&text).context("couldn't copy to clipboard")?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        self.status = None;
+
+        if self.mode == Mode::Search {
+            match key.code {
+                KeyCode::Enter => self.mode = Mode::Browse,
+                KeyCode::Esc => {
+                    self.mode = Mode::Browse;
+                    self.search.clear();
+                    self.refresh_rows();
+                }
+                KeyCode::Backspace => {
+                    self.search.pop();
+                    self.refresh_rows();
+                }
+                KeyCode::Char(c) => {
+                    self.search.push(c);
+                    self.refresh_rows();
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('c') if ctrl => self.quit = true,
+            KeyCode::Char('q') => self.quit = true,
+            KeyCode::Esc if self.details => self.details = false,
+            KeyCode::Esc if !self.search.is_empty() => {
+                self.search.clear();
+                self.refresh_rows();
+            }
+            KeyCode::Esc => self.quit = true,
+            KeyCode::Down | KeyCode::Char('j') => self.move_rows(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_rows(-1),
+            KeyCode::PageDown | KeyCode::Char(' ') => self.move_rows(self.page_size as isize),
+            KeyCode::PageUp | KeyCode::Char('b') => self.move_rows(-(self.page_size as isize)),
+            KeyCode::Home | KeyCode::Char('g') => self.selected_row = 0,
+            KeyCode::End | KeyCode::Char('G') => {
+                self.selected_row = self.visible.len().saturating_sub(1);
+            }
+            KeyCode::Right | KeyCode::Char('l') => self.move_columns(1),
+            KeyCode::Left | KeyCode::Char('h') => self.move_columns(-1),
+            KeyCode::Char('/') => self.mode = Mode::Search,
+            KeyCode::Char('s') => self.toggle_sort(),
+            KeyCode::Enter => {
+                self.details = !self.details;
+                self.details_scroll = 0;
+            }
+            KeyCode::Char('J') if self.details => {
+                self.details_scroll = self.details_scroll.saturating_add(1);
+            }
+            KeyCode::Char('K') if self.details => {
+                self.details_scroll = self.details_scroll.saturating_sub(1);
+            }
+            KeyCode::Char('y') => {
+                if let Some(value) = self.selected_cell() {
+                    self.to_copy = Some(cell_details(value));
+                    self.status = Some("copied cell".to_string());
+                }
+            }
+            KeyCode::Char('Y') => {
+                if let Some(row) = self.selected_row_json() {
+                    self.to_copy = Some(row);
+                    self.status = Some("copied row (as JSON)".to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn move_rows(&mut self, by: isize) {
+        let last = self.visible.len().saturating_sub(1);
+        self.selected_row = self.selected_row.saturating_add_signed(by).min(last);
+    }
+
+    fn move_columns(&mut self, by: isize) {
+        let last = self.columns.len().saturating_sub(1);
+        self.selected_column = self.selected_column.saturating_add_signed(by).min(last);
+        self.details_scroll = 0;
+    }
+
+    /// Sorts rows by the selected column: ascending first, then descending, and then back to the
+    /// order they were returned in.
+    fn toggle_sort(&mut self) {
+        let column = self.selected_column;
+        self.sort = match self.sort {
+            Some(Sort {
+                column: c,
+                descending: false,
+            }) if c == column => Some(Sort {
+                column,
+                descending: true,
+            }),
+            Some(Sort {
+                column: c,
+                descending: true,
+            }) if c == column => None,
+            _ => Some(Sort {
+                column,
+                descending: false,
+            }),
+        };
+        self.refresh_rows();
+    }
+
+    /// Recomputes the rows to show, and their order, as per the search and the sort.
+    fn refresh_rows(&mut self) {
+        let search = self.search.to_lowercase();
+        self.visible = (0..self.cells.len())
+            .filter(|&i| {
+                search.is_empty()
+                    || self.texts[i]
+                        .iter()
+                        .any(|text| text.to_lowercase().contains(&search))
+            })
+            .collect();
+
+        if let Some(sort) = self.sort {
+            self.visible.sort_by(|&a, &b| {
+                let ordering = compare_values(
+                    self.cells[a][sort.column],
+                    self.cells[b][sort.column],
+                    &self.texts[a][sort.column],
+                    &self.texts[b][sort.column],
+                );
+                if sort.descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            });
+        }
+
+        self.selected_row = 0;
+        self.details_scroll = 0;
+    }
+
+    fn selected_cell(&self) -> Option<Option<&'a GraphValue>> {
+        self.visible
+            .get(self.selected_row)
+            .map(|&row| self.cells[row][self.selected_column])
+    }
+
+    fn selected_row_json(&self) -> Option<String> {
+        let row = self.visible.get(self.selected_row)?;
+        let entries = self
+            .columns
+            .iter()
+            .zip(&self.cells[*row])
+            .map(|(column, value)| {
+                format!(
+                    "{}:{}",
+                    serde_json::to_string(column).unwrap_or_default(),
+                    serde_json::to_string(value).unwrap_or_default()
+                )
+            })
+            .collect::<Vec<_>>();
+
+        Some(format!("{{{}}}", entries.join(",")))
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [table_area, details_area, footer_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Percentage(if self.details { 40 } else { 0 }),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        // header, and the line below it
+        self.page_size = (table_area.height as usize).saturating_sub(2).max(1);
+        self.scroll_columns_into_view(table_area.width as usize);
+
+        let shown_columns = self.shown_columns(table_area.width as usize);
+        let header = Row::new(shown_columns.clone().map(|i| {
+            let marker = match self.sort {
+                Some(Sort { column, descending }) if column == i => {
+                    if descending {
+                        " ↓"
+                    } else {
+                        " ↑"
+                    }
+                }
+                _ => "",
+            };
+            Cell::from(format!("{}{marker}", self.columns[i]))
+        }))
+        .style(Style::default().add_modifier(Modifier::BOLD))
+        .bottom_margin(1);
+        let rows = self.visible.iter().map(|&row| {
+            Row::new(
+                shown_columns
+                    .clone()
+                    .map(|i| Cell::from(self.texts[row][i].as_str())),
+            )
+        });
+        let widths = shown_columns
+            .clone()
+            .map(|i| Constraint::Length(self.widths[i] as u16));
+
+        let table = Table::new(rows, widths)
+            .header(header)
+            .column_spacing(COLUMN_SPACING)
+            .row_highlight_style(Style::default().bg(Color::DarkGray))
+            .cell_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        self.table_state.select(Some(self.selected_row));
+        self.table_state
+            .select_column(Some(self.selected_column - self.first_column));
+        frame.render_stateful_widget(table, table_area, &mut self.table_state);
+
+        if self.details {
+            let details = Paragraph::new(cell_details(self.selected_cell().flatten()))
+                .wrap(Wrap { trim: false })
+                .scroll((self.details_scroll, 0))
+                .block(
+                    Block::default()
+                        .borders(Borders::TOP)
+                        .title(format!(" {} ", self.columns[self.selected_column])),
+                );
+            frame.render_widget(details, details_area);
+        }
+
+        frame.render_widget(self.footer(), footer_area);
+    }
+
+    /// Moves the first column shown just enough for the selected column to be visible.
+    fn scroll_columns_into_view(&mut self, width: usize) {
+        if self.selected_column < self.first_column {
+            self.first_column = self.selected_column;
+        }
+        while self.first_column < self.selected_column
+            && !self.shown_columns(width).contains(&self.selected_column)
+        {
+            self.first_column += 1;
+        }
+    }
+
+    /// Columns that fit in `width`, starting from the first column shown (which is always shown,
+    /// albeit cut short, if needed).
+    fn shown_columns(&self, width: usize) -> std::ops::Range<usize> {
+        let mut used = 0;
+        let mut end = self.first_column;
+        while end < self.columns.len() {
+            let spacing = if end > self.first_column {
+                COLUMN_SPACING as usize
+            } else {
+                0
+            };
+            let needed = self.widths[end] + spacing;
+            if end > self.first_column && used + needed > width {
+                break;
+            }
+            used += needed;
+            end += 1;
+        }
+
+        self.first_column..end
+    }
+
+    fn footer(&self) -> Line<'_> {
+        if self.mode == Mode::Search {
+            return Line::from(vec![Span::raw("/"), Span::raw(self.search.as_str())]);
+        }
+
+        let position = format!(
+            " row {}/{}  column {}/{} ",
+            (self.selected_row + 1).min(self.visible.len()),
+            self.visible.len(),
+            self.selected_column + 1,
+            self.columns.len(),
+        );
+        let mut spans = vec![Span::styled(
+            position,
+            Style::default().add_modifier(Modifier::REVERSED),
+        )];
+        if !self.search.is_empty() {
+            spans.push(Span::raw(format!("  search: {}", self.search)));
+        }
+        spans.push(Span::styled(
+            format!("  {}", self.status.as_deref().unwrap_or(KEY_HINTS)),
+            Style::default().fg(Color::DarkGray),
+        ));
+
+        Line::from(spans)
+    }
+}
+
+/// Orders nulls (and missing values) last, numbers numerically, and everything else by how it's
+/// displayed.
+fn compare_values(
+    a: Option<&GraphValue>,
+    b: Option<&GraphValue>,
+    a_text: &str,
+    b_text: &str,
+) -> Ordering {
+    match (a, b) {
+        (None | Some(GraphValue::Null), None | Some(GraphValue::Null)) => Ordering::Equal,
+        (None | Some(GraphValue::Null), _) => Ordering::Greater,
+        (_, None | Some(GraphValue::Null)) => Ordering::Less,
+        (Some(GraphValue::Number(a)), Some(GraphValue::Number(b))) => a
+            .as_f64()
+            .partial_cmp(&b.as_f64())
+            .unwrap_or(Ordering::Equal),
+        _ => a_text.cmp(b_text),
+    }
+}
+
+/// Strings are shown as they are; everything else as pretty-printed JSON.
+fn cell_details(value: Option<&GraphValue>) -> String {
+    match value {
+        None => String::new(),
+        Some(GraphValue::String(s)) => s.clone(),
+        Some(value) => serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string()),
+    }
+}
+
+/// Copies text to the clipboard via an OSC 52 escape sequence, which most terminal emulators (and
+/// tmux, with `set-clipboard` on) support, including over SSH.
+fn copy_to_clipboard(text: &str) -> std::io::Result<()> {
+    let mut stdout = std::io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", BASE64.encode(text))?;
+    stdout.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_snapshot;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    fn sample_results() -> NonEmptyResults {
+        let results = vec![
+            serde_json::json!({"language": "Rust", "year": 2010, "features": {"gc": false}}),
+            serde_json::json!({"language": "Python", "year": 1991, "features": {"gc": true}}),
+            serde_json::json!({"language": "Go", "year": 2009}),
+        ];
+        NonEmptyResults::with_columns(&["language", "year", "features"], results)
+    }
+
+    fn press(browser: &mut Browser, keys: &str) {
+        for c in keys.chars() {
+            browser.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+    }
+
+    fn languages(browser: &Browser) -> String {
+        browser
+            .visible
+            .iter()
+            .map(|&row| browser.texts[row][0].as_str())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    #[test]
+    fn sorting_cycles_through_ascending_descending_and_original_order() {
+        // GIVEN
+        let results = sample_results();
+        let mut browser = Browser::new(&results);
+        press(&mut browser, "l");
+
+        // WHEN
+        press(&mut browser, "s");
+        let ascending = languages(&browser);
+        press(&mut browser, "s");
+        let descending = languages(&browser);
+        press(&mut browser, "s");
+        let original = languages(&browser);
+
+        // THEN
+        assert_eq!(ascending, "Python,Go,Rust");
+        assert_eq!(descending, "Rust,Go,Python");
+        assert_eq!(original, "Rust,Python,Go");
+    }
+
+    #[test]
+    fn search_filters_rows_as_query_is_typed() {
+        // GIVEN
+        let results = sample_results();
+        let mut browser = Browser::new(&results);
+
+        // WHEN
+        press(&mut browser, "/O");
+        let after_one_char = languages(&browser);
+        press(&mut browser, "n");
+        let after_two_chars = languages(&browser);
+
+        // THEN
+        assert_eq!(after_one_char, "Python,Go");
+        assert_eq!(after_two_chars, "Python");
+    }
+
+    #[test]
+    fn browser_shows_details_of_selected_cell() -> anyhow::Result<()> {
+        // GIVEN
+        let results = sample_results();
+        let mut browser = Browser::new(&results);
+        let mut terminal = Terminal::new(TestBackend::new(60, 12))?;
+        press(&mut browser, "ll");
+        browser.handle_key(KeyEvent::from(KeyCode::Enter));
+
+        // WHEN
+        terminal.draw(|frame| browser.draw(frame))?;
+
+        // THEN
+        assert_snapshot!(terminal.backend(), @r#"
+        "language    year    features                                "
+        "                                                            "
+        "Rust        2010    {gc: false}                             "
+        "Python      1991    {gc: true}                              "
+        "Go          2009                                            "
+        "                                                            "
+        " features ──────────────────────────────────────────────────"
+        "{                                                           "
+        "  "gc": false                                               "
+        "}                                                           "
+        "                                                            "
+        " row 1/3  column 3/3   q quit  / search  s sort  enter detai"
+        "#);
+
+        Ok(())
+    }
+}
@@ -1,25 +1,40 @@
+use super::console::is_console_cmd;
+use super::highlight_query;
+use crate::domain::{KEYWORDS, Schema, TokenKind, is_statement_complete, tokenize};
 use rustyline::completion::{Completer, FilenameCompleter, Pair};
-use rustyline::highlight::Highlighter;
+use rustyline::highlight::{CmdKind, Highlighter};
 use rustyline::hint::Hinter;
-use rustyline::validate::Validator;
+use rustyline::validate::{ValidationContext, ValidationResult, Validator};
 use rustyline::{Context, Helper, Result};
+use std::borrow::Cow;
 
 pub struct QueryFilenameCompleter {
     inner: FilenameCompleter,
+    color: bool,
+    schema: Schema,
 }
 
-impl Default for QueryFilenameCompleter {
-    fn default() -> Self {
+impl QueryFilenameCompleter {
+    /// `color` determines whether queries are syntax highlighted as they're typed.
+    pub fn new(color: bool) -> Self {
         Self {
             inner: FilenameCompleter::new(),
+            color,
+            schema: Schema::default(),
         }
     }
+
+    /// Sets the schema used to complete labels, relationship types and property keys.
+    pub fn set_schema(&mut self, schema: Schema) {
+        self.schema = schema;
+    }
 }
 
 impl Completer for QueryFilenameCompleter {
     type Candidate = Pair;
 
-    /// Complete file paths for input starting with `@`.
+    /// Complete file paths for input starting with `@`; anything else (that's not a console
+    /// command) is completed as a query (see [`complete_query`]).
     ///
     /// Example: user types `@quer|` (cursor is `|`)
     ///
@@ -43,7 +58,11 @@ impl Completer for QueryFilenameCompleter {
         ctx: &Context<'_>,
     ) -> Result<(usize, Vec<Self::Candidate>)> {
         if !line.starts_with('@') {
-            return Ok((pos, vec![]));
+            if is_console_cmd(line.trim()) {
+                return Ok((pos, vec![]));
+            }
+
+            return Ok(complete_query(line, pos, &self.schema));
         }
 
         // Only complete when cursor is at the end of the line
@@ -59,12 +78,315 @@ impl Completer for QueryFilenameCompleter {
     }
 }
 
+/// Completes the word before the cursor based on where it's at in the query: labels after a `:`
+/// in a node pattern (or in a `WHERE` predicate), relationship types after a `:` in a
+/// relationship pattern, property keys after a `.` or as keys in a map, and Cypher keywords
+/// everywhere else. Nothing is completed inside strings and comments.
+fn complete_query(line: &str, pos: usize, schema: &Schema) -> (usize, Vec<Pair>) {
+    let before_cursor = &line[..pos];
+    let word_start = before_cursor
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_alphanumeric() || *c == '_')
+        .last()
+        .map(|(i, _)| i)
+        .unwrap_or(pos);
+    let word = &line[word_start..pos];
+
+    // a placeholder is appended to the input so that the word being completed is classified
+    // even if nothing has been typed yet (eg. right after a `:`)
+    let probe = format!("{before_cursor}_");
+    let tokens = tokenize(&probe);
+
+    if tokens
+        .iter()
+        .any(|t| matches!(t.kind, TokenKind::String | TokenKind::Comment) && t.end == probe.len())
+    {
+        return (pos, vec![]);
+    }
+
+    let kind = tokens
+        .iter()
+        .find(|t| t.start == word_start && t.end == probe.len())
+        .map(|t| t.kind);
+
+    let is_map_key = || {
+        let innermost_bracket = tokens
+            .iter()
+            .filter(|t| t.kind == TokenKind::Bracket && t.start < word_start)
+            .fold(vec![], |mut stack, t| {
+                match &probe[t.start..t.end] {
+                    "(" | "[" | "{" => stack.push(&probe[t.start..t.end]),
+                    _ => {
+                        stack.pop();
+                    }
+                }
+                stack
+            })
+            .pop();
+        let previous = before_cursor[..word_start]
+            .chars()
+            .rev()
+            .find(|c| !c.is_whitespace());
+
+        innermost_bracket == Some("{") && matches!(previous, Some('{') | Some(','))
+    };
+
+    let candidates: Vec<Pair> = match kind {
+        Some(TokenKind::Label) => names_with_prefix(&schema.labels, word),
+        Some(TokenKind::RelationshipType) => names_with_prefix(&schema.relationship_types, word),
+        Some(TokenKind::PropertyKey) => names_with_prefix(&schema.property_keys, word),
+        _ if is_map_key() => names_with_prefix(&schema.property_keys, word),
+        _ if word.is_empty() => vec![],
+        _ => KEYWORDS
+            .iter()
+            .filter(|k| k.starts_with(&word.to_ascii_uppercase()))
+            .map(|k| Pair {
+                display: k.to_string(),
+                replacement: k.to_string(),
+            })
+            .collect(),
+    };
+
+    (word_start, candidates)
+}
+
+fn names_with_prefix(names: &[String], prefix: &str) -> Vec<Pair> {
+    names
+        .iter()
+        .filter(|name| name.starts_with(prefix))
+        .map(|name| {
+            let is_identifier = name.chars().all(|c| c.is_alphanumeric() || c == '_')
+                && !name.starts_with(|c: char| c.is_ascii_digit());
+            Pair {
+                display: name.clone(),
+                replacement: if is_identifier {
+                    name.clone()
+                } else {
+                    format!("`{}`", name.replace('`', "``"))
+                },
+            }
+        })
+        .collect()
+}
+
 impl Hinter for QueryFilenameCompleter {
     type Hint = String;
 }
 
-impl Highlighter for QueryFilenameCompleter {}
+impl Highlighter for QueryFilenameCompleter {
+    /// Highlights Cypher syntax in queries; console commands are left as is.
+    fn highlight<'l>(&self, line: &'l str, pos: usize) -> Cow<'l, str> {
+        if !self.color || is_console_cmd(line.trim()) {
+            return Cow::Borrowed(line);
+        }
+
+        Cow::Owned(highlight_query(line, pos))
+    }
+
+    /// Re-highlights on every change (including cursor movements), as the brackets to emphasise
+    /// depend on the cursor's position.
+    fn highlight_char(&self, _line: &str, _pos: usize, _kind: CmdKind) -> bool {
+        self.color
+    }
+}
 
-impl Validator for QueryFilenameCompleter {}
+impl Validator for QueryFilenameCompleter {
+    /// Keeps reading (with Enter starting a new line) until input is complete (see
+    /// [`is_input_complete`]), so that statements can span multiple lines, and are stored in
+    /// history as a whole.
+    fn validate(&self, ctx: &mut ValidationContext) -> Result<ValidationResult> {
+        if is_input_complete(ctx.input()) {
+            Ok(ValidationResult::Valid(None))
+        } else {
+            Ok(ValidationResult::Incomplete)
+        }
+    }
+}
 
 impl Helper for QueryFilenameCompleter {}
+
+/// Input is complete when it's a console command (or blank), or when it forms complete statements
+/// (ie. it ends with a `;`, and has no unclosed brackets, quotes or comments).
+fn is_input_complete(input: &str) -> bool {
+    let input = input.trim();
+
+    is_console_cmd(input) || is_statement_complete(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_debug_snapshot;
+
+    fn complete(input: &str) -> (usize, Vec<String>) {
+        let schema = Schema::new(
+            [
+                "Language".to_string(),
+                "Person".to_string(),
+                "Paradigm".to_string(),
+            ],
+            ["CREATED_BY".to_string(), "INFLUENCED".to_string()],
+            [
+                "name".to_string(),
+                "nickname".to_string(),
+                "year".to_string(),
+                "first release".to_string(),
+            ],
+        );
+        let (start, candidates) = complete_query(input, input.len(), &schema);
+
+        (
+            start,
+            candidates.into_iter().map(|c| c.replacement).collect(),
+        )
+    }
+
+    //-------------//
+    //  SUCCESSES  //
+    //-------------//
+
+    #[test]
+    fn complete_query_completes_labels() {
+        // GIVEN
+        // WHEN
+        let in_pattern = complete("MATCH (l:P");
+        let in_predicate = complete("MATCH (l) WHERE l:");
+
+        // THEN
+        assert_debug_snapshot!((in_pattern, in_predicate), @r#"
+        (
+            (
+                9,
+                [
+                    "Paradigm",
+                    "Person",
+                ],
+            ),
+            (
+                18,
+                [
+                    "Language",
+                    "Paradigm",
+                    "Person",
+                ],
+            ),
+        )
+        "#);
+    }
+
+    #[test]
+    fn complete_query_completes_relationship_types() {
+        // GIVEN
+        // WHEN
+        let result = complete("MATCH (l:Language)-[:CREATED_BY|I");
+
+        // THEN
+        assert_debug_snapshot!(result, @r#"
+        (
+            32,
+            [
+                "INFLUENCED",
+            ],
+        )
+        "#);
+    }
+
+    #[test]
+    fn complete_query_completes_property_keys() {
+        // GIVEN
+        // WHEN
+        let after_dot = complete("MATCH (l:Language)\nRETURN l.");
+        let in_map = complete("MATCH (l:Language {name: 'Rust', f");
+
+        // THEN
+        assert_debug_snapshot!((after_dot, in_map), @r#"
+        (
+            (
+                28,
+                [
+                    "`first release`",
+                    "name",
+                    "nickname",
+                    "year",
+                ],
+            ),
+            (
+                33,
+                [
+                    "`first release`",
+                ],
+            ),
+        )
+        "#);
+    }
+
+    #[test]
+    fn complete_query_completes_keywords() {
+        // GIVEN
+        // WHEN
+        let result = complete("MATCH (l:Language) ret");
+
+        // THEN
+        assert_debug_snapshot!(result, @r#"
+        (
+            19,
+            [
+                "RETURN",
+            ],
+        )
+        "#);
+    }
+
+    #[test]
+    fn complete_query_completes_nothing_in_strings_and_comments() {
+        // GIVEN
+        // WHEN
+        let in_string = complete("MATCH (l {name: 'ret");
+        let in_comment = complete("MATCH (l) // ret");
+
+        // THEN
+        assert!(in_string.1.is_empty());
+        assert!(in_comment.1.is_empty());
+    }
+
+    #[test]
+    fn is_input_complete_accepts_console_commands_and_terminated_statements() {
+        // GIVEN
+        let inputs = [
+            "",
+            "page on",
+            "@queries/languages.cypher",
+            "MATCH (n) RETURN n;",
+            "MATCH (l:Language)\nWHERE l.year > 2000\nRETURN l.name;",
+            "RETURN 1; RETURN 2;",
+        ];
+
+        // WHEN
+        // THEN
+        for input in inputs {
+            assert!(is_input_complete(input), "{input} should've been complete");
+        }
+    }
+
+    #[test]
+    fn is_input_complete_keeps_reading_unterminated_statements() {
+        // GIVEN
+        let inputs = [
+            "MATCH (n)",
+            "MATCH (n)\nRETURN n",
+            "RETURN 'a;",
+            "RETURN [1, 2;",
+            "RETURN 1 /* ;",
+        ];
+
+        // WHEN
+        // THEN
+        for input in inputs {
+            assert!(
+                !is_input_complete(input),
+                "{input} shouldn't have been complete"
+            );
+        }
+    }
+}
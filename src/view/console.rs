@@ -1,15 +1,28 @@
-use super::{QueryFilenameCompleter, get_results};
+use super::{QueryFilenameCompleter, get_plan, get_results_for_display};
 use crate::config::DEFAULT_RESULTS_DIR;
-use crate::domain::{Pager, QueryResults, ResultsFormat};
-use crate::repository::QueryExecutor;
-use crate::service::{page_results, write_results};
+use crate::domain::{
+    BenchmarkNumRuns, BenchmarkSummary, CellOverflow, DisplayOptions, ExpandedMode,
+    NonEmptyResults, OutputFormat, Pager, PlanMode, QueryParam, QueryParams, QueryResults,
+    ResultsFormat, Schema, WriteOptions, parse_display_limit, split_plan_prefix, split_statements,
+};
+use crate::error::follow_up_db_client_error;
+use crate::repository::{Connect, ConnectionTarget, DbClientError, QueryExecutor};
+use crate::service::{
+    page_results, print_benchmark_stats, write_benchmark_report, write_results, write_results_to,
+};
 use anyhow::Context;
 use chrono::Utc;
 use colored::Colorize;
 use rustyline::error::ReadlineError;
+use rustyline::{
+    Cmd, ConditionalEventHandler, Event, EventContext, EventHandler, KeyEvent, RepeatCount,
+};
+use std::collections::HashMap;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 use tokio::time::Instant;
 
@@ -17,20 +30,78 @@ const BANNER: &str = include_str!("assets/logo.txt");
 const COMMANDS: &str = include_str!("assets/commands.txt");
 const KEYMAPS: &str = include_str!("assets/keymaps.txt");
 const CTRL_C_QUIT_THRESHOLD_MILLIS: u64 = 1000;
+const DEFAULT_BENCH_NUM_WARMUP_RUNS: u16 = 3;
+const PARAM_USAGE: &str = "usage: :param <KEY=VALUE> | :param unset <KEY>";
+const BENCH_USAGE: &str =
+    "Usage: bench [-n <RUNS>] [-W <WARMUP_RUNS>] <query/@path> | bench last | bench export <FILE>";
 
 pub struct ConsoleConfig {
     pub page_results: bool,
     pub write_results: bool,
     pub results_directory: PathBuf,
     pub history_file_path: PathBuf,
+    pub config_file_path: PathBuf,
     pub results_format: ResultsFormat,
+    pub write_options: WriteOptions,
+    pub output_format: OutputFormat,
+    pub display: DisplayOptions,
 }
 
-pub struct Console<D: QueryExecutor> {
+pub struct Console<D: QueryExecutor + Connect> {
     db_client: D,
+    profile_name: Option<String>,
     config: ConsoleConfig,
+    params: QueryParams,
+    /// Schemas of the databases connected to in this session, by db uri.
+    schemas: HashMap<String, Schema>,
     pager: Option<Pager>,
     last_ctrl_c: Option<Instant>,
+    /// The last benchmark run in this session, so that it can be re-displayed or exported.
+    last_benchmark: Option<BenchmarkSummary>,
+}
+
+/// What the console's `bench` command was asked to do.
+#[derive(Debug)]
+enum BenchCmd {
+    Run {
+        num_runs: BenchmarkNumRuns,
+        num_warmup_runs: u16,
+        query: String,
+    },
+    Last,
+    Export(PathBuf),
+}
+
+/// What the console's `:param` command was asked to do.
+#[derive(Debug)]
+enum ParamCmd {
+    Set(QueryParam),
+    Unset(String),
+}
+
+/// What the console's `page` command was asked to do.
+#[derive(Debug, Clone, Copy)]
+enum PageCmd {
+    On,
+    Builtin,
+    Off,
+}
+
+/// Records whether ctrl+c was pressed with input typed (which is then discarded), before letting
+/// rustyline handle it as usual.
+struct InterruptHandler(Arc<AtomicBool>);
+
+impl ConditionalEventHandler for InterruptHandler {
+    fn handle(
+        &self,
+        _evt: &Event,
+        _n: RepeatCount,
+        _positive: bool,
+        ctx: &EventContext,
+    ) -> Option<Cmd> {
+        self.0.store(!ctx.line().is_empty(), Ordering::Relaxed);
+        None
+    }
 }
 
 #[allow(unused)]
@@ -40,36 +111,67 @@ enum ConsoleColor {
     Green,
 }
 
-impl<D: QueryExecutor> Console<D> {
-    pub fn new(db_client: D, config: ConsoleConfig, pager: Option<Pager>) -> Self {
+impl<D: QueryExecutor + Connect> Console<D> {
+    pub fn new(
+        db_client: D,
+        config: ConsoleConfig,
+        pager: Option<Pager>,
+        profile_name: Option<String>,
+    ) -> Self {
         Self {
             db_client,
+            profile_name,
             config,
+            params: QueryParams::default(),
+            schemas: HashMap::new(),
             pager,
             last_ctrl_c: None,
+            last_benchmark: None,
         }
     }
 
     pub async fn run_loop(&mut self) -> anyhow::Result<()> {
-        print_banner(std::io::stdout(), true);
+        let color = colored::control::SHOULD_COLORIZE.should_colorize();
+
+        print_banner(std::io::stdout(), color);
         print_help(
             std::io::stdout(),
             &self.db_client.db_uri(),
+            self.profile_name.as_deref(),
             &self.config,
-            true,
+            color,
         );
 
         let mut editor = rustyline::Editor::new()?;
-        editor.set_helper(Some(QueryFilenameCompleter::default()));
+        editor.set_helper(Some(QueryFilenameCompleter::new(color)));
         let _ = editor.load_history(&self.config.history_file_path);
 
+        if let Some(helper) = editor.helper_mut() {
+            helper.set_schema(self.get_schema(false).await);
+        }
+
+        // set when ctrl+c is pressed with input typed, so that clearing it isn't taken as a first
+        // press towards exiting
+        let input_cleared = Arc::new(AtomicBool::new(false));
+        editor.bind_sequence(
+            KeyEvent::ctrl('C'),
+            EventHandler::Conditional(Box::new(InterruptHandler(Arc::clone(&input_cleared)))),
+        );
+
         loop {
-            let user_input = match editor.readline(">> ") {
+            let prompt = get_prompt(&self.db_client.db_uri(), self.profile_name.as_deref());
+
+            let user_input = match editor.readline(&prompt) {
                 Ok(input) => {
                     self.last_ctrl_c = None;
                     input
                 }
                 Err(ReadlineError::Interrupted) => {
+                    if input_cleared.swap(false, Ordering::Relaxed) {
+                        print_hint("input cleared");
+                        self.last_ctrl_c = None;
+                        continue;
+                    }
                     if let Some(last_time) = self.last_ctrl_c
                         && last_time.elapsed() < Duration::from_millis(CTRL_C_QUIT_THRESHOLD_MILLIS)
                     {
@@ -101,33 +203,53 @@ impl<D: QueryExecutor> Console<D> {
                     print_help(
                         std::io::stdout(),
                         &self.db_client.db_uri(),
+                        self.profile_name.as_deref(),
                         &self.config,
-                        true,
+                        color,
                     );
                 }
-                cmd if cmd.starts_with("page") => match cmd.split_once(" ") {
-                    Some((_, "on")) => {
-                        if self.pager.is_none() {
-                            match crate::utils::get_pager() {
-                                Ok(p) => {
-                                    self.pager = Some(p);
-                                    self.config.page_results = true;
-                                    print_info("paging results turned ON");
-                                }
-                                Err(e) => {
-                                    print_error(format!("Error: couldn't turn on pager: {:#}", e));
-                                }
-                            }
-                        } else if !self.config.page_results {
-                            self.config.page_results = true;
-                            print_info("paging results turned ON");
+                cmd if is_use_cmd(cmd) => match cmd.split_once(" ") {
+                    Some((_, target)) => {
+                        if self
+                            .switch_connection(ConnectionTarget::from(target.trim()))
+                            .await
+                            && let Some(helper) = editor.helper_mut()
+                        {
+                            helper.set_schema(self.get_schema(false).await);
                         }
                     }
-                    Some((_, "off")) => {
-                        self.config.page_results = false;
-                        print_info("paging results turned OFF");
+                    None => print_error("Usage: use <PROFILE/URI>"),
+                },
+                cmd if cmd.starts_with("schema") => match cmd.split_once(" ") {
+                    None => print_schema(&self.get_schema(false).await),
+                    Some((_, "refresh")) => {
+                        let schema = self.get_schema(true).await;
+                        print_schema(&schema);
+                        if let Some(helper) = editor.helper_mut() {
+                            helper.set_schema(schema);
+                        }
                     }
-                    _ => print_error("Usage: page on/off"),
+                    _ => print_error("Usage: schema [refresh]"),
+                },
+                cmd if cmd.starts_with("page") => match parse_page_cmd(cmd) {
+                    Ok(page_cmd) => match switch_pager(
+                        page_cmd,
+                        &mut self.pager,
+                        &mut self.config.page_results,
+                        crate::utils::get_pager,
+                    ) {
+                        Ok(()) => print_info(match page_cmd {
+                            PageCmd::On => "paging results turned ON",
+                            PageCmd::Builtin => {
+                                "paging results turned ON (via the built-in browser)"
+                            }
+                            PageCmd::Off => "paging results turned OFF",
+                        }),
+                        Err(e) => {
+                            print_error(format!("Error: couldn't turn on pager: {:#}", e));
+                        }
+                    },
+                    Err(e) => print_error(e),
                 },
                 cmd if cmd.starts_with("format") => match cmd.split_once(" ") {
                     Some((_, arg)) => match ResultsFormat::from_str(arg) {
@@ -140,9 +262,71 @@ impl<D: QueryExecutor> Console<D> {
                         }
                     },
                     None => {
-                        print_error("Usage: format <csv/json>");
+                        print_error(
+                            "Usage: format <csv/json/ndjson/tsv/parquet/arrow/markdown/html>",
+                        );
                     }
                 },
+                cmd if cmd.starts_with("output") => match cmd.split_once(" ") {
+                    Some((_, arg)) => match OutputFormat::from_str(arg) {
+                        Ok(f) if f.results_format().is_some_and(|r| r.is_binary()) => {
+                            print_error(format!("{f} results cannot be printed to the console"));
+                        }
+                        Ok(f) => {
+                            print_info(format!("output format set to: {}", &f));
+                            self.config.output_format = f;
+                        }
+                        Err(e) => {
+                            print_error(e);
+                        }
+                    },
+                    None => {
+                        print_error("Usage: output <table/csv/json/ndjson/tsv/markdown/html>");
+                    }
+                },
+                cmd if cmd.starts_with("expanded") => match cmd.split_once(" ") {
+                    Some((_, arg)) => match ExpandedMode::from_str(arg) {
+                        Ok(mode) => {
+                            print_info(format!("expanded display set to: {mode}"));
+                            self.config.display.expanded = mode;
+                        }
+                        Err(e) => print_error(e),
+                    },
+                    None => print_error("Usage: expanded on/off/auto"),
+                },
+                cmd if cmd.starts_with("width") => match cmd.split_once(" ") {
+                    Some((_, arg)) => match parse_display_limit(arg, "off") {
+                        Ok(width) => {
+                            print_info(format!(
+                                "max column width set to: {}",
+                                display_limit(width, "off")
+                            ));
+                            self.config.display.max_column_width = width;
+                        }
+                        Err(e) => print_error(e),
+                    },
+                    None => print_error("Usage: width <NUMBER/off>"),
+                },
+                cmd if cmd.starts_with("overflow") => match cmd.split_once(" ") {
+                    Some((_, arg)) => match CellOverflow::from_str(arg) {
+                        Ok(overflow) => {
+                            print_info(format!("overflow set to: {overflow}"));
+                            self.config.display.overflow = overflow;
+                        }
+                        Err(e) => print_error(e),
+                    },
+                    None => print_error("Usage: overflow truncate/wrap"),
+                },
+                cmd if cmd.starts_with("rows") => match cmd.split_once(" ") {
+                    Some((_, arg)) => match parse_display_limit(arg, "all") {
+                        Ok(rows) => {
+                            print_info(format!("max rows set to: {}", display_limit(rows, "all")));
+                            self.config.display.max_rows = rows;
+                        }
+                        Err(e) => print_error(e),
+                    },
+                    None => print_error("Usage: rows <NUMBER/all>"),
+                },
                 cmd if cmd.starts_with("dir") => match cmd.split_once(" ") {
                     Some((_, "reset")) => {
                         self.config.results_directory = PathBuf::new().join(DEFAULT_RESULTS_DIR);
@@ -173,110 +357,502 @@ impl<D: QueryExecutor> Console<D> {
                     }
                     _ => print_error("Usage: write on/off"),
                 },
+                cmd if cmd.starts_with("bench") => match parse_bench_cmd(cmd) {
+                    Ok(BenchCmd::Run {
+                        num_runs,
+                        num_warmup_runs,
+                        query,
+                    }) => {
+                        if let Err(e) = editor.add_history_entry(cmd) {
+                            println!("Error: {e}");
+                        }
+                        match get_query_from_user_input(&query) {
+                            Ok(query) => {
+                                self.benchmark_query(&query, num_runs, num_warmup_runs)
+                                    .await;
+                            }
+                            Err(e) => print_error(format!("Error: {:#}", e)),
+                        }
+                    }
+                    Ok(BenchCmd::Last) => match &self.last_benchmark {
+                        Some(summary) => {
+                            print_info(format!("benchmark of: {}", summary.report.query));
+                            print_benchmark_stats(&summary.stats);
+                            println!();
+                        }
+                        None => print_error("no benchmark has been run in this session yet"),
+                    },
+                    Ok(BenchCmd::Export(path)) => match &self.last_benchmark {
+                        Some(summary) => match write_benchmark_report(&summary.report, &path) {
+                            Ok(()) => print_info(format!(
+                                "wrote benchmark report to {}",
+                                path.to_string_lossy()
+                            )),
+                            Err(e) => print_error(format!(
+                                "Error: couldn't write benchmark report: {:#}",
+                                e
+                            )),
+                        },
+                        None => print_error("no benchmark has been run in this session yet"),
+                    },
+                    Err(e) => print_error(e),
+                },
+                cmd if cmd.starts_with(":params") => match cmd.split_once(" ") {
+                    None => print_params(&self.params),
+                    Some((_, "clear")) => {
+                        self.params.clear();
+                        print_info("query params cleared");
+                    }
+                    Some((_, json)) => match QueryParams::from_json(json) {
+                        Ok(params) => {
+                            self.params = params;
+                            print_params(&self.params);
+                        }
+                        Err(e) => print_error(format!("Error: {:#}", e)),
+                    },
+                },
+                cmd if cmd.starts_with(":param") => match cmd.split_once(" ") {
+                    Some((_, arg)) => match parse_param_cmd(arg.trim()) {
+                        Ok(ParamCmd::Set(param)) => {
+                            print_info(format!("query param set: ${param}"));
+                            self.params.set(param);
+                        }
+                        Ok(ParamCmd::Unset(key)) => match self.params.unset(&key) {
+                            Some(_) => print_info(format!("query param ${key} unset")),
+                            None => print_error(format!("Error: query param ${key} is not set")),
+                        },
+                        Err(e) => print_error(e),
+                    },
+                    None => print_error("Usage: :param <KEY=VALUE> | :param unset <KEY>"),
+                },
                 user_input => {
                     if let Err(e) = editor.add_history_entry(user_input) {
                         println!("Error: {e}");
                     }
 
-                    let query_to_execute = match get_query_from_user_input(user_input) {
-                        Ok(q) => q,
-                        Err(e) => {
-                            print_error(format!("Error: {:#}", e));
-                            continue;
+                    let queries = if user_input.starts_with('@') {
+                        match get_query_from_user_input(user_input) {
+                            Ok(q) => vec![q],
+                            Err(e) => {
+                                print_error(format!("Error: {:#}", e));
+                                continue;
+                            }
                         }
+                    } else {
+                        split_statements(user_input)
                     };
 
-                    let start = Instant::now();
-
-                    let results = tokio::select! {
-                        res = self.db_client.execute_query(&query_to_execute) => res,
-                        Ok(_) = tokio::signal::ctrl_c() => {
-                            print_hint("\nquery cancelled");
-                            continue;
+                    for (i, query) in queries.iter().enumerate() {
+                        let succeeded = match split_plan_prefix(query) {
+                            Some((mode, query)) => self.explain_query(query, mode).await,
+                            None => self.execute_query(query).await?,
+                        };
+                        if !succeeded {
+                            let num_skipped = queries.len() - i - 1;
+                            if num_skipped > 0 {
+                                print_hint(format!(
+                                    "skipped the remaining {num_skipped} statement(s)"
+                                ));
+                            }
+                            break;
                         }
-                    };
-                    print_time(Instant::now().saturating_duration_since(start));
+                    }
+                }
+            }
+        }
+
+        let _ = editor.save_history(&self.config.history_file_path);
+
+        Ok(())
+    }
+
+    /// Executes a query and displays (or pages/writes) its results; returns whether the results
+    /// were fetched, ie. `false` if the query failed or was cancelled.
+    async fn execute_query(&self, query: &str) -> anyhow::Result<bool> {
+        let start = Instant::now();
+
+        let results = tokio::select! {
+            res = self.db_client.execute_query(query, &self.params) => res,
+            Ok(_) = tokio::signal::ctrl_c() => {
+                print_hint("\nquery cancelled");
+                return Ok(false);
+            }
+        };
+        print_time(Instant::now().saturating_duration_since(start));
 
-                    match results {
-                        Ok(QueryResults::Empty) => {
-                            println!("\nNo results\n");
+        match results {
+            Ok(QueryResults::Empty) => {
+                println!("\nNo results\n");
+            }
+            Ok(QueryResults::NonEmpty(results)) => {
+                if self.config.write_results {
+                    match write_results(
+                        &results,
+                        &self.config.results_directory,
+                        &self.config.results_format,
+                        self.config.write_options,
+                        Utc::now(),
+                    ) {
+                        Ok(p) => {
+                            print_info(format!("wrote results to {}", p.to_string_lossy()));
+                            self.page_results(&results, Some(&p));
                         }
-                        Ok(QueryResults::NonEmpty(results)) => {
-                            if self.config.write_results {
-                                match write_results(
-                                    &results,
-                                    &self.config.results_directory,
-                                    &self.config.results_format,
-                                    Utc::now(),
-                                ) {
-                                    Ok(p) => {
-                                        print_info(format!(
-                                            "wrote results to {}",
-                                            p.to_string_lossy()
-                                        ));
-
-                                        if self.config.page_results
-                                            && let Some(pager) = &self.pager
-                                            && let Err(e) = page_results(&p, pager)
-                                        {
-                                            print_error(format!(
-                                                "Error: couldn't display results via pager: {:#}",
-                                                e
-                                            ));
-                                        }
-                                    }
-                                    Err(e) => {
-                                        print_error(format!(
-                                            "Error: couldn't write results: {:#}",
-                                            e
-                                        ));
-                                    }
+                        Err(e) => {
+                            print_error(format!("Error: couldn't write results: {:#}", e));
+                        }
+                    }
+                } else if self.config.page_results && self.pager.is_some() {
+                    self.page_results(&results, None);
+                } else {
+                    match self.config.output_format.results_format() {
+                        None => println!(
+                            "\n{}\n",
+                            get_results_for_display(
+                                &results,
+                                &self.config.display,
+                                crate::utils::terminal_width()
+                            )
+                        ),
+                        Some(format) => {
+                            let mut buffer = Vec::new();
+                            match write_results_to(
+                                &results,
+                                &format,
+                                self.config.write_options,
+                                &mut buffer,
+                            ) {
+                                Ok(_) => {
+                                    println!("\n{}\n", String::from_utf8_lossy(&buffer).trim_end())
                                 }
-                            } else if self.config.page_results
-                                && let Some(pager) = &self.pager
-                            {
-                                let temp_results_directory = tempfile::tempdir().context(
-                                    "couldn't create temporary directory for paging results",
-                                )?;
-
-                                match write_results(
-                                    &results,
-                                    &temp_results_directory,
-                                    &self.config.results_format,
-                                    Utc::now(),
-                                ) {
-                                    Ok(p) => {
-                                        if let Err(e) = page_results(&p, pager) {
-                                            print_error(format!(
-                                                "Error: couldn't display results via pager: {:#}",
-                                                e
-                                            ));
-                                        }
-                                    }
-                                    Err(e) => {
-                                        print_error(format!(
-                                            "Error: couldn't write results to temporary directory: {:#}",
-                                            e
-                                        ));
-                                    }
+                                Err(e) => {
+                                    print_error(format!("Error: couldn't print results: {:#}", e))
                                 }
-                            } else {
-                                let results_str = get_results(&results);
-                                println!("\n{}\n", results_str);
                             }
                         }
-                        Err(e) => print_error(format!("Error: couldn't get results: {:#}", e)),
                     }
                 }
             }
+            Err(e) => {
+                print_error(format!("Error: couldn't get results: {:#}", e));
+                return Ok(false);
+            }
         }
 
-        let _ = editor.save_history(&self.config.history_file_path);
+        Ok(true)
+    }
 
-        Ok(())
+    /// Fetches and displays a query's plan (writing it to the filesystem as well, if writing
+    /// results is turned on); returns whether the plan was fetched.
+    async fn explain_query(&self, query: &str, mode: PlanMode) -> bool {
+        let start = Instant::now();
+
+        let plan = tokio::select! {
+            res = self.db_client.explain_query(query, &self.params, mode) => res,
+            Ok(_) = tokio::signal::ctrl_c() => {
+                print_hint("\nquery cancelled");
+                return false;
+            }
+        };
+        print_time(Instant::now().saturating_duration_since(start));
+
+        let plan = match plan {
+            Ok(p) => p,
+            Err(e) => {
+                print_error(format!("Error: couldn't get query plan: {:#}", e));
+                return false;
+            }
+        };
+
+        println!("\n{}\n", get_plan(&plan));
+
+        if self.config.write_results
+            && let QueryResults::NonEmpty(results) = plan.to_results()
+        {
+            match write_results(
+                &results,
+                &self.config.results_directory,
+                &self.config.results_format,
+                self.config.write_options,
+                Utc::now(),
+            ) {
+                Ok(p) => print_info(format!("wrote query plan to {}", p.to_string_lossy())),
+                Err(e) => print_error(format!("Error: couldn't write query plan: {:#}", e)),
+            }
+        }
+
+        true
+    }
+
+    /// Benchmarks a query (pressing ctrl+c cancels the benchmark), and holds on to its summary.
+    async fn benchmark_query(
+        &mut self,
+        query: &str,
+        num_runs: BenchmarkNumRuns,
+        num_warmup_runs: u16,
+    ) {
+        let summary = tokio::select! {
+            res = crate::service::benchmark_query(&self.db_client, query, &self.params, None, num_runs, num_warmup_runs) => res,
+            Ok(_) = tokio::signal::ctrl_c() => {
+                print_hint("\nbenchmark cancelled");
+                return;
+            }
+        };
+
+        match summary {
+            Ok(Some(summary)) => {
+                println!();
+                print_hint(
+                    "use \"bench last\" to show this summary again, or \"bench export <FILE>\" to save it",
+                );
+                self.last_benchmark = Some(summary);
+            }
+            Ok(None) => {}
+            Err(e) => print_error(format!("Error: couldn't benchmark query: {:#}", e)),
+        }
+    }
+
+    /// Displays results via the pager, if paging is turned on; `results_file` is the file results
+    /// have been written to, if they have been.
+    fn page_results(&self, results: &NonEmptyResults, results_file: Option<&Path>) {
+        if !self.config.page_results {
+            return;
+        }
+        let Some(pager) = &self.pager else {
+            return;
+        };
+
+        if let Err(e) = page_results(
+            results,
+            results_file,
+            pager,
+            self.config.output_format,
+            &self.config.results_format,
+            self.config.write_options,
+            &self.config.display,
+        ) {
+            print_error(format!(
+                "Error: couldn't display results via pager: {:#}",
+                e
+            ));
+        }
+    }
+
+    /// Returns the schema of the connected database (used for completions), fetching it if it
+    /// hasn't been fetched for this connection yet, or if `refresh` is set. An empty schema is
+    /// returned if it can't be fetched.
+    async fn get_schema(&mut self, refresh: bool) -> Schema {
+        let db_uri = self.db_client.db_uri();
+        if !refresh && let Some(schema) = self.schemas.get(&db_uri) {
+            return schema.clone();
+        }
+
+        let result = tokio::select! {
+            res = self.db_client.fetch_schema() => res,
+            Ok(_) = tokio::signal::ctrl_c() => {
+                print_hint("\nfetching schema cancelled");
+                return Schema::default();
+            }
+        };
+
+        match result {
+            Ok(schema) => {
+                self.schemas.insert(db_uri, schema.clone());
+                schema
+            }
+            Err(e) => {
+                print_error(format!("Error: couldn't fetch schema: {:#}", e));
+                print_hint("labels, relationship types and property keys won't be completed");
+                Schema::default()
+            }
+        }
+    }
+
+    /// Replaces the current db client with one built for `target`; the current client is kept
+    /// if the new one can't be built or its connectivity can't be verified. Returns whether the
+    /// connection was switched.
+    async fn switch_connection(&mut self, target: ConnectionTarget) -> bool {
+        let result = tokio::select! {
+            res = connect_and_verify::<D>(&target, &self.config.config_file_path) => res,
+            Ok(_) = tokio::signal::ctrl_c() => {
+                print_hint("\nswitching connection cancelled");
+                return false;
+            }
+        };
+
+        match result {
+            Ok(db_client) => {
+                self.db_client = db_client;
+                self.profile_name = match target {
+                    ConnectionTarget::Profile(name) => Some(name),
+                    ConnectionTarget::Uri(_) => None,
+                };
+                print_info(format!("connected to {}", self.db_client.db_uri()));
+                true
+            }
+            Err(e) => {
+                let follow_up = follow_up_db_client_error(&e);
+                print_error(format!(
+                    "Error: couldn't switch connection: {:#}",
+                    anyhow::anyhow!(e)
+                ));
+                if let Some(f) = follow_up {
+                    print_hint(f);
+                }
+                false
+            }
+        }
+    }
+}
+
+async fn connect_and_verify<D: QueryExecutor + Connect>(
+    target: &ConnectionTarget,
+    config_file_path: &Path,
+) -> Result<D, DbClientError> {
+    let db_client = D::connect(target, config_file_path).await?;
+    db_client.verify_connectivity().await?;
+
+    Ok(db_client)
+}
+
+/// Only treats input as the `use` command if it has at most one argument, so that queries
+/// starting with Cypher's `USE` clause (eg. `USE neo4j MATCH ...`) are still executed.
+fn is_use_cmd(input: &str) -> bool {
+    match input.split_once(" ") {
+        Some(("use", arg)) => !arg.trim().contains(char::is_whitespace),
+        Some(_) => false,
+        None => input == "use",
+    }
+}
+
+/// Whether input is one of the console's own commands (as opposed to a query); these are run as
+/// soon as they're entered, whereas queries are read until they're terminated with a `;`.
+pub(super) fn is_console_cmd(input: &str) -> bool {
+    matches!(
+        input,
+        "" | "bye" | "exit" | "quit" | ":q" | "clear" | "help" | ":h"
+    ) || input.starts_with('@')
+        || is_use_cmd(input)
+        || [
+            "page", "format", "output", "expanded", "width", "overflow", "rows", "dir", "write",
+            "schema", "bench", ":param",
+        ]
+        .iter()
+        .any(|cmd| input.starts_with(cmd))
+}
+
+fn display_limit(limit: Option<usize>, unset: &str) -> String {
+    limit.map_or(unset.to_string(), |l| l.to_string())
+}
+
+/// Parses the `bench` command: `bench [-n <RUNS>] [-W <WARMUP_RUNS>] <query/@path>`, `bench last`
+/// or `bench export <FILE>`. The query has to be on a single line; a trailing `;` is optional.
+fn parse_bench_cmd(input: &str) -> Result<BenchCmd, String> {
+    let args = input.strip_prefix("bench").unwrap_or(input).trim();
+
+    match args.split_once(char::is_whitespace) {
+        None if args == "last" => return Ok(BenchCmd::Last),
+        Some(("export", path)) if !path.trim().is_empty() => {
+            return Ok(BenchCmd::Export(PathBuf::from(path.trim())));
+        }
+        None if args == "export" => return Err(BENCH_USAGE.to_string()),
+        _ => {}
+    }
+
+    let mut num_runs = BenchmarkNumRuns::default();
+    let mut num_warmup_runs = DEFAULT_BENCH_NUM_WARMUP_RUNS;
+    let mut rest = args;
+    while let Some((flag @ ("-n" | "-W"), after)) = rest.split_once(char::is_whitespace) {
+        let after = after.trim_start();
+        let (value, after) = after.split_once(char::is_whitespace).unwrap_or((after, ""));
+        if flag == "-n" {
+            num_runs = BenchmarkNumRuns::from_str(value)
+                .map_err(|e| format!(r#"Error: invalid value "{value}" for -n: {e}"#))?;
+        } else {
+            num_warmup_runs = value.parse().map_err(|_| {
+                format!(r#"Error: invalid value "{value}" for -W: value is not a valid number"#)
+            })?;
+        }
+        rest = after.trim_start();
+    }
+
+    let query = rest.trim().trim_end_matches(';').trim();
+    if query.is_empty() {
+        return Err(BENCH_USAGE.to_string());
+    }
+
+    Ok(BenchCmd::Run {
+        num_runs,
+        num_warmup_runs,
+        query: query.to_string(),
+    })
+}
+
+/// Parses the arguments of the `:param` command: `unset <KEY>` unsets a param, and anything else
+/// is a `key=value` param to set (the same as `--param`, so `key=` sets it to an empty string).
+fn parse_param_cmd(input: &str) -> Result<ParamCmd, String> {
+    if let Some(("unset", key)) = input.split_once(char::is_whitespace) {
+        let key = key.trim().trim_start_matches('$');
+        if !key.is_empty() && !key.contains('=') {
+            return Ok(ParamCmd::Unset(key.to_string()));
+        }
+    }
+
+    QueryParam::from_str(input)
+        .map(ParamCmd::Set)
+        .map_err(|e| format!("Error: {e}; {PARAM_USAGE}"))
+}
+
+fn parse_page_cmd(input: &str) -> Result<PageCmd, String> {
+    match input.strip_prefix("page").unwrap_or(input).trim() {
+        "on" => Ok(PageCmd::On),
+        "builtin" => Ok(PageCmd::Builtin),
+        "off" => Ok(PageCmd::Off),
+        _ => Err("Usage: page on/off/builtin".to_string()),
     }
 }
 
+/// Applies a `page` command to the pager results are paged via, and to whether they're paged.
+///
+/// `page on` sets up the configured pager (see `get_pager`), unless a pager other than the
+/// built-in browser (which `page builtin` switches to) is already in use.
+fn switch_pager<F>(
+    cmd: PageCmd,
+    pager: &mut Option<Pager>,
+    page_results: &mut bool,
+    get_configured_pager: F,
+) -> anyhow::Result<()>
+where
+    F: FnOnce() -> anyhow::Result<Pager>,
+{
+    match cmd {
+        PageCmd::On => {
+            if pager.as_ref().is_none_or(Pager::is_builtin) {
+                *pager = Some(get_configured_pager()?);
+            }
+            *page_results = true;
+        }
+        PageCmd::Builtin => {
+            *pager = Some(Pager::builtin());
+            *page_results = true;
+        }
+        PageCmd::Off => *page_results = false,
+    }
+
+    Ok(())
+}
+
+fn get_prompt(db_uri: &str, profile_name: Option<&str>) -> String {
+    let label = match profile_name {
+        Some(name) => name,
+        None => db_uri
+            .split_once("://")
+            .map(|(_, address)| address)
+            .unwrap_or(db_uri),
+    };
+
+    format!("{label} >> ")
+}
+
 fn print_error<S: AsRef<str>>(contents: S) {
     println!("{}", contents.as_ref().red());
 }
@@ -293,6 +869,27 @@ fn print_hint<S: AsRef<str>>(contents: S) {
     println!("{}", contents.as_ref().yellow());
 }
 
+fn print_params(params: &QueryParams) {
+    if params.is_empty() {
+        print_info("no query params set");
+        return;
+    }
+
+    for (key, value) in params.iter() {
+        print_info(format!("${key} = {value}"));
+    }
+}
+
+fn print_schema(schema: &Schema) {
+    for (name, items) in [
+        ("labels", &schema.labels),
+        ("relationship types", &schema.relationship_types),
+        ("property keys", &schema.property_keys),
+    ] {
+        print_info(format!("{name} ({}): {}", items.len(), items.join(", ")));
+    }
+}
+
 fn print_banner(mut writer: impl Write, color: bool) {
     if color {
         let _ = writeln!(writer, "{}\n", BANNER.blue());
@@ -301,22 +898,42 @@ fn print_banner(mut writer: impl Write, color: bool) {
     }
 }
 
-fn print_help(mut writer: impl Write, db_uri: &str, config: &ConsoleConfig, color: bool) {
+fn print_help(
+    mut writer: impl Write,
+    db_uri: &str,
+    profile_name: Option<&str>,
+    config: &ConsoleConfig,
+    color: bool,
+) {
+    let profile_info = profile_name
+        .map(|p| format!(" (profile: {p})"))
+        .unwrap_or_default();
+
     let config_help = format!(
         " config
    page results                            {}
    write results to filesystem             {}
    results format                          {}
+   output format                           {}
+   expanded display                        {}
+   max column width                        {}
+   overflow                                {}
+   max rows                                {}
    results directory                       {}",
         if config.page_results { "ON" } else { "OFF" },
         if config.write_results { "ON" } else { "OFF" },
         config.results_format,
+        config.output_format,
+        config.display.expanded,
+        display_limit(config.display.max_column_width, "off"),
+        config.display.overflow,
+        display_limit(config.display.max_rows, "all"),
         config.results_directory.to_string_lossy(),
     );
 
     let help = if color {
         format!(
-            r#" connected to: {}
+            r#" connected to: {}{}
 
 {}
 
@@ -324,20 +941,21 @@ fn print_help(mut writer: impl Write, db_uri: &str, config: &ConsoleConfig, colo
 {}
 "#,
             db_uri.cyan(),
+            profile_info.cyan(),
             config_help.blue(),
             COMMANDS.yellow(),
             KEYMAPS.green()
         )
     } else {
         format!(
-            r#" connected to: {}
+            r#" connected to: {}{}
 
 {}
 
 {}
 {}
 "#,
-            db_uri, config_help, COMMANDS, KEYMAPS,
+            db_uri, profile_info, config_help, COMMANDS, KEYMAPS,
         )
     };
 
@@ -367,7 +985,7 @@ fn get_query_from_user_input(contents: &str) -> anyhow::Result<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use insta::assert_snapshot;
+    use insta::{assert_debug_snapshot, assert_snapshot};
 
     const QUERY_FILE_PATH: &str = "src/view/testdata/query.cypher";
     const QUERY_WITH_WHITESPACE_FILE_PATH: &str = "src/view/testdata/query-with-whitespace.cypher";
@@ -384,9 +1002,13 @@ mod tests {
         let console_config = ConsoleConfig {
             page_results: false,
             results_format: ResultsFormat::Csv,
+            write_options: WriteOptions::default(),
+            output_format: OutputFormat::Table,
+            display: DisplayOptions::default(),
             results_directory: PathBuf::new().join(DEFAULT_RESULTS_DIR),
             write_results: false,
             history_file_path: PathBuf::new(),
+            config_file_path: PathBuf::new(),
         };
 
         // WHEN
@@ -394,6 +1016,7 @@ mod tests {
         print_help(
             &mut buf,
             "https://db.cluster-cf0abc1xyzjk.us-east-1.neptune.amazonaws.com:8182",
+            None,
             &console_config,
             false,
         );
@@ -473,10 +1096,347 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn help_shows_profile_name_when_connected_via_a_profile() {
+        // GIVEN
+        let mut buf = Vec::new();
+        let console_config = ConsoleConfig {
+            page_results: false,
+            results_format: ResultsFormat::Csv,
+            write_options: WriteOptions::default(),
+            output_format: OutputFormat::Table,
+            display: DisplayOptions::default(),
+            results_directory: PathBuf::new().join(DEFAULT_RESULTS_DIR),
+            write_results: false,
+            history_file_path: PathBuf::new(),
+            config_file_path: PathBuf::new(),
+        };
+
+        // WHEN
+        print_help(
+            &mut buf,
+            "bolt://127.0.0.1:7687",
+            Some("dev"),
+            &console_config,
+            false,
+        );
+
+        // THEN
+        let result = String::from_utf8(buf).expect("string should've been built");
+        let first_line = result.lines().next().expect("help should've had a line");
+        assert_snapshot!(first_line, @" connected to: bolt://127.0.0.1:7687 (profile: dev)");
+    }
+
+    #[test]
+    fn get_prompt_uses_profile_name_if_available() {
+        // GIVEN
+        // WHEN
+        let result = get_prompt("bolt://127.0.0.1:7687", Some("dev"));
+
+        // THEN
+        assert_snapshot!(result, @"dev >> ");
+    }
+
+    #[test]
+    fn get_prompt_falls_back_to_db_address() {
+        // GIVEN
+        // WHEN
+        let result = get_prompt("bolt://127.0.0.1:7687", None);
+
+        // THEN
+        assert_snapshot!(result, @"127.0.0.1:7687 >> ");
+    }
+
+    #[test]
+    fn is_console_cmd_detects_console_commands() {
+        // GIVEN
+        let inputs = [
+            "",
+            "help",
+            ":q",
+            "page on",
+            "format csv",
+            "output markdown",
+            "expanded auto",
+            "width 40",
+            "overflow wrap",
+            "rows all",
+            "use dev",
+            ":param name=Rust",
+            ":params clear",
+            "schema refresh",
+            "bench -n 10 RETURN 1",
+            "@query.cypher",
+        ];
+
+        // WHEN
+        // THEN
+        for input in inputs {
+            assert!(is_console_cmd(input), "{input} should've been a command");
+        }
+    }
+
+    #[test]
+    fn is_console_cmd_treats_everything_else_as_queries() {
+        // GIVEN
+        let inputs = [
+            "MATCH (n) RETURN n",
+            "USE neo4j MATCH (n) RETURN n",
+            "RETURN 1;",
+            "helpful",
+        ];
+
+        // WHEN
+        // THEN
+        for input in inputs {
+            assert!(
+                !is_console_cmd(input),
+                "{input} shouldn't have been a command"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_bench_cmd_parses_flags_and_subcommands() {
+        // GIVEN
+        let inputs = [
+            "bench MATCH (n) RETURN n LIMIT 1;",
+            "bench -n 20 -W 0 @queries/languages.cypher",
+            "bench last",
+            "bench export reports/languages.json",
+        ];
+
+        // WHEN
+        let result: Vec<_> = inputs.iter().map(|i| parse_bench_cmd(i)).collect();
+
+        // THEN
+        assert_debug_snapshot!(result, @r#"
+        [
+            Ok(
+                Run {
+                    num_runs: BenchmarkNumRuns(
+                        5,
+                    ),
+                    num_warmup_runs: 3,
+                    query: "MATCH (n) RETURN n LIMIT 1",
+                },
+            ),
+            Ok(
+                Run {
+                    num_runs: BenchmarkNumRuns(
+                        20,
+                    ),
+                    num_warmup_runs: 0,
+                    query: "@queries/languages.cypher",
+                },
+            ),
+            Ok(
+                Last,
+            ),
+            Ok(
+                Export(
+                    "reports/languages.json",
+                ),
+            ),
+        ]
+        "#);
+    }
+
+    #[test]
+    fn parse_param_cmd_sets_and_unsets_params() {
+        // GIVEN
+        let inputs = [
+            "name=Rust",
+            "name=",
+            "$year = 2010",
+            "unset name",
+            "unset $year",
+        ];
+
+        // WHEN
+        let result: Vec<_> = inputs.iter().map(|i| parse_param_cmd(i)).collect();
+
+        // THEN
+        assert_debug_snapshot!(result, @r#"
+        [
+            Ok(
+                Set(
+                    QueryParam {
+                        key: "name",
+                        value: String("Rust"),
+                    },
+                ),
+            ),
+            Ok(
+                Set(
+                    QueryParam {
+                        key: "name",
+                        value: String(""),
+                    },
+                ),
+            ),
+            Ok(
+                Set(
+                    QueryParam {
+                        key: "year",
+                        value: Number(2010),
+                    },
+                ),
+            ),
+            Ok(
+                Unset(
+                    "name",
+                ),
+            ),
+            Ok(
+                Unset(
+                    "year",
+                ),
+            ),
+        ]
+        "#);
+    }
+
+    #[test]
+    fn is_use_cmd_detects_use_command() {
+        // GIVEN
+        let inputs = ["use", "use dev", "use bolt://127.0.0.1:7687", "use  dev "];
+
+        // WHEN
+        // THEN
+        for input in inputs {
+            assert!(is_use_cmd(input), "{input} should've been a use command");
+        }
+    }
+
+    #[test]
+    fn is_use_cmd_ignores_cypher_use_clause() {
+        // GIVEN
+        let inputs = [
+            "USE neo4j MATCH (n) RETURN n",
+            "use neo4j MATCH (n) RETURN n",
+            "used",
+        ];
+
+        // WHEN
+        // THEN
+        for input in inputs {
+            assert!(
+                !is_use_cmd(input),
+                "{input} shouldn't have been a use command"
+            );
+        }
+    }
+
+    #[test]
+    fn switch_pager_goes_back_to_configured_pager_after_builtin_one() -> anyhow::Result<()> {
+        // GIVEN
+        let mut pager = Some(Pager::custom("cat")?);
+        let mut page_results = false;
+        let cmds = ["page builtin", "page on", "page off", "page on"];
+
+        // WHEN
+        let mut result = Vec::new();
+        for cmd in cmds {
+            let page_cmd = parse_page_cmd(cmd).map_err(anyhow::Error::msg)?;
+            switch_pager(page_cmd, &mut pager, &mut page_results, || {
+                Pager::custom("cat")
+            })?;
+            let pager = match &pager {
+                Some(p) if p.is_builtin() => "builtin",
+                Some(_) => "configured",
+                None => "none",
+            };
+            result.push(format!("{cmd}: pager={pager}, paging={page_results}"));
+        }
+
+        // THEN
+        assert_snapshot!(result.join("\n"), @r"
+        page builtin: pager=builtin, paging=true
+        page on: pager=configured, paging=true
+        page off: pager=configured, paging=false
+        page on: pager=configured, paging=true
+        ");
+
+        Ok(())
+    }
+
+    #[test]
+    fn switch_pager_sets_up_configured_pager_if_there_is_none() -> anyhow::Result<()> {
+        // GIVEN
+        let mut pager = None;
+        let mut page_results = false;
+
+        // WHEN
+        switch_pager(PageCmd::On, &mut pager, &mut page_results, || {
+            Pager::custom("cat")
+        })?;
+
+        // THEN
+        assert!(pager.as_ref().is_some_and(|p| !p.is_builtin()));
+        assert!(page_results);
+
+        Ok(())
+    }
+
+    #[test]
+    fn switch_pager_keeps_pager_already_in_use() -> anyhow::Result<()> {
+        // GIVEN
+        let mut pager = Some(Pager::custom("cat")?);
+        let mut page_results = false;
+
+        // WHEN
+        switch_pager(PageCmd::On, &mut pager, &mut page_results, || {
+            anyhow::bail!("configured pager shouldn't be looked up")
+        })?;
+
+        // THEN
+        assert!(pager.as_ref().is_some_and(|p| !p.is_builtin()));
+        assert!(page_results);
+
+        Ok(())
+    }
+
     //------------//
     //  FAILURES  //
     //------------//
 
+    #[test]
+    fn parse_bench_cmd_fails_for_invalid_input() {
+        // GIVEN
+        let inputs = [
+            "bench",
+            "bench -n 0 RETURN 1",
+            "bench -W x RETURN 1",
+            "bench -n 10",
+            "bench export",
+        ];
+
+        // WHEN
+        let result: Vec<_> = inputs.iter().map(|i| parse_bench_cmd(i)).collect();
+
+        // THEN
+        assert_debug_snapshot!(result, @r#"
+        [
+            Err(
+                "Usage: bench [-n <RUNS>] [-W <WARMUP_RUNS>] <query/@path> | bench last | bench export <FILE>",
+            ),
+            Err(
+                "Error: invalid value \"0\" for -n: needs to be greater than 0",
+            ),
+            Err(
+                "Error: invalid value \"x\" for -W: value is not a valid number",
+            ),
+            Err(
+                "Usage: bench [-n <RUNS>] [-W <WARMUP_RUNS>] <query/@path> | bench last | bench export <FILE>",
+            ),
+            Err(
+                "Usage: bench [-n <RUNS>] [-W <WARMUP_RUNS>] <query/@path> | bench last | bench export <FILE>",
+            ),
+        ]
+        "#);
+    }
+
     #[test]
     fn get_query_from_user_input_fails_if_no_file_path_provided() {
         // GIVEN
@@ -512,4 +1472,49 @@ mod tests {
         // THEN
         assert_snapshot!(result, @r#"couldn't read file "/nonexistent/path/to/query.cypher""#);
     }
+
+    #[test]
+    fn parse_page_cmd_fails_for_invalid_input() {
+        // GIVEN
+        let inputs = ["page", "page maybe"];
+
+        // WHEN
+        let result: Vec<_> = inputs.iter().map(|i| parse_page_cmd(i)).collect();
+
+        // THEN
+        assert_debug_snapshot!(result, @r#"
+        [
+            Err(
+                "Usage: page on/off/builtin",
+            ),
+            Err(
+                "Usage: page on/off/builtin",
+            ),
+        ]
+        "#);
+    }
+
+    #[test]
+    fn parse_param_cmd_fails_for_invalid_input() {
+        // GIVEN
+        let inputs = ["name", "=5", "unset"];
+
+        // WHEN
+        let result: Vec<_> = inputs.iter().map(|i| parse_param_cmd(i)).collect();
+
+        // THEN
+        assert_debug_snapshot!(result, @r#"
+        [
+            Err(
+                "Error: parameter needs to be in the form key=value; usage: :param <KEY=VALUE> | :param unset <KEY>",
+            ),
+            Err(
+                "Error: parameter key is empty; usage: :param <KEY=VALUE> | :param unset <KEY>",
+            ),
+            Err(
+                "Error: parameter needs to be in the form key=value; usage: :param <KEY=VALUE> | :param unset <KEY>",
+            ),
+        ]
+        "#);
+    }
 }
@@ -0,0 +1,172 @@
+use crate::domain::{Token, TokenKind, matching_bracket, tokenize};
+use colored::Colorize;
+
+/// A part of a query, along with the kind of token it is (`None` for the text between tokens),
+/// and whether it's a bracket that's emphasised.
+#[derive(Debug)]
+struct Span<'a> {
+    text: &'a str,
+    kind: Option<TokenKind>,
+    emphasised: bool,
+}
+
+/// Colours a (possibly multi-line) query for display in the console's input line; if the cursor
+/// is on (or right after) a bracket, the bracket and its match are emphasised.
+pub fn highlight_query(query: &str, cursor: usize) -> String {
+    let mut highlighted = String::with_capacity(query.len() * 2);
+    for span in get_spans(query, cursor) {
+        let Some(kind) = span.kind else {
+            highlighted.push_str(span.text);
+            continue;
+        };
+
+        let text = span.text;
+        let colored_text = match kind {
+            TokenKind::Keyword => text.blue().bold(),
+            TokenKind::Label => text.yellow(),
+            TokenKind::RelationshipType => text.magenta(),
+            TokenKind::PropertyKey => text.cyan(),
+            TokenKind::String => text.green(),
+            TokenKind::Number => text.red(),
+            TokenKind::Parameter => text.bright_cyan().italic(),
+            TokenKind::Comment => text.bright_black(),
+            TokenKind::Bracket if span.emphasised => text.bright_white().on_bright_black().bold(),
+            TokenKind::Bracket => text.normal(),
+        };
+        highlighted.push_str(&colored_text.to_string());
+    }
+
+    highlighted
+}
+
+/// Splits a query into the spans it's highlighted by.
+fn get_spans(query: &str, cursor: usize) -> Vec<Span<'_>> {
+    let tokens = tokenize(query);
+    let matched_brackets = get_matched_brackets(&tokens, query, cursor);
+
+    let mut spans = Vec::with_capacity(tokens.len() * 2 + 1);
+    let mut last_end = 0;
+    for token in &tokens {
+        if token.start > last_end {
+            spans.push(Span {
+                text: &query[last_end..token.start],
+                kind: None,
+                emphasised: false,
+            });
+        }
+        spans.push(Span {
+            text: &query[token.start..token.end],
+            kind: Some(token.kind),
+            emphasised: matched_brackets.contains(&token.start),
+        });
+        last_end = token.end;
+    }
+    if last_end < query.len() {
+        spans.push(Span {
+            text: &query[last_end..],
+            kind: None,
+            emphasised: false,
+        });
+    }
+
+    spans
+}
+
+fn get_matched_brackets(tokens: &[Token], query: &str, cursor: usize) -> Vec<usize> {
+    let before_cursor = query[..cursor]
+        .chars()
+        .next_back()
+        .map(|c| cursor - c.len_utf8());
+
+    [Some(cursor), before_cursor]
+        .into_iter()
+        .flatten()
+        .find_map(|i| matching_bracket(tokens, query, i).map(|m| vec![i, m]))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_snapshot;
+
+    #[test]
+    fn get_spans_splits_query_into_tokens() {
+        // GIVEN
+        let query = r#"MATCH (l:Language {name: $name})-[:INFLUENCED]->(o)
+WHERE o.year > 2000 // recent ones
+RETURN o.name, "done""#;
+
+        // WHEN
+        let result = get_spans(query, 0);
+
+        // THEN
+        assert_snapshot!(describe(&result), @r#"
+        Keyword: "MATCH"
+        Bracket: "("
+        Label: "Language"
+        Bracket: "{"
+        PropertyKey: "name"
+        Parameter: "$name"
+        Bracket: "}"
+        Bracket: ")"
+        Bracket: "["
+        RelationshipType: "INFLUENCED"
+        Bracket: "]"
+        Bracket: "("
+        Bracket: ")"
+        Keyword: "WHERE"
+        PropertyKey: "year"
+        Number: "2000"
+        Comment: "// recent ones"
+        Keyword: "RETURN"
+        PropertyKey: "name"
+        String: "\"done\""
+        "#);
+    }
+
+    #[test]
+    fn get_spans_emphasises_matching_brackets() {
+        // GIVEN
+        let query = "RETURN [(1), 2]";
+
+        // WHEN
+        let result = get_spans(query, query.len());
+
+        // THEN
+        assert_snapshot!(describe(&result), @r#"
+        Keyword: "RETURN"
+        Bracket: "[" (emphasised)
+        Bracket: "("
+        Number: "1"
+        Bracket: ")"
+        Number: "2"
+        Bracket: "]" (emphasised)
+        "#);
+    }
+
+    #[test]
+    fn get_spans_covers_the_whole_query() {
+        // GIVEN
+        let query = "MATCH (n) // trailing text";
+
+        // WHEN
+        let result = get_spans(query, 0);
+
+        // THEN
+        let text: String = result.iter().map(|s| s.text).collect();
+        assert_eq!(text, query);
+    }
+
+    fn describe(spans: &[Span<'_>]) -> String {
+        spans
+            .iter()
+            .filter_map(|span| {
+                let kind = span.kind?;
+                let emphasised = if span.emphasised { " (emphasised)" } else { "" };
+                Some(format!("{:?}: {:?}{emphasised}", kind, span.text))
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
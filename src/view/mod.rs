@@ -1,7 +1,15 @@
+mod benchmark;
+mod browser;
 mod completer;
 mod console;
+mod highlight;
+mod plan;
 mod results;
 
+pub use benchmark::*;
+pub use browser::*;
 use completer::*;
 pub use console::*;
+use highlight::*;
+pub use plan::*;
 pub use results::*;
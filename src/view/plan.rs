@@ -0,0 +1,125 @@
+use crate::domain::{PlanOperator, QueryPlan};
+
+/// Renders a query plan as an indented tree, with each operator followed by its arguments and
+/// the stats the database reported for it (stats that are unset, ie. "-", are left out).
+pub fn get_plan(plan: &QueryPlan) -> String {
+    let mut lines = vec![];
+
+    for (i, tree) in plan.trees.iter().enumerate() {
+        if i > 0 {
+            lines.push(String::new());
+        }
+        if let Some(name) = &tree.name {
+            lines.push(format!("{name}:"));
+        }
+        lines.push(describe_operator(&tree.root));
+        push_children(&tree.root, "", &mut lines);
+    }
+
+    lines.join("\n")
+}
+
+fn push_children(operator: &PlanOperator, prefix: &str, lines: &mut Vec<String>) {
+    for (i, child) in operator.children.iter().enumerate() {
+        let is_last = i == operator.children.len() - 1;
+        let (connector, child_prefix) = if is_last {
+            ("└─ ", "   ")
+        } else {
+            ("├─ ", "│  ")
+        };
+
+        lines.push(format!("{prefix}{connector}{}", describe_operator(child)));
+        push_children(child, &format!("{prefix}{child_prefix}"), lines);
+    }
+}
+
+fn describe_operator(operator: &PlanOperator) -> String {
+    let mut description = operator.name.clone();
+
+    if !is_unset(&operator.arguments) {
+        description.push_str(&format!("  {}", operator.arguments));
+    }
+
+    let stats: Vec<String> = operator
+        .stats
+        .iter()
+        .filter(|(_, value)| !is_unset(value))
+        .map(|(key, value)| format!("{}: {value}", key.to_lowercase()))
+        .collect();
+    if !stats.is_empty() {
+        description.push_str(&format!("  ({})", stats.join(", ")));
+    }
+
+    description
+}
+
+fn is_unset(value: &str) -> bool {
+    matches!(value.trim(), "" | "-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::PlanTree;
+    use insta::assert_snapshot;
+
+    #[test]
+    fn get_plan_renders_an_indented_tree() {
+        // GIVEN
+        let operator = |name: &str, arguments: &str, rows: &str, children| PlanOperator {
+            id: name.to_lowercase(),
+            name: name.to_string(),
+            arguments: arguments.to_string(),
+            stats: vec![
+                ("Estimated Rows".to_string(), "10".to_string()),
+                ("Rows".to_string(), rows.to_string()),
+            ],
+            children,
+        };
+        let plan = QueryPlan {
+            trees: vec![
+                PlanTree {
+                    name: None,
+                    root: operator(
+                        "ProduceResults",
+                        "l.name",
+                        "2",
+                        vec![operator(
+                            "Apply",
+                            "-",
+                            "2",
+                            vec![
+                                operator(
+                                    "Filter",
+                                    "l.year > 2000",
+                                    "3",
+                                    vec![operator("NodeByLabelScan", "l:Language", "5", vec![])],
+                                ),
+                                operator("Argument", "", "-", vec![]),
+                            ],
+                        )],
+                    ),
+                },
+                PlanTree {
+                    name: Some("subQuery1".to_string()),
+                    root: operator("Limit", "2", "2", vec![]),
+                },
+            ],
+        };
+
+        // WHEN
+        let result = get_plan(&plan);
+
+        // THEN
+        assert_snapshot!(result, @r"
+        ProduceResults  l.name  (estimated rows: 10, rows: 2)
+        └─ Apply  (estimated rows: 10, rows: 2)
+           ├─ Filter  l.year > 2000  (estimated rows: 10, rows: 3)
+           │  └─ NodeByLabelScan  l:Language  (estimated rows: 10, rows: 5)
+           └─ Argument  (estimated rows: 10)
+
+        subQuery1:
+        Limit  2  (estimated rows: 10, rows: 2)
+        ");
+    }
+}
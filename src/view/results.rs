@@ -1,49 +1,222 @@
-use serde_json::Value;
 use tabled::builder::Builder;
+use tabled::settings::object::Columns;
+use tabled::settings::peaker::PriorityMax;
 use tabled::settings::style::Style;
+use tabled::settings::width::Width;
 
-use crate::domain::NonEmptyResults;
+use crate::domain::{CellOverflow, DisplayOptions, ExpandedMode, GraphValue, NonEmptyResults};
 
-pub fn get_results(results: &NonEmptyResults) -> String {
-    let mut builder = Builder::default();
+const ELLIPSIS: &str = "…";
+
+/// Renders results for a terminal that's `width` characters wide (if known).
+///
+/// Tables are fit to the terminal's width by truncating or wrapping their widest columns; when
+/// wrapping, nested values that don't fit in a column are pretty-printed as JSON. Only up to
+/// `max_rows` rows are rendered, followed by a note on how many there are in all.
+pub fn get_results_for_display(
+    results: &NonEmptyResults,
+    options: &DisplayOptions,
+    width: Option<usize>,
+) -> String {
+    let total_rows = results.list().len();
+    let num_rows = options.max_rows.unwrap_or(total_rows).min(total_rows);
+
+    let expanded = match options.expanded {
+        ExpandedMode::Off => false,
+        ExpandedMode::On => true,
+        ExpandedMode::Auto => {
+            let table = results_builder_with(results, num_rows, |v| v.to_string())
+                .build()
+                .with(Style::psql())
+                .to_string();
+            let table_width = table.lines().map(|l| l.chars().count()).max();
+            matches!((table_width, width), (Some(t), Some(w)) if t > w)
+        }
+    };
+
+    let mut output = match expanded {
+        true => expanded_results(results, num_rows),
+        false => fitted_results(results, num_rows, options, width),
+    };
+    if num_rows < total_rows {
+        output.push_str(&format!(
+            "\n(showing {} of {} rows)",
+            format_count(num_rows),
+            format_count(total_rows)
+        ));
+    }
+
+    output
+}
 
-    if let Value::Object(first) = results.first() {
-        let headers: Vec<String> = first.keys().cloned().collect();
-        builder.push_record(&headers);
-
-        for result in results.list() {
-            if let Value::Object(row) = result {
-                let cells: Vec<String> = headers
-                    .iter()
-                    .map(|h| {
-                        row.get(h)
-                            .map(|v| match v {
-                                Value::String(s) => s.clone(),
-                                Value::Null => "null".to_string(),
-                                _ => v.to_string(),
-                            })
-                            .unwrap_or_else(|| "".to_string())
-                    })
-                    .collect();
-                builder.push_record(cells);
+/// Renders every record as a block of `column | value` lines, the way psql's expanded display
+/// does.
+fn expanded_results(results: &NonEmptyResults, num_rows: usize) -> String {
+    let headers = results.columns();
+    let title = |i: usize| format!("-[ RECORD {} ]", i + 1);
+
+    let records: Vec<Vec<String>> = results
+        .cells()
+        .take(num_rows)
+        .map(|cells| {
+            cells
+                .into_iter()
+                .map(|c| c.map(|v| v.to_string()).unwrap_or_default())
+                .collect()
+        })
+        .collect();
+    // the key column is widened to fit the titles (the widest being the last record's), so that
+    // the title's `+` lines up with the `|`s below it
+    let key_width = headers
+        .iter()
+        .map(|h| h.chars().count())
+        .chain(
+            records
+                .len()
+                .checked_sub(1)
+                .map(|i| title(i).chars().count()),
+        )
+        .max()
+        .unwrap_or_default();
+    let value_width = records
+        .iter()
+        .flatten()
+        .flat_map(|v| v.lines())
+        .map(|l| l.chars().count())
+        .max()
+        .unwrap_or_default();
+
+    let mut lines = vec![];
+    for (i, record) in records.iter().enumerate() {
+        let title = title(i);
+        let separator_width = (key_width + 1).saturating_sub(title.chars().count());
+        lines.push(format!(
+            "{title}{}+{}",
+            "-".repeat(separator_width),
+            "-".repeat(value_width + 1)
+        ));
+
+        for (header, value) in headers.iter().zip(record) {
+            let mut value_lines = value.lines();
+            lines.push(format!(
+                "{header:<key_width$} | {}",
+                value_lines.next().unwrap_or_default()
+            ));
+            for line in value_lines {
+                lines.push(format!("{:<key_width$} | {line}", ""));
             }
         }
     }
 
-    let mut table = builder.build();
+    lines
+        .iter()
+        .map(|l| l.trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
 
+/// Renders a table that fits in `width` characters (if known), with no column wider than
+/// `options.max_column_width`.
+fn fitted_results(
+    results: &NonEmptyResults,
+    num_rows: usize,
+    options: &DisplayOptions,
+    width: Option<usize>,
+) -> String {
+    let column_width = match (options.max_column_width, width) {
+        (Some(max), Some(width)) => Some(max.min(width)),
+        (max, width) => max.or(width),
+    };
+    let pretty_print = |v: &GraphValue| match (options.overflow, column_width) {
+        (CellOverflow::Wrap, Some(column_width))
+            if is_nested(v) && v.to_string().chars().count() > column_width =>
+        {
+            serde_json::to_string_pretty(v).unwrap_or_else(|_| v.to_string())
+        }
+        _ => v.to_string(),
+    };
+
+    let mut table = results_builder_with(results, num_rows, pretty_print).build();
     table.with(Style::psql());
 
+    if let Some(max) = options.max_column_width {
+        match options.overflow {
+            CellOverflow::Truncate => table.modify(
+                Columns::new(..),
+                Width::truncate(max).multiline(true).suffix(ELLIPSIS),
+            ),
+            CellOverflow::Wrap => table.modify(Columns::new(..), Width::wrap(max).keep_words(true)),
+        };
+    }
+    if let Some(width) = width {
+        match options.overflow {
+            CellOverflow::Truncate => table.with(
+                Width::truncate(width)
+                    .multiline(true)
+                    .suffix(ELLIPSIS)
+                    .priority(PriorityMax::right()),
+            ),
+            CellOverflow::Wrap => table.with(
+                Width::wrap(width)
+                    .keep_words(true)
+                    .priority(PriorityMax::right()),
+            ),
+        };
+    }
+
     table.to_string()
 }
 
+fn is_nested(value: &GraphValue) -> bool {
+    matches!(
+        value,
+        GraphValue::List(_)
+            | GraphValue::Map(_)
+            | GraphValue::Node(_)
+            | GraphValue::Relationship(_)
+            | GraphValue::Path(_)
+    )
+}
+
+/// Formats a count with thousands separators, eg. 10000 as "10,000".
+fn format_count(count: usize) -> String {
+    let digits = count.to_string();
+    let mut formatted = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+
+    formatted
+}
+
+fn results_builder_with<V>(results: &NonEmptyResults, num_rows: usize, value: V) -> Builder
+where
+    V: Fn(&GraphValue) -> String,
+{
+    let mut builder = Builder::default();
+
+    let headers = results.columns();
+    if !headers.is_empty() {
+        builder.push_record(headers.iter().map(|h| h.to_string()));
+
+        for cells in results.cells().take(num_rows) {
+            builder.push_record(cells.into_iter().map(|c| c.map(&value).unwrap_or_default()));
+        }
+    }
+
+    builder
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use insta::assert_snapshot;
 
     #[test]
-    fn get_results_returns_correct_table_for_array_of_objects() {
+    fn get_results_for_display_returns_correct_table_for_array_of_objects() {
         // GIVEN
         let results = vec![
             serde_json::json!({"language": "Rust", "creator": "Graydon Hoare", "year": 2010}),
@@ -53,7 +226,7 @@ mod tests {
         let results = NonEmptyResults::try_from(results).expect("results should've been created");
 
         // WHEN
-        let result = get_results(&results);
+        let result = get_results_for_display(&results, &DisplayOptions::default(), None);
 
         // THEN
         assert_snapshot!(result, @r"
@@ -66,7 +239,30 @@ mod tests {
     }
 
     #[test]
-    fn get_results_formats_null_values_correctly() {
+    fn get_results_for_display_follows_column_order() {
+        // GIVEN
+        let results = vec![
+            serde_json::json!({"language": "Rust", "creator": "Graydon Hoare", "year": 2010}),
+            serde_json::json!({"language": "Python", "creator": "Guido van Rossum", "year": 1991}),
+            serde_json::json!({"language": "Go", "creator": "Rob Pike", "year": 2009}),
+        ];
+        let results = NonEmptyResults::with_columns(&["language", "creator", "year"], results);
+
+        // WHEN
+        let result = get_results_for_display(&results, &DisplayOptions::default(), None);
+
+        // THEN
+        assert_snapshot!(result, @r"
+         language | creator          | year 
+        ----------+------------------+------
+         Rust     | Graydon Hoare    | 2010 
+         Python   | Guido van Rossum | 1991 
+         Go       | Rob Pike         | 2009
+        ");
+    }
+
+    #[test]
+    fn get_results_for_display_formats_null_values_correctly() {
         // GIVEN
         let results = vec![
             serde_json::json!({"language": "Rust", "creator": null}),
@@ -75,7 +271,7 @@ mod tests {
         let results = NonEmptyResults::try_from(results).expect("results should've been created");
 
         // WHEN
-        let result = get_results(&results);
+        let result = get_results_for_display(&results, &DisplayOptions::default(), None);
 
         // THEN
         assert_snapshot!(result, @r"
@@ -87,7 +283,7 @@ mod tests {
     }
 
     #[test]
-    fn get_results_converts_non_string_values_to_string() {
+    fn get_results_for_display_converts_non_string_values_to_string() {
         // GIVEN
         let results = vec![
             serde_json::json!({"version": "1.0", "stable": true, "downloads": 1000}),
@@ -96,7 +292,7 @@ mod tests {
         let results = NonEmptyResults::try_from(results).expect("results should've been created");
 
         // WHEN
-        let result = get_results(&results);
+        let result = get_results_for_display(&results, &DisplayOptions::default(), None);
 
         // THEN
         assert_snapshot!(result, @r"
@@ -108,7 +304,7 @@ mod tests {
     }
 
     #[test]
-    fn get_results_skips_non_object_array_elements() {
+    fn get_results_for_display_shows_non_object_rows_in_a_value_column() {
         // GIVEN
         let results = vec![
             serde_json::json!({"language": "Rust", "creator": "Graydon Hoare"}),
@@ -118,19 +314,178 @@ mod tests {
         let results = NonEmptyResults::try_from(results).expect("results should've been created");
 
         // WHEN
-        let result = get_results(&results);
+        let result = get_results_for_display(&results, &DisplayOptions::default(), None);
 
         // THEN
         assert_snapshot!(result, @r"
-         creator          | language 
-        ------------------+----------
-         Graydon Hoare    | Rust     
-         Guido van Rossum | Python
+         creator          | language | value   
+        ------------------+----------+---------
+         Graydon Hoare    | Rust     |         
+                          |          | invalid 
+         Guido van Rossum | Python   |
+        ");
+    }
+
+    #[test]
+    fn get_results_for_display_shows_keys_from_all_rows() {
+        // GIVEN
+        let results = vec![
+            serde_json::json!({"language": "Rust", "year": 2010}),
+            serde_json::json!({"language": "Python", "creator": "Guido van Rossum"}),
+            serde_json::json!({"language": "Go", "year": 2009, "paradigm": "concurrent"}),
+        ];
+        let results = NonEmptyResults::try_from(results).expect("results should've been created");
+
+        // WHEN
+        let result = get_results_for_display(&results, &DisplayOptions::default(), None);
+
+        // THEN
+        assert_snapshot!(result, @r"
+         language | year | creator          | paradigm   
+        ----------+------+------------------+------------
+         Rust     | 2010 |                  |            
+         Python   |      | Guido van Rossum |            
+         Go       | 2009 |                  | concurrent
+        ");
+    }
+
+    #[test]
+    fn get_results_for_display_shows_a_block_per_record_when_expanded() {
+        // GIVEN
+        let results = vec![
+            serde_json::json!({"language": "Rust", "tagline": "fast\nreliable", "year": 2010}),
+            serde_json::json!({"language": "Python", "tagline": null}),
+        ];
+        let results = NonEmptyResults::with_columns(&["language", "tagline", "year"], results);
+        let options = DisplayOptions {
+            expanded: ExpandedMode::On,
+            ..Default::default()
+        };
+
+        // WHEN
+        let result = get_results_for_display(&results, &options, None);
+
+        // THEN
+        assert_snapshot!(result, @r"
+        -[ RECORD 1 ]-+---------
+        language      | Rust
+        tagline       | fast
+                      | reliable
+        year          | 2010
+        -[ RECORD 2 ]-+---------
+        language      | Python
+        tagline       | null
+        year          |
+        ");
+    }
+
+    #[test]
+    fn get_results_for_display_switches_to_expanded_when_table_is_too_wide() {
+        // GIVEN
+        let results = vec![serde_json::json!({"language": "Rust", "year": 2010})];
+        let results = NonEmptyResults::with_columns(&["language", "year"], results);
+        let options = DisplayOptions {
+            expanded: ExpandedMode::Auto,
+            ..Default::default()
+        };
+
+        // WHEN
+        let narrow = get_results_for_display(&results, &options, Some(10));
+        let wide = get_results_for_display(&results, &options, Some(80));
+
+        // THEN
+        assert_snapshot!(narrow, @r"
+        -[ RECORD 1 ]-+-----
+        language      | Rust
+        year          | 2010
+        ");
+        assert_snapshot!(wide, @r"
+         language | year 
+        ----------+------
+         Rust     | 2010
+        ");
+    }
+
+    #[test]
+    fn get_results_for_display_truncates_columns_to_fit_the_terminal() {
+        // GIVEN
+        let results = vec![serde_json::json!({
+            "language": "Rust",
+            "tagline": "A language empowering everyone to build reliable and efficient software."
+        })];
+        let results = NonEmptyResults::with_columns(&["language", "tagline"], results);
+
+        // WHEN
+        let result = get_results_for_display(&results, &DisplayOptions::default(), Some(40));
+
+        // THEN
+        assert_snapshot!(result, @r"
+         language | tagline                     
+        ----------+-----------------------------
+         Rust     | A language empowering ever…
+        ");
+    }
+
+    #[test]
+    fn get_results_for_display_wraps_cells_and_pretty_prints_nested_values() {
+        // GIVEN
+        let results = vec![serde_json::json!({
+            "language": "Rust",
+            "tagline": "A language empowering everyone to build reliable and efficient software.",
+            "features": {"garbage_collection": false, "paradigms": ["functional", "imperative"]},
+        })];
+        let results = NonEmptyResults::with_columns(&["language", "tagline", "features"], results);
+        let options = DisplayOptions {
+            max_column_width: Some(30),
+            overflow: CellOverflow::Wrap,
+            ..Default::default()
+        };
+
+        // WHEN
+        let result = get_results_for_display(&results, &options, None);
+
+        // THEN
+        assert_snapshot!(result, @r#"
+         language | tagline                        | features                       
+        ----------+--------------------------------+--------------------------------
+         Rust     | A language empowering everyone | {                              
+                  |  to build reliable and         |   "garbage_collection": false, 
+                  | efficient software.            |   "paradigms": [               
+                  |                                |     "functional",              
+                  |                                |     "imperative"               
+                  |                                |   ]                            
+                  |                                | }
+        "#);
+    }
+
+    #[test]
+    fn get_results_for_display_caps_the_number_of_rows() {
+        // GIVEN
+        let results = (0..1200)
+            .map(|i| serde_json::json!({"id": i}))
+            .collect::<Vec<_>>();
+        let results = NonEmptyResults::with_columns(&["id"], results);
+        let options = DisplayOptions {
+            max_rows: Some(3),
+            ..Default::default()
+        };
+
+        // WHEN
+        let result = get_results_for_display(&results, &options, None);
+
+        // THEN
+        assert_snapshot!(result, @r"
+         id 
+        ----
+         0  
+         1  
+         2  
+        (showing 3 of 1,200 rows)
         ");
     }
 
     #[test]
-    fn get_results_shows_empty_string_for_missing_columns() {
+    fn get_results_for_display_shows_empty_string_for_missing_columns() {
         // GIVEN
         let results = vec![
             serde_json::json!({"language": "Rust", "creator": "Graydon Hoare", "year": 2010}),
@@ -140,7 +495,7 @@ mod tests {
         let results = NonEmptyResults::try_from(results).expect("results should've been created");
 
         // WHEN
-        let result = get_results(&results);
+        let result = get_results_for_display(&results, &DisplayOptions::default(), None);
 
         // THEN
         assert_snapshot!(result, @r"
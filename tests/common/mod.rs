@@ -1,16 +1,31 @@
 use insta_cmd::get_cargo_bin;
 use std::{ffi::OsStr, path::PathBuf, process::Command};
+use tempfile::TempDir;
 
 pub struct Fixture {
     bin_path: PathBuf,
+    config_dir: TempDir,
 }
 
 #[cfg(test)]
 impl Fixture {
     pub fn new() -> Self {
         let bin_path = get_cargo_bin("grafq");
+        let config_dir = TempDir::new().expect("temporary directory should've been created");
 
-        Self { bin_path }
+        Self {
+            bin_path,
+            config_dir,
+        }
+    }
+
+    #[allow(unused)]
+    pub fn write_config(&self, contents: &str) {
+        let grafq_config_dir = self.config_dir.path().join("grafq");
+        std::fs::create_dir_all(&grafq_config_dir)
+            .expect("config directory should've been created");
+        std::fs::write(grafq_config_dir.join("grafq.toml"), contents)
+            .expect("config file should've been written");
     }
 
     pub fn cmd<I, S>(&self, args: I) -> Command
@@ -21,6 +36,7 @@ impl Fixture {
         let mut command = Command::new(&self.bin_path);
         command.args(args);
         command.env_clear();
+        command.env("XDG_CONFIG_HOME", self.config_dir.path());
 
         command
     }
@@ -24,12 +24,15 @@ fn shows_help() {
     Usage: grafq console [OPTIONS]
 
     Options:
-      -p, --page-results             Display results via a pager ("less", by default, can be overridden by $GRAFQ_PAGER)
-      -w, --write-results            Write results to filesystem
-      -d, --results-dir <DIRECTORY>  Directory to write results in [default: .grafq]
-          --debug                    Output debug information without doing anything
-      -f, --results-format <FORMAT>  Format to write results in [default: json] [possible values: csv, json]
-      -h, --help                     Print help
+      -p, --page-results                 Display results via a pager ("less", by default, can be overridden by $GRAFQ_PAGER)
+      -w, --write-results                Write results to filesystem
+      -d, --results-dir <DIRECTORY>      Directory to write results in [default: .grafq]
+          --debug                        Output debug information without doing anything
+      -f, --results-format <FORMAT>      Format to write results in [default: json] [possible values: csv, json, ndjson, tsv, parquet, arrow, markdown, html]
+          --profile <NAME>               Profile (from grafq's config file) to use; environment variables override its values
+          --results-compression <CODEC>  Compression for parquet/arrow results [default: zstd for parquet, none for arrow] [possible values: none, lz4, zstd]
+          --flatten[=<ARRAYS>]           Flatten nested values into dotted columns (except in JSON/NDJSON); arrays stay as JSON, or are spread over columns/rows [possible values: json, index, explode]
+      -h, --help                         Print help
 
     ----- stderr -----
     "#);
@@ -90,3 +93,28 @@ fn debug_flag_works_for_overridden_flags() {
     ----- stderr -----
     ");
 }
+
+#[test]
+fn debug_flag_shows_selected_profile() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["console", "--profile", "dev", "--debug"]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    DEBUG INFO
+
+    command:                    console
+    profile:                    dev
+    display results via pager:  false
+    write results:              false
+    results directory:          .grafq
+    results format:             json
+
+    ----- stderr -----
+    ");
+}
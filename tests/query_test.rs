@@ -23,21 +23,41 @@ fn shows_help() {
     ----- stdout -----
     Execute a one-off query
 
-    Usage: grafq query [OPTIONS] <QUERY>
+    Usage: grafq query [OPTIONS] <QUERY>...
 
     Arguments:
-      <QUERY>  Cypher query to execute
+      <QUERY>...  Cypher query to execute ("@FILE" reads it from a file, "-" from stdin); benchmarks can compare several queries
 
     Options:
       -p, --page-results                    Display results via a pager ("less", by default, can be overridden by $GRAFQ_PAGER)
-      -b, --bench                           Whether to benchmark the query
+          --param <KEY=VALUE>               Query parameter, bound to $KEY (VALUE is parsed as JSON, falling back to a string); can be repeated
           --debug                           Output debug information without doing anything
+          --params-file <FILE>              JSON file containing an object of query parameters (overridden by --param)
+      -b, --bench                           Whether to benchmark the query
+          --profile <NAME>                  Profile (from grafq's config file) to use; environment variables override its values
       -n, --bench-num-runs <NUMBER>         Number of benchmark runs [default: 5]
       -W, --bench-num-warmup-runs <NUMBER>  Number of benchmark warmup runs [default: 3]
+          --bench-params <FILE>             CSV/NDJSON file with a parameter set per row/line; every benchmark run binds one of them (on top of --param)
+          --bench-params-order <ORDER>      Order to bind the parameter sets in [default: round-robin] [possible values: round-robin, random]
+      -c, --concurrency <NUMBER>            Run the benchmark from this many concurrent tasks (as a load test)
+          --duration <DURATION>             Run the load test for this long (eg. 30s, 5m) instead of for a number of runs
+          --rate <NUMBER>                   Cap the load test at this many queries per second (across all tasks)
+          --bench-output <FILE>             File to write a benchmark report to (JSON or CSV, as per its extension)
+          --bench-baseline <FILE>           Benchmark report (from an earlier --bench-output) to compare the median against
+          --bench-threshold <PERCENT>       Percentage by which the median can exceed the baseline's before grafq exits with an error [default: 10]
+      -e, --explain[=<MODE>]                Show the query's execution plan instead of its results ("profile" and "details" execute the query to gather stats) [possible values: explain, profile, details]
       -P, --print-query                     Print query
       -w, --write-results                   Write results to filesystem
       -d, --results-dir <DIRECTORY>         Directory to write results in [default: .grafq]
-      -f, --results-format <FORMAT>         Format to write results in [default: json] [possible values: csv, json]
+      -f, --results-format <FORMAT>         Format to write results in [default: json] [possible values: csv, json, ndjson, tsv, parquet, arrow, markdown, html]
+          --results-compression <CODEC>     Compression for parquet/arrow results [default: zstd for parquet, none for arrow] [possible values: none, lz4, zstd]
+          --flatten[=<ARRAYS>]              Flatten nested values into dotted columns (except in JSON/NDJSON); arrays stay as JSON, or are spread over columns/rows [possible values: json, index, explode]
+      -o, --output <FILE>                   File to write results to ("-" for stdout), in --format (or as per its extension, falling back to --results-format)
+          --format <FORMAT>                 Format to print (or page) results in [default: table, or --results-format when stdout is piped or results are paged] [possible values: table, csv, json, ndjson, tsv, parquet, arrow, markdown, html]
+      -x, --expanded[=<MODE>]               Display each record as a block of "column | value" lines ("auto" does so if the table is wider than the terminal) [possible values: off, on, auto]
+          --max-column-width <WIDTH>        Maximum width of a table column (on top of the table being fit to the terminal)
+          --overflow <MODE>                 What to do with cells that don't fit in their column [default: truncate] [possible values: truncate, wrap]
+          --max-rows <NUMBER>               Maximum number of rows to display
       -h, --help                            Print help
 
     ----- stderr -----
@@ -151,156 +171,917 @@ fn debug_flag_works_for_write_results_flags() {
     ");
 }
 
-//-------------//
-//  FAILURES   //
-//-------------//
-
 #[test]
-fn fails_if_provided_with_no_db_uri() {
+fn debug_flag_works_for_query_params() {
     // GIVEN
     let fx = Fixture::new();
-    let mut cmd = fx.cmd(["query", QUERY]);
+    let mut cmd = fx.cmd([
+        "query",
+        "--param",
+        "name=Alice",
+        "--param",
+        "limit=5",
+        "--params-file",
+        "path/to/params.json",
+        "--debug",
+        QUERY,
+    ]);
 
     // WHEN
     // THEN
     assert_cmd_snapshot!(cmd, @r#"
-    success: false
-    exit_code: 1
+    success: true
+    exit_code: 0
     ----- stdout -----
+    DEBUG INFO
 
-    ----- stderr -----
-    Error: couldn't build db client
+    command:                    query
+    display results via pager:  false
+    benchmark:                  false
+    print query:                false
+    params file:                path/to/params.json
+    params:                     name="Alice", limit=5
+    write results:              false
 
-    Caused by:
-        DB_URI is not set
+    query:
+    ---
+    MATCH (c: Candidate) RETURN c.id LIMIT 5
+    ---
+
+    ----- stderr -----
+    "#);
+}
 
-    grafq requires the environment variable DB_URI to be set.
+#[test]
+fn empty_query_param_values_are_bound_as_empty_strings() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["query", "--param", "name=", "--debug", QUERY]);
 
-    - For an AWS Neptune database, use the https scheme. Neptune uses IAM
-        authentication, so ensure your AWS credentials are configured correctly (via
-        environment variables or the AWS shared config file):
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r#"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    DEBUG INFO
 
-        DB_URI="https://abc.xyz.us-east-1.neptune.amazonaws.com:8182"
+    command:                    query
+    display results via pager:  false
+    benchmark:                  false
+    print query:                false
+    params:                     name=""
+    write results:              false
 
-    - For a Neo4j database, use the bolt scheme and provide authentication details:
+    query:
+    ---
+    MATCH (c: Candidate) RETURN c.id LIMIT 5
+    ---
 
-        DB_URI="bolt://127.0.0.1:7687"
-        NEO4J_USER="neo4j"
-        NEO4J_PASSWORD="your-password"
-        NEO4J_DB="neo4j"
+    ----- stderr -----
     "#);
 }
 
 #[test]
-fn fails_if_provided_with_invalid_db_uri_scheme() {
+fn debug_flag_works_for_benchmark_report_flags() {
     // GIVEN
     let fx = Fixture::new();
-    let mut cmd = fx.cmd(["query", QUERY]);
-    cmd.env("DB_URI", "invalid://abc.xyz:8182");
+    let mut cmd = fx.cmd([
+        "query",
+        "--bench",
+        "--bench-output",
+        "path/to/report.json",
+        "--bench-baseline",
+        "path/to/baseline.json",
+        "--bench-threshold",
+        "5",
+        "--debug",
+        QUERY,
+    ]);
 
     // WHEN
     // THEN
-    assert_cmd_snapshot!(cmd, @r#"
-    success: false
-    exit_code: 1
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
     ----- stdout -----
+    DEBUG INFO
 
-    ----- stderr -----
-    Error: couldn't build db client
+    command:                    query
+    display results via pager:  false
+    benchmark:                  true
+    benchmark num runs:         5
+    benchmark num warmup runs:  3
+    benchmark output:           path/to/report.json
+    benchmark baseline:         path/to/baseline.json
+    regression threshold:       5%
+    print query:                false
+    write results:              false
 
-    Caused by:
-        DB_URI has unsupported scheme: "invalid"
+    query:
+    ---
+    MATCH (c: Candidate) RETURN c.id LIMIT 5
+    ---
 
-    Only 'bolt' and 'https' schemes are supported by grafq.
-    Use bolt for neo4j, and https for AWS Neptune.
-    "#);
+    ----- stderr -----
+    ");
 }
 
 #[test]
-fn fails_if_not_provided_with_neo4j_auth() {
+fn debug_flag_works_for_load_test_flags() {
     // GIVEN
     let fx = Fixture::new();
-    let mut cmd = fx.cmd(["query", QUERY]);
-    cmd.env("DB_URI", "bolt://127.0.0.1:7687");
+    let mut cmd = fx.cmd([
+        "query",
+        "--bench",
+        "--concurrency",
+        "8",
+        "--duration",
+        "30s",
+        "--rate",
+        "100",
+        "--debug",
+        QUERY,
+    ]);
 
     // WHEN
     // THEN
-    assert_cmd_snapshot!(cmd, @r#"
-    success: false
-    exit_code: 1
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
     ----- stdout -----
+    DEBUG INFO
+
+    command:                    query
+    display results via pager:  false
+    benchmark:                  true
+    benchmark num runs:         5
+    benchmark num warmup runs:  3
+    concurrency:                8
+    duration:                   30s
+    rate:                       100/s
+    print query:                false
+    write results:              false
+
+    query:
+    ---
+    MATCH (c: Candidate) RETURN c.id LIMIT 5
+    ---
 
     ----- stderr -----
-    Error: couldn't build db client
+    ");
+}
 
-    Caused by:
-        environment variable "NEO4J_USER" is missing
+#[test]
+fn debug_flag_works_for_explain_flag() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["query", "--explain=profile", "--debug", QUERY]);
 
-    The environment variables NEO4J_USER, NEO4J_PASSWORD, and NEO4J_DB need to be set when connecting
-    to a neo4j database (which was determined by the bolt scheme in DB_URI).
-    "#);
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    DEBUG INFO
+
+    command:                    query
+    display results via pager:  false
+    benchmark:                  false
+    explain:                    profile
+    print query:                false
+    write results:              false
+
+    query:
+    ---
+    MATCH (c: Candidate) RETURN c.id LIMIT 5
+    ---
+
+    ----- stderr -----
+    ");
 }
 
 #[test]
-fn fails_if_provided_with_incorrect_benchmark_num_runs() {
+fn debug_flag_works_for_benchmark_params_flags() {
     // GIVEN
     let fx = Fixture::new();
-    let mut cmd = fx.cmd(["query", "--bench", "--bench-num-runs", "0", QUERY]);
+    let mut cmd = fx.cmd([
+        "query",
+        "--bench",
+        "--bench-params",
+        "params.ndjson",
+        "--bench-params-order",
+        "random",
+        "--debug",
+        QUERY,
+    ]);
 
     // WHEN
     // THEN
     assert_cmd_snapshot!(cmd, @r"
-    success: false
-    exit_code: 2
+    success: true
+    exit_code: 0
     ----- stdout -----
+    DEBUG INFO
+
+    command:                    query
+    display results via pager:  false
+    benchmark:                  true
+    benchmark num runs:         5
+    benchmark num warmup runs:  3
+    benchmark params:           params.ndjson (random)
+    print query:                false
+    write results:              false
+
+    query:
+    ---
+    MATCH (c: Candidate) RETURN c.id LIMIT 5
+    ---
 
     ----- stderr -----
-    error: invalid value '0' for '--bench-num-runs <NUMBER>': needs to be greater than 0
+    ");
+}
 
-    For more information, try '--help'.
+#[test]
+fn debug_flag_works_for_output_flags() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "query", "--output", "-", "--format", "ndjson", "--debug", QUERY,
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    DEBUG INFO
+
+    command:                    query
+    display results via pager:  false
+    benchmark:                  false
+    print query:                false
+    write results:              false
+    output:                     -
+    output format:              ndjson
+
+    query:
+    ---
+    MATCH (c: Candidate) RETURN c.id LIMIT 5
+    ---
+
+    ----- stderr -----
     ");
 }
 
 #[test]
-fn fails_if_both_benchmark_and_write_flags_are_provided() {
+fn debug_flag_works_for_expanded_flag() {
     // GIVEN
     let fx = Fixture::new();
-    let mut cmd = fx.cmd(["query", "--bench", "--write-results", QUERY]);
+    let mut cmd = fx.cmd(["query", "--expanded=auto", "--debug", QUERY]);
 
     // WHEN
     // THEN
     assert_cmd_snapshot!(cmd, @r"
-    success: false
-    exit_code: 1
+    success: true
+    exit_code: 0
     ----- stdout -----
+    DEBUG INFO
+
+    command:                    query
+    display results via pager:  false
+    benchmark:                  false
+    print query:                false
+    write results:              false
+    expanded display:           auto
+
+    query:
+    ---
+    MATCH (c: Candidate) RETURN c.id LIMIT 5
+    ---
 
     ----- stderr -----
-    Error: cannot benchmark and write results at the same time
     ");
 }
 
 #[test]
-fn fails_if_incorrect_results_format_provided() {
+fn debug_flag_works_for_display_limit_flags() {
     // GIVEN
     let fx = Fixture::new();
     let mut cmd = fx.cmd([
         "query",
-        "--write-results",
-        "--results-format",
-        "unknown",
+        "--max-column-width",
+        "40",
+        "--overflow",
+        "wrap",
+        "--max-rows",
+        "50",
+        "--debug",
         QUERY,
     ]);
 
     // WHEN
     // THEN
     assert_cmd_snapshot!(cmd, @r"
-    success: false
-    exit_code: 2
+    success: true
+    exit_code: 0
     ----- stdout -----
+    DEBUG INFO
+
+    command:                    query
+    display results via pager:  false
+    benchmark:                  false
+    print query:                false
+    write results:              false
+    max column width:           40
+    overflow:                   wrap
+    max rows:                   50
+
+    query:
+    ---
+    MATCH (c: Candidate) RETURN c.id LIMIT 5
+    ---
 
     ----- stderr -----
-    error: invalid value 'unknown' for '--results-format <FORMAT>'
-      [possible values: csv, json]
+    ");
+}
+
+#[test]
+fn fails_if_max_rows_is_zero() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["query", "--max-rows", "0", QUERY]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: invalid value '0' for '--max-rows <NUMBER>': 0 is not in 1..=4294967295
+
+    For more information, try '--help'.
+    ");
+}
+
+#[test]
+fn debug_flag_works_for_flatten_flag() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "query",
+        "--write-results",
+        "--results-format",
+        "csv",
+        "--flatten=explode",
+        "--debug",
+        QUERY,
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    DEBUG INFO
+
+    command:                    query
+    display results via pager:  false
+    benchmark:                  false
+    print query:                false
+    write results:              true
+    results directory:          .grafq
+    results format:             csv
+    flatten (arrays):           explode
+
+    query:
+    ---
+    MATCH (c: Candidate) RETURN c.id LIMIT 5
+    ---
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn debug_flag_works_for_several_queries() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "query",
+        "--bench",
+        "--debug",
+        QUERY,
+        "@queries/candidates.cypher",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    DEBUG INFO
+
+    command:                    query
+    display results via pager:  false
+    benchmark:                  true
+    benchmark num runs:         5
+    benchmark num warmup runs:  3
+    print query:                false
+    write results:              false
+
+    queries:
+    ---
+    MATCH (c: Candidate) RETURN c.id LIMIT 5
+    ---
+    @queries/candidates.cypher
+    ---
+
+    ----- stderr -----
+    ");
+}
+
+//-------------//
+//  FAILURES   //
+//-------------//
+
+#[test]
+fn fails_if_provided_with_no_db_uri() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["query", QUERY]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r#"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: couldn't build db client
+
+    Caused by:
+        DB_URI is not set
+
+    grafq requires the environment variable DB_URI to be set (or a profile with "uri"
+    to be selected via --profile).
+
+    - For an AWS Neptune database, use the https scheme. Neptune uses IAM
+        authentication, so ensure your AWS credentials are configured correctly (via
+        environment variables or the AWS shared config file):
+
+        DB_URI="https://abc.xyz.us-east-1.neptune.amazonaws.com:8182"
+
+    - For a Neo4j database, use the bolt scheme and provide authentication details:
+
+        DB_URI="bolt://127.0.0.1:7687"
+        NEO4J_USER="neo4j"
+        NEO4J_PASSWORD="your-password"
+        NEO4J_DB="neo4j"
+    "#);
+}
+
+#[test]
+fn fails_if_provided_with_invalid_db_uri_scheme() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["query", QUERY]);
+    cmd.env("DB_URI", "invalid://abc.xyz:8182");
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r#"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: couldn't build db client
+
+    Caused by:
+        DB_URI has unsupported scheme: "invalid"
+
+    Only 'bolt' and 'https' schemes are supported by grafq.
+    Use bolt for neo4j, and https for AWS Neptune.
+    "#);
+}
+
+#[test]
+fn fails_if_not_provided_with_neo4j_auth() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["query", QUERY]);
+    cmd.env("DB_URI", "bolt://127.0.0.1:7687");
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r#"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: couldn't build db client
+
+    Caused by:
+        environment variable "NEO4J_USER" is missing
+
+    The environment variables NEO4J_USER, NEO4J_PASSWORD, and NEO4J_DB need to be set when connecting
+    to a neo4j database (which was determined by the bolt scheme in DB_URI).
+
+    These can also be provided via the neo4j_user, neo4j_password, and neo4j_db keys of a profile.
+    "#);
+}
+
+#[test]
+fn fails_if_provided_with_incorrect_benchmark_num_runs() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["query", "--bench", "--bench-num-runs", "0", QUERY]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: invalid value '0' for '--bench-num-runs <NUMBER>': needs to be greater than 0
+
+    For more information, try '--help'.
+    ");
+}
+
+#[test]
+fn fails_if_both_benchmark_and_write_flags_are_provided() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["query", "--bench", "--write-results", QUERY]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: cannot benchmark and write results at the same time
+    ");
+}
+
+#[test]
+fn fails_if_several_queries_are_provided_without_benchmark_flag() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["query", QUERY, QUERY]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: several queries can only be provided when benchmarking
+    ");
+}
+
+#[test]
+fn fails_if_several_queries_are_provided_for_a_load_test() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["query", "--bench", "--concurrency", "4", QUERY, QUERY]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: load tests, benchmark reports and baselines only apply to a single query
+    ");
+}
+
+#[test]
+fn fails_if_benchmark_params_are_provided_for_a_load_test() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "query",
+        "--bench",
+        "--bench-params",
+        "params.csv",
+        "--concurrency",
+        "4",
+        QUERY,
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: parameter sets aren't supported for load tests or query comparisons
+    ");
+}
+
+#[test]
+fn fails_if_output_is_provided_while_writing_results() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["query", "--write-results", "--output", "results.csv", QUERY]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: the argument '--write-results' cannot be used with '--output <FILE>'
+
+    Usage: grafq query --write-results <QUERY>...
+
+    For more information, try '--help'.
+    ");
+}
+
+#[test]
+fn fails_if_binary_results_are_to_be_paged() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["query", "--page-results", "-f", "parquet", QUERY]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: results in a binary format (parquet, arrow) cannot be displayed via a pager
+    ");
+}
+
+#[test]
+fn fails_if_benchmark_params_file_has_unsupported_extension() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["query", "--bench", "--bench-params", "params.txt", QUERY]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: parameter sets file needs to have a .csv, .ndjson or .jsonl extension: params.txt
+    ");
+}
+
+#[test]
+fn fails_if_both_explain_and_benchmark_flags_are_provided() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["query", "--explain", "--bench", QUERY]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: the argument '--explain[=<MODE>]' cannot be used with '--bench'
+
+    Usage: grafq query --explain[=<MODE>] <QUERY>...
+
+    For more information, try '--help'.
+    ");
+}
+
+#[test]
+fn fails_if_benchmark_output_is_provided_without_benchmark_flag() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["query", "--bench-output", "report.json", QUERY]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: the following required arguments were not provided:
+      --bench
+
+    Usage: grafq query --bench --bench-output <FILE> <QUERY>...
+
+    For more information, try '--help'.
+    ");
+}
+
+#[test]
+fn fails_if_incorrect_results_format_provided() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "query",
+        "--write-results",
+        "--results-format",
+        "unknown",
+        QUERY,
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: invalid value 'unknown' for '--results-format <FORMAT>'
+      [possible values: csv, json, ndjson, tsv, parquet, arrow, markdown, html]
+
+    For more information, try '--help'.
+    ");
+}
+
+#[test]
+fn uses_db_uri_from_profile() {
+    // GIVEN
+    let fx = Fixture::new();
+    fx.write_config(
+        r#"
+[profiles.dev]
+uri = "invalid://abc.xyz:8182"
+"#,
+    );
+    let mut cmd = fx.cmd(["query", "--profile", "dev", QUERY]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r#"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: couldn't build db client
+
+    Caused by:
+        DB_URI has unsupported scheme: "invalid"
+
+    Only 'bolt' and 'https' schemes are supported by grafq.
+    Use bolt for neo4j, and https for AWS Neptune.
+    "#);
+}
+
+#[test]
+fn env_vars_override_profile_values() {
+    // GIVEN
+    let fx = Fixture::new();
+    fx.write_config(
+        r#"
+[profiles.dev]
+uri = "bolt://127.0.0.1:7687"
+"#,
+    );
+    let mut cmd = fx.cmd(["query", "--profile", "dev", QUERY]);
+    cmd.env("DB_URI", "invalid://abc.xyz:8182");
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r#"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: couldn't build db client
+
+    Caused by:
+        DB_URI has unsupported scheme: "invalid"
+
+    Only 'bolt' and 'https' schemes are supported by grafq.
+    Use bolt for neo4j, and https for AWS Neptune.
+    "#);
+}
+
+#[test]
+fn fails_if_profile_doesnt_exist() {
+    // GIVEN
+    let fx = Fixture::new();
+    fx.write_config(
+        r#"
+[profiles.dev]
+uri = "bolt://127.0.0.1:7687"
+"#,
+    );
+    let mut cmd = fx.cmd(["query", "--profile", "prod", QUERY]);
+
+    // WHEN
+    // THEN
+    insta::with_settings!({
+        filters => vec![(r"reads profiles from .*grafq\.toml", "reads profiles from [CONFIG_FILE]")]
+    }, {
+        assert_cmd_snapshot!(cmd, @r#"
+        success: false
+        exit_code: 1
+        ----- stdout -----
+
+        ----- stderr -----
+        Error: couldn't load profile
+
+        Caused by:
+            profile "prod" not found
+
+        grafq reads profiles from [CONFIG_FILE]. A profile looks like this:
+
+        [profiles.dev]
+        uri = "bolt://127.0.0.1:7687"
+        neo4j_user = "neo4j"
+        neo4j_password = "your-password"
+        neo4j_db = "neo4j"
+
+        [profiles.prod]
+        uri = "https://abc.xyz.us-east-1.neptune.amazonaws.com:8182"
+        aws_region = "us-east-1"
+        aws_profile = "prod"
+        "#);
+    });
+}
+
+#[test]
+fn fails_if_profile_is_malformed() {
+    // GIVEN
+    let fx = Fixture::new();
+    fx.write_config(
+        r#"
+[profiles.dev]
+url = "bolt://127.0.0.1:7687"
+"#,
+    );
+    let mut cmd = fx.cmd(["query", "--profile", "dev", QUERY]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r#"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: couldn't load profile
+
+    Caused by:
+        0: profile "dev" is malformed
+        1: unknown field `url`, expected one of `uri`, `neo4j_user`, `neo4j_password`, `neo4j_db`, `aws_region`, `aws_profile`, `results_dir`, `results_format`
+           
+
+    A profile supports the following keys: uri, neo4j_user, neo4j_password, neo4j_db, aws_region,
+    aws_profile, results_dir, results_format.
+    "#);
+}
+
+#[test]
+fn fails_if_query_param_is_malformed() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["query", "--param", "limit", QUERY]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: invalid value 'limit' for '--param <KEY=VALUE>': parameter needs to be in the form key=value
 
     For more information, try '--help'.
     ");
@@ -29,8 +29,9 @@ fn shows_help() {
       help     Print this message or the help of the given subcommand(s)
 
     Options:
-          --debug  Output debug information without doing anything
-      -h, --help   Print help
+          --debug           Output debug information without doing anything
+          --profile <NAME>  Profile (from grafq's config file) to use; environment variables override its values
+      -h, --help            Print help
 
     ----- stderr -----
     ");